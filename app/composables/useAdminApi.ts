@@ -1,5 +1,5 @@
-import type { ConfigFileWithContent, Manifest } from '~/types'
-import { getErrorMessage, withNetworkRetry } from '~/utils/errorHandler'
+import type { ConfigFileWithContent, Manifest, MissingDependency, OverrideFileWithContent } from '~/types'
+import { describeError, getErrorMessage, withNetworkRetry } from '~/utils/errorHandler'
 import { resolveModpackKey } from '~/utils/modpackKey'
 
 /**
@@ -16,14 +16,17 @@ export function useAdminApi()
 
 	const {
 		selectFile,
+		selectDirectory,
 		selectSaveFile,
 		selectMultipleFiles,
 		readDirectoryRecursive,
+		readOverridesDirectory,
 		writeFile,
 		parseMinecraftInstance,
 		compareManifests,
 		readFile,
-		isBinaryFile
+		isBinaryFile,
+		exportServerPack
 	} = useTauri()
 
 	/**
@@ -63,6 +66,13 @@ export function useAdminApi()
 			{
 				const diff = await compareManifests(manifestStore.previousManifest, parsed)
 				manifestStore.setUpdateInfo(diff)
+				if (diff != null && diff.missingDependencies.length > 0)
+				{
+					setStatus(
+						`Manifest generated, but removed addons are still required: ${describeMissingDependencies(diff.missingDependencies)}`,
+						'warning'
+					)
+				}
 			}
 			else
 			{
@@ -178,7 +188,7 @@ export function useAdminApi()
 		}
 		catch (err)
 		{
-			setStatus(`Failed to read config files: ${err instanceof Error ? err.message : 'Unknown error'}`, 'error')
+			setStatus(`Failed to read config files: ${describeError(err, 'Unknown error')}`, 'error')
 			return []
 		}
 	}
@@ -229,23 +239,59 @@ export function useAdminApi()
 		}
 		catch (err)
 		{
-			setStatus(`Failed to read config files from directory: ${err instanceof Error ? err.message : 'Unknown error'}`, 'error')
+			setStatus(`Failed to read config files from directory: ${describeError(err, 'Unknown error')}`, 'error')
 			return []
 		}
 	}
 
 	/**
-   * Upload manifest and config files to GitHub
+   * Select a directory laid out like the instance root and read every file in it as an override
+   */
+	async function selectOverridesDirectory(
+		setStatus: (message: string, type: 'success' | 'error' | 'info' | 'warning') => void
+	): Promise<OverrideFileWithContent[]>
+	{
+		const dirPath = await selectDirectory()
+		if (dirPath == null || dirPath.length === 0)
+		{
+			setStatus('No directory selected.', 'warning')
+			return []
+		}
+
+		try
+		{
+			setStatus('Reading override files...', 'info')
+			const overrides = await readOverridesDirectory(dirPath)
+			if (overrides.length === 0)
+			{
+				setStatus('No files found in the selected directory.', 'warning')
+				return []
+			}
+
+			setStatus(`Added ${overrides.length} override file(s) from directory.`, 'success')
+			return overrides
+		}
+		catch (err)
+		{
+			setStatus(`Failed to read override files: ${describeError(err, 'Unknown error')}`, 'error')
+			return []
+		}
+	}
+
+	/**
+   * Upload manifest, config files and override files to GitHub
    */
 	async function uploadToGithub(
 		manifest: Manifest | null,
 		configFiles: ConfigFileWithContent[],
+		overrides: OverrideFileWithContent[],
+		removedConfigFiles: string[],
 		customModpackName: string,
 		onProgress: (progress: number, message?: string) => void,
 		setStatus: (message: string, type: 'success' | 'error' | 'info' | 'warning') => void
 	): Promise<{ success: boolean, updateReference?: string }>
 	{
-		if (manifest == null && configFiles.length === 0)
+		if (manifest == null && configFiles.length === 0 && overrides.length === 0 && removedConfigFiles.length === 0)
 		{
 			return { success: false }
 		}
@@ -294,7 +340,11 @@ export function useAdminApi()
 					config_files: configFiles.map((cf) => ({
 						filename: cf.filename,
 						relative_path: cf.relative_path
-					}))
+					})),
+					removed_config_files: removedConfigFiles,
+					gameVersion: manifest.gameVersion,
+					baseModLoader: manifest.baseModLoader,
+					baseModpack: manifest.baseModpack
 				}
 			}
 			else
@@ -309,7 +359,23 @@ export function useAdminApi()
 					config_files: configFiles.map((cf) => ({
 						filename: cf.filename,
 						relative_path: cf.relative_path
-					}))
+					})),
+					removed_config_files: removedConfigFiles
+				}
+			}
+
+			// Excluding a library other addons need would break the pack for every user
+			if (manifest !== null)
+			{
+				const check = await compareManifests(manifest, manifestWithConfig)
+				if (check != null && check.missingDependencies.length > 0)
+				{
+					logger.error('Upload blocked by missing dependencies', { missing: check.missingDependencies })
+					setStatus(
+						`Excluded addons are still required: ${describeMissingDependencies(check.missingDependencies)}. Include them or exclude the addons that need them.`,
+						'error'
+					)
+					return { success: false }
 				}
 			}
 
@@ -324,6 +390,7 @@ export function useAdminApi()
 					modpackKey,
 					manifest: manifestWithConfig,
 					configFiles,
+					overrides,
 					onProgress: (p, msg) =>
 					{
 						onProgress(p, msg)
@@ -347,15 +414,86 @@ export function useAdminApi()
 		}
 	}
 
+	/**
+   * Export a ready-to-run server pack zip with the server-side addons, configs and overrides
+   */
+	async function exportServerPackZip(
+		manifest: Manifest | null,
+		configFiles: ConfigFileWithContent[],
+		overrides: OverrideFileWithContent[],
+		setStatus: (message: string, type: 'success' | 'error' | 'info' | 'warning') => void
+	): Promise<boolean>
+	{
+		if (manifest == null)
+		{
+			return false
+		}
+
+		const dirPath = await selectDirectory()
+		if (dirPath == null || dirPath.length === 0)
+		{
+			setStatus('No directory selected.', 'warning')
+			return false
+		}
+
+		const excludedSet = manifestStore.excludedAddons
+		const serverManifest: Manifest = {
+			...manifest,
+			mods: manifest.mods.filter((m) => !excludedSet.has(m.addon_name)),
+			resourcepacks: manifest.resourcepacks.filter((r) => !excludedSet.has(r.addon_name)),
+			shaderpacks: manifest.shaderpacks.filter((s) => !excludedSet.has(s.addon_name)),
+			datapacks: manifest.datapacks.filter((d) => !excludedSet.has(d.addon_name)),
+			config_files: configFiles.map((cf) => ({
+				filename: cf.filename,
+				relative_path: cf.relative_path
+			})),
+			overrides: overrides.map(({ content: _content, ...file }) => file)
+		}
+
+		try
+		{
+			setStatus('Exporting server pack...', 'info')
+			const result = await exportServerPack(serverManifest, configFiles, overrides, {
+				outputPath: `${dirPath}/server-pack-${Date.now()}.zip`,
+				zip: true
+			})
+			if (result.skipped_addons.length > 0)
+			{
+				logger.info('Client-only addons left out of the server pack', { skipped: result.skipped_addons })
+			}
+			setStatus(
+				`Server pack saved as ${result.output_path} (${result.addon_count} addons, ${result.config_count} configs, ${result.override_count} overrides, ${result.skipped_addons.length} client-only addons left out).`,
+				'success'
+			)
+			return true
+		}
+		catch (error)
+		{
+			setStatus(getErrorMessage(error, 'server pack export'), 'error')
+			logger.error('Server pack export failed', { error })
+			return false
+		}
+	}
+
 	return {
 		loadInstance,
 		saveManifest,
 		selectConfigFiles,
 		scanDirectoryForConfigFiles,
-		uploadToGithub
+		selectOverridesDirectory,
+		uploadToGithub,
+		exportServerPackZip
 	}
 }
 
+/**
+ * List missing dependencies as "Addon needs Dependency"
+ */
+function describeMissingDependencies(missing: MissingDependency[]): string
+{
+	return missing.map((m) => `${m.addon_name} needs ${m.dependency_name}`).join(', ')
+}
+
 /**
  * Calculate relative path for a config file based on its location
  */
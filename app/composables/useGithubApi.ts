@@ -4,7 +4,7 @@ import { listen, type UnlistenFn } from '@tauri-apps/api/event'
 
 import { useCache } from './useCache'
 
-import type { ConfigFileWithContent, Manifest } from '~/types'
+import type { ConfigFileWithContent, Manifest, OverrideFileWithContent } from '~/types'
 
 export interface GithubProgress
 {
@@ -16,6 +16,7 @@ interface CachedGitHubData
 {
 	manifest: Manifest
 	configFiles: ConfigFileWithContent[]
+	overrides: OverrideFileWithContent[]
 	uploadedAt?: number
 	downloadedAt?: number
 }
@@ -23,7 +24,7 @@ interface CachedGitHubData
 export const useGithubApi = () =>
 {
 	// Bump namespace when download path logic changes so in-memory cache cannot mask fixes after app update.
-	const cache = useCache<CachedGitHubData>('github-v2', 600000) // 10 minutes
+	const cache = useCache<CachedGitHubData>('github-v3', 600000) // 10 minutes
 	const { $logger: logger } = useNuxtApp()
 
 	/**
@@ -36,6 +37,7 @@ export const useGithubApi = () =>
 		modpackKey?: string
 		manifest: Manifest
 		configFiles: ConfigFileWithContent[]
+		overrides?: OverrideFileWithContent[]
 		onProgress?: (progress: number, message?: string) => void
 	}): Promise<void> =>
 	{
@@ -59,7 +61,8 @@ export const useGithubApi = () =>
 				uuid: opts.uuid,
 				modpackKey: opts.modpackKey,
 				manifest: opts.manifest,
-				configFiles: opts.configFiles
+				configFiles: opts.configFiles,
+				overrides: opts.overrides ?? null
 			})
 
 			// Cache the uploaded manifest for potential re-use
@@ -67,6 +70,7 @@ export const useGithubApi = () =>
 			cache.set(cacheKey, {
 				manifest: opts.manifest,
 				configFiles: opts.configFiles,
+				overrides: opts.overrides ?? [],
 				uploadedAt: Date.now()
 			})
 
@@ -77,7 +81,8 @@ export const useGithubApi = () =>
 				uuid: opts.uuid,
 				duration: `${duration.toFixed(2)}ms`,
 				manifestSize: JSON.stringify(opts.manifest).length,
-				configFileCount: opts.configFiles.length
+				configFileCount: opts.configFiles.length,
+				overrideCount: opts.overrides?.length ?? 0
 			})
 		}
 		finally
@@ -98,7 +103,7 @@ export const useGithubApi = () =>
 		uuid: string
 		modpackKey?: string
 		onProgress?: (progress: number, message?: string) => void
-	}): Promise<{ manifest: Manifest, configFiles: ConfigFileWithContent[] }> =>
+	}): Promise<{ manifest: Manifest, configFiles: ConfigFileWithContent[], overrides: OverrideFileWithContent[] }> =>
 	{
 		const cacheKey = `${opts.repo}-${opts.modpackKey ?? 'legacy'}-${opts.uuid}`
 		const startTime = performance.now()
@@ -111,7 +116,8 @@ export const useGithubApi = () =>
 			if (typeof opts.onProgress === 'function') opts.onProgress(100, 'Using cached data')
 			return {
 				manifest: cached.manifest,
-				configFiles: cached.configFiles
+				configFiles: cached.configFiles,
+				overrides: cached.overrides
 			}
 		}
 
@@ -121,17 +127,19 @@ export const useGithubApi = () =>
 			repo: opts.repo,
 			uuid: opts.uuid,
 			modpackKey: opts.modpackKey
-		}) as { manifest: Manifest, config_files: ConfigFileWithContent[] }
+		}) as { manifest: Manifest, config_files: ConfigFileWithContent[], overrides: OverrideFileWithContent[] }
 
 		const downloadResult = {
 			manifest: result.manifest,
-			configFiles: result.config_files
+			configFiles: result.config_files,
+			overrides: result.overrides
 		}
 
 		// Cache the result
 		cache.set(cacheKey, {
 			manifest: result.manifest,
 			configFiles: result.config_files,
+			overrides: result.overrides,
 			downloadedAt: Date.now()
 		})
 
@@ -142,7 +150,8 @@ export const useGithubApi = () =>
 			uuid: opts.uuid,
 			duration: `${duration.toFixed(2)}ms`,
 			manifestSize: JSON.stringify(result.manifest).length,
-			configFileCount: result.config_files.length
+			configFileCount: result.config_files.length,
+			overrideCount: result.overrides.length
 		})
 
 		if (typeof opts.onProgress === 'function') opts.onProgress(100, 'Download complete')
@@ -191,5 +200,27 @@ export const useGithubApi = () =>
 		return configFiles
 	}
 
-	return { uploadUpdate, downloadUpdate, downloadManifest, downloadConfigFiles }
+	/**
+	 * Downloads the override files listed in the manifest, checked against their hashes.
+	 */
+	const downloadOverrides = async (opts: {
+		repo: string
+		uuid: string
+		modpackKey?: string
+		manifest: Manifest
+		onProgress?: (progress: number, message?: string) => void
+	}): Promise<OverrideFileWithContent[]> =>
+	{
+		if (typeof opts.onProgress === 'function') opts.onProgress(10, 'Downloading override files...')
+		const overrides = await invoke<OverrideFileWithContent[]>('download_overrides', {
+			repo: opts.repo,
+			uuid: opts.uuid,
+			modpackKey: opts.modpackKey,
+			manifest: opts.manifest
+		})
+		if (typeof opts.onProgress === 'function') opts.onProgress(100, 'Override files downloaded')
+		return overrides
+	}
+
+	return { uploadUpdate, downloadUpdate, downloadManifest, downloadConfigFiles, downloadOverrides }
 }
@@ -1,6 +1,6 @@
 import { invoke } from '@tauri-apps/api/core'
 
-import type { Addon, ConfigFileWithContent, Manifest, ManifestUpdateInfo, UpdateDiff } from '~/types'
+import type { Addon, CachePruneResult, Compatibility, ConfigFileWithContent, DownloadCacheInfo, InstallPlan, InstallReceipt, InstallResult, InstallSide, InstallUpdateOptions, Manifest, ManifestUpdateInfo, OverrideFileWithContent, PreflightReport, RepairOptions, RepairResult, ServerPackOptions, ServerPackResult, SnapshotInfo, UpdateDiff, VerifyReport } from '~/types'
 
 export const useTauri = () =>
 {
@@ -155,26 +155,191 @@ export const useTauri = () =>
 			console.error('[useTauri] openUrl failed:', { url, error })
 		}
 	}
+	const toInstallOptions = (options?: InstallUpdateOptions) =>
+		options !== undefined
+			? {
+				old_manifest: options.oldManifest ?? null,
+				cleanup_old: options.cleanupOld ?? true,
+				update_id: options.updateId ?? null,
+				max_concurrent_downloads: options.maxConcurrentDownloads ?? null,
+				transactional: options.transactional ?? null,
+				retry: options.retry ?? null,
+				use_cache: options.useCache ?? null,
+				snapshot: options.snapshot ?? null,
+				snapshot_retention: options.snapshotRetention ?? null,
+				merge_configs: options.mergeConfigs ?? null,
+				update_instance_file: options.updateInstanceFile ?? null,
+				allow_incompatible: options.allowIncompatible ?? null,
+				side: options.side ?? null,
+				preflight: options.preflight ?? null
+			}
+			: undefined
+
 	const installUpdate = async (
 		modpackPath: string,
 		manifest: Manifest,
 		configFiles: ConfigFileWithContent[],
-		options?: {
-			oldManifest?: Manifest | null
-			cleanupOld?: boolean
-		}
-	): Promise<void> =>
+		overrides: OverrideFileWithContent[],
+		options?: InstallUpdateOptions
+	): Promise<InstallResult> =>
+	{
+		return await invoke<InstallResult>('install_update', {
+			modpackPath,
+			manifest,
+			configFiles,
+			overrides,
+			options: toInstallOptions(options)
+		})
+	}
+
+	const planInstall = async (
+		modpackPath: string,
+		manifest: Manifest,
+		configFiles: ConfigFileWithContent[],
+		overrides: OverrideFileWithContent[],
+		options?: InstallUpdateOptions
+	): Promise<InstallPlan> =>
+	{
+		return await invoke<InstallPlan>('plan_install', {
+			modpackPath,
+			manifest,
+			configFiles,
+			overrides,
+			options: toInstallOptions(options)
+		})
+	}
+
+	/** Checks free space, write access and locked files for an install without changing anything. */
+	const preflightInstall = async (
+		modpackPath: string,
+		manifest: Manifest,
+		configFiles: ConfigFileWithContent[],
+		overrides: OverrideFileWithContent[],
+		options?: InstallUpdateOptions
+	): Promise<PreflightReport> =>
 	{
-		return await invoke('install_update', {
+		return await invoke<PreflightReport>('preflight_install', {
 			modpackPath,
 			manifest,
 			configFiles,
+			overrides,
+			options: toInstallOptions(options)
+		})
+	}
+
+	/** Asks a running install to stop. Resolves to false if no install is running. */
+	const cancelInstall = async (): Promise<boolean> =>
+	{
+		return await invoke<boolean>('cancel_install')
+	}
+
+	/** Asks a running upload to stop. Resolves to false if no upload is running. */
+	const cancelUpload = async (): Promise<boolean> =>
+	{
+		return await invoke<boolean>('cancel_upload')
+	}
+
+	const getDownloadCacheInfo = async (): Promise<DownloadCacheInfo> =>
+	{
+		return await invoke<DownloadCacheInfo>('get_download_cache_info')
+	}
+
+	/**
+	 * Removes entries unused for `maxAgeDays`, then the least recently used ones
+	 * until the cache fits in `maxBytes`. Without either limit the cache is cleared.
+	 */
+	const pruneDownloadCache = async (maxBytes?: number, maxAgeDays?: number): Promise<CachePruneResult> =>
+	{
+		return await invoke<CachePruneResult>('prune_download_cache', {
+			maxBytes: maxBytes ?? null,
+			maxAgeDays: maxAgeDays ?? null
+		})
+	}
+
+	/** Returns what CEMM last installed into the instance, or null if it never installed there. */
+	const getInstallReceipt = async (modpackPath: string): Promise<InstallReceipt | null> =>
+	{
+		return await invoke<InstallReceipt | null>('get_install_receipt', { modpackPath })
+	}
+
+	const listSnapshots = async (modpackPath?: string): Promise<SnapshotInfo[]> =>
+	{
+		return await invoke<SnapshotInfo[]>('list_snapshots', { modpackPath: modpackPath ?? null })
+	}
+
+	const restoreSnapshot = async (snapshotId: string): Promise<SnapshotInfo> =>
+	{
+		return await invoke<SnapshotInfo>('restore_snapshot', { snapshotId })
+	}
+
+	const deleteSnapshot = async (snapshotId: string): Promise<void> =>
+	{
+		await invoke('delete_snapshot', { snapshotId })
+	}
+
+	/** Keeps the newest `keep` snapshots of an instance and returns how many were deleted. */
+	const pruneSnapshots = async (modpackPath: string, keep: number): Promise<number> =>
+	{
+		return await invoke<number>('prune_snapshots', { modpackPath, keep })
+	}
+
+	/** Checks every addon and config file of the manifest in the instance without changing anything. */
+	const verifyInstance = async (modpackPath: string, manifest: Manifest, side?: InstallSide): Promise<VerifyReport> =>
+	{
+		return await invoke<VerifyReport>('verify_instance', { modpackPath, manifest, side: side ?? null })
+	}
+
+	/** Compares the Minecraft version, mod loader and base modpack of a manifest with the instance. */
+	const checkInstanceCompatibility = async (modpackPath: string, manifest: Manifest): Promise<Compatibility> =>
+	{
+		return await invoke<Compatibility>('check_instance_compatibility', { modpackPath, manifest })
+	}
+
+	/** Re-downloads or fixes only the broken files of an instance. */
+	const repairInstance = async (
+		modpackPath: string,
+		manifest: Manifest,
+		configFiles?: ConfigFileWithContent[],
+		options?: RepairOptions,
+		overrides?: OverrideFileWithContent[]
+	): Promise<RepairResult> =>
+	{
+		return await invoke<RepairResult>('repair_instance', {
+			modpackPath,
+			manifest,
+			configFiles: configFiles ?? null,
+			overrides: overrides ?? null,
 			options: options !== undefined
 				? {
-					old_manifest: options.oldManifest ?? null,
-					cleanup_old: options.cleanupOld ?? (options.oldManifest !== null && options.oldManifest !== undefined)
+					remove_extra_files: options.removeExtraFiles ?? false,
+					max_concurrent_downloads: options.maxConcurrentDownloads ?? null,
+					retry: options.retry ?? null,
+					use_cache: options.useCache ?? null,
+					side: options.side ?? null
 				}
-				: undefined
+				: null
+		})
+	}
+
+	/** Builds a ready-to-run server directory or zip with the server-side addons, configs and overrides. */
+	const exportServerPack = async (
+		manifest: Manifest,
+		configFiles: ConfigFileWithContent[],
+		overrides: OverrideFileWithContent[],
+		options: ServerPackOptions
+	): Promise<ServerPackResult> =>
+	{
+		return await invoke<ServerPackResult>('export_server_pack', {
+			manifest,
+			configFiles,
+			overrides,
+			options: {
+				output_path: options.outputPath,
+				zip: options.zip ?? false,
+				max_concurrent_downloads: options.maxConcurrentDownloads ?? null,
+				retry: options.retry ?? null,
+				use_cache: options.useCache ?? null
+			}
 		})
 	}
 
@@ -217,14 +382,16 @@ export const useTauri = () =>
 					...newManifest.resourcepacks.map((addon) => addon.addon_name),
 					...newManifest.shaderpacks.map((addon) => addon.addon_name),
 					...newManifest.datapacks.map((addon) => addon.addon_name)
-				]
+				],
+				toggled: []
 			}
 		}
 
 		const diff: UpdateDiff = {
 			removed_addons: [],
 			updated_addon_ids: [],
-			new_addons: []
+			new_addons: [],
+			toggled: []
 		}
 
 		// Helper function to process addon categories
@@ -240,6 +407,20 @@ export const useTauri = () =>
 				}
 			}
 
+			// Find toggled addons (enabled or disabled, kept on disk as .disabled)
+			for (const oldAddon of oldAddons)
+			{
+				const newAddon = newAddons.find((addon) => addon.addon_project_id === oldAddon.addon_project_id)
+				if (newAddon !== undefined && (oldAddon.disabled === true) !== (newAddon.disabled === true))
+				{
+					diff.toggled.push({
+						addon_project_id: newAddon.addon_project_id,
+						addon_name: newAddon.addon_name,
+						disabled: newAddon.disabled === true
+					})
+				}
+			}
+
 			// Find updated addons (same project ID, different version)
 			// Store project_id for reliable matching during removal
 			for (const oldAddon of oldAddons)
@@ -323,6 +504,12 @@ export const useTauri = () =>
 		}
 	}
 
+	/** Reads every file below `dirPath` as an override file, paths relative to `dirPath`. */
+	const readOverridesDirectory = async (dirPath: string): Promise<OverrideFileWithContent[]> =>
+	{
+		return await invoke<OverrideFileWithContent[]>('read_overrides_directory', { dirPath })
+	}
+
 	const validatePath = async (path: string): Promise<{
 		exists: boolean
 		is_directory?: boolean
@@ -365,6 +552,21 @@ export const useTauri = () =>
 		openCurseforgeUrl,
 		openUrl,
 		installUpdate,
+		planInstall,
+		preflightInstall,
+		cancelInstall,
+		cancelUpload,
+		getDownloadCacheInfo,
+		pruneDownloadCache,
+		getInstallReceipt,
+		listSnapshots,
+		restoreSnapshot,
+		deleteSnapshot,
+		pruneSnapshots,
+		verifyInstance,
+		repairInstance,
+		exportServerPack,
+		checkInstanceCompatibility,
 		keyringTestDirect,
 		keyringSetAndVerify,
 		loadExistingManifest,
@@ -373,6 +575,7 @@ export const useTauri = () =>
 		downloadConfigFiles,
 		selectConfigDirectory,
 		readDirectoryRecursive,
+		readOverridesDirectory,
 		validatePath
 	}
 }
@@ -1,5 +1,5 @@
-import type { ConfigFileWithContent, Manifest } from '~/types'
-import { getErrorMessage, withNetworkRetry } from '~/utils/errorHandler'
+import type { ConfigFileWithContent, Manifest, OverrideFileWithContent } from '~/types'
+import { describeError, getErrorMessage, withNetworkRetry } from '~/utils/errorHandler'
 import { resolveModpackKey } from '~/utils/modpackKey'
 
 /**
@@ -8,10 +8,10 @@ import { resolveModpackKey } from '~/utils/modpackKey'
  */
 export function useUserApi()
 {
-	const { downloadManifest, downloadConfigFiles: apiDownloadConfigFiles } = useGithubApi()
+	const { downloadManifest, downloadConfigFiles: apiDownloadConfigFiles, downloadOverrides: apiDownloadOverrides } = useGithubApi()
 	const appStore = useAppStore()
 	const manifestStore = useManifestStore()
-	const { writeFile, readFile, parseMinecraftInstance, installUpdate: installUpdateTauri } = useTauri()
+	const { writeFile, readFile, parseMinecraftInstance, installUpdate: installUpdateTauri, preflightInstall } = useTauri()
 	const { $logger: logger } = useNuxtApp()
 
 	/**
@@ -132,13 +132,46 @@ export function useUserApi()
 		}
 		catch (err)
 		{
-			const errorMessage = err instanceof Error ? err.message : 'Failed to download config files'
+			const errorMessage = describeError(err, 'Failed to download config files')
 			setStatus(errorMessage, 'error')
 			logger.error('Failed to download config files', { error: err, updateInput, repo: appStore.githubRepo })
 			return { success: false, configFiles: [] }
 		}
 	}
 
+	/**
+   * Download the override files of the update. They are written by installUpdate.
+   */
+	async function downloadOverrides(
+		updateInput: string,
+		manifest: Manifest,
+		onProgress: (progress: number, message?: string) => void,
+		setStatus: (message: string, type: 'success' | 'error' | 'info' | 'warning') => void
+	): Promise<{ success: boolean, overrides: OverrideFileWithContent[] }>
+	{
+		try
+		{
+			const resolvedQuery = await resolveUpdateQuery(updateInput)
+
+			const overrides = await apiDownloadOverrides({
+				repo: appStore.githubRepo,
+				uuid: resolvedQuery.updateId,
+				modpackKey: resolvedQuery.modpackKey,
+				manifest,
+				onProgress
+			})
+
+			setStatus(`Downloaded ${overrides.length} override file(s)`, 'success')
+			return { success: true, overrides }
+		}
+		catch (err)
+		{
+			setStatus(describeError(err, 'Failed to download override files'), 'error')
+			logger.error('Failed to download override files', { error: err, updateInput, repo: appStore.githubRepo })
+			return { success: false, overrides: [] }
+		}
+	}
+
 	async function resolveUpdateQuery(updateInput: string): Promise<{ updateId: string, modpackKey?: string }>
 	{
 		const trimmed = updateInput.trim().replace(/\\/g, '/')
@@ -196,6 +229,7 @@ export function useUserApi()
 	async function installUpdate(
 		manifest: Manifest,
 		configFiles: ConfigFileWithContent[],
+		overrides: OverrideFileWithContent[],
 		previousManifest: Manifest | null,
 		onProgress: (progress: number, message?: string) => void,
 		setStatus: (message: string, type: 'success' | 'error' | 'info' | 'warning') => void
@@ -218,15 +252,43 @@ export function useUserApi()
 				}
 			}
 
-			await installUpdateTauri(
-				appStore.modpackPath,
-				manifest,
-				configFiles,
-				{
-					oldManifest: previousManifest,
-					cleanupOld: previousManifest !== null
-				}
-			)
+			const options = {
+				oldManifest: previousManifest,
+				cleanupOld: true,
+				side: appStore.installSide
+			}
+
+			// Report every problem at once instead of failing halfway through
+			const preflight = await preflightInstall(appStore.modpackPath, manifest, configFiles, overrides, options)
+			if (preflight.issues.length > 0)
+			{
+				logger.warn('Preflight checks failed', { issues: preflight.issues })
+				setStatus(
+					`Cannot install the update: ${preflight.issues.map((i) => i.message).join('. ')}`,
+					'error'
+				)
+				return false
+			}
+
+			const result = await installUpdateTauri(appStore.modpackPath, manifest, configFiles, overrides, options)
+
+			if (result.config_conflicts.length > 0)
+			{
+				logger.warn('Config merge conflicts', { conflicts: result.config_conflicts })
+				setStatus(
+					`Update installed. ${result.config_conflicts.length} config setting(s) you changed were also changed by the modpack and were replaced: `
+					+ result.config_conflicts.map((c) => `${c.relative_path}: ${c.key}`).join(', '),
+					'warning'
+				)
+				return true
+			}
+
+			if (result.compatibility_warnings.length > 0)
+			{
+				logger.warn('Modpack and instance differ', { warnings: result.compatibility_warnings })
+				setStatus(`Update installed. ${result.compatibility_warnings.join('. ')}`, 'warning')
+				return true
+			}
 
 			setStatus(
 				previousManifest !== null ? 'Update installation complete!' : 'Fresh installation complete!',
@@ -236,7 +298,7 @@ export function useUserApi()
 		}
 		catch (err)
 		{
-			setStatus(err instanceof Error ? err.message : 'Installation failed', 'error')
+			setStatus(describeError(err, 'Installation failed'), 'error')
 			logger.error('Installation failed', { error: err })
 			return false
 		}
@@ -295,7 +357,7 @@ export function useUserApi()
 		}
 		catch (err)
 		{
-			const errorMsg = err instanceof Error ? err.message : 'Unknown error generating previous manifest'
+			const errorMsg = describeError(err, 'Unknown error generating previous manifest')
 			logger.error('Failed to generate cemm-manifest_old.json', { error: errorMsg })
 			manifestStore.loadInstalledManifest(null)
 			return { success: false, error: errorMsg }
@@ -330,6 +392,7 @@ export function useUserApi()
 	return {
 		downloadFromGithub,
 		downloadConfigFiles,
+		downloadOverrides,
 		installUpdate,
 		generatePreviousManifest,
 		writeNewManifest
@@ -1,17 +1,21 @@
 import { defineStore } from 'pinia'
 
+import type { InstallSide } from '~/types'
+
 export const useAppStore = defineStore('app', () =>
 {
 	const mode = ref<'admin' | 'user'>('admin')
 	const githubRepo = ref('') // For modpack updates (e.g., "YassaaaTU/cemm-updates")
 	const appRepo = ref('YassaaaTU/cemm') // For app version updates (fixed)
 	const modpackPath = ref('')
+	const installSide = ref<InstallSide>('client') // 'server' skips client-only addons and configs
 
 	return {
 		mode,
 		githubRepo,
 		appRepo,
-		modpackPath
+		modpackPath,
+		installSide
 	}
 }, {
 	persist: {
@@ -1,6 +1,6 @@
 import { defineStore } from 'pinia'
 
-import type { Manifest, ManifestUpdateInfo } from '~/types'
+import type { AddonEnvironment, Manifest, ManifestUpdateInfo } from '~/types'
 
 export const useManifestStore = defineStore('manifest', () =>
 {
@@ -62,6 +62,20 @@ export const useManifestStore = defineStore('manifest', () =>
 		excludedAddons.value = new Set()
 	}
 
+	function setAddonEnvironment(projectId: number, environment: AddonEnvironment | undefined)
+	{
+		if (manifest.value === null) return
+		const categories = [manifest.value.mods, manifest.value.resourcepacks, manifest.value.shaderpacks, manifest.value.datapacks]
+		for (const addons of categories)
+		{
+			const addon = addons.find((a) => a.addon_project_id === projectId)
+			if (addon !== undefined)
+			{
+				addon.environment = environment
+			}
+		}
+	}
+
 	return {
 		manifest,
 		selectedAddons,
@@ -74,6 +88,7 @@ export const useManifestStore = defineStore('manifest', () =>
 		setUpdateInfo,
 		toggleExclusion,
 		isExcluded,
-		clearExclusions
+		clearExclusions,
+		setAddonEnvironment
 	}
 })
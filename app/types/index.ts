@@ -4,12 +4,60 @@ export interface Addon
 	addon_name: string
 	addon_project_id: number
 	cdn_download_url: string
-	mod_folder_path: string
+	mod_folder_path: string // install folder relative to the instance root; empty or absolute paths use the category folder
 	version: string
 	thumbnailUrl?: string // optional, for UI only
 	webSiteURL?: string // optional, CurseForge or homepage URL (always preserved)
 	disabled?: boolean // optional, true if .disabled file detected
 	fileNameOnDisk: string // exact filename on disk for reliable removal
+	fileLength?: number // expected size in bytes, used to reject truncated downloads
+	fileFingerprint?: number // CurseForge murmur2 fingerprint
+	hashes?: FileHash[] // file hashes reported by CurseForge
+	alternativeDownloadUrls?: string[] // mirrors tried when cdn_download_url fails
+	dependencies?: AddonDependency[] // CurseForge projects this file depends on
+	environment?: AddonEnvironment // side the addon is needed on, unknown addons are installed everywhere
+}
+
+export type AddonEnvironment = 'client' | 'server' | 'both'
+
+export type InstallSide = 'client' | 'server'
+
+/**
+ * An edge from an addon to a CurseForge project it depends on.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/composables/manifest.rs (AddonDependency struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface AddonDependency
+{
+	addon_project_id: number
+	kind: 'required' | 'optional'
+}
+
+/**
+ * A required dependency that an update removes, excludes or disables while an addon still needs it.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/composables/manifest.rs (MissingDependency struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface MissingDependency
+{
+	addon_name: string // the addon that needs the dependency
+	addon_project_id: number
+	dependency_name: string
+	dependency_project_id: number
+}
+
+export interface FileHash
+{
+	algorithm: 'sha1' | 'md5'
+	value: string
 }
 
 /**
@@ -26,6 +74,24 @@ export interface ConfigFile
 {
 	filename: string
 	relative_path: string
+	environment?: AddonEnvironment // overrides the side inferred from the file name (e.g. *-client.toml)
+	asset?: ReleaseAsset // set by upload_update for files too large for the repository tree
+}
+
+/**
+ * A file stored as a GitHub release asset because it is too large for the repository tree.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/composables/manifest.rs (ReleaseAsset struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface ReleaseAsset
+{
+	url: string // public download URL
+	size: number
+	sha256: string
 }
 
 /**
@@ -45,6 +111,38 @@ export interface ConfigFileWithContent extends ConfigFile
 	is_binary?: boolean // true if this is a binary file (content will be base64 data URI)
 }
 
+/**
+ * A file of any type installed as-is, relative to the instance root.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/composables/manifest.rs (OverrideFile struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface OverrideFile
+{
+	relative_path: string // forward slashes, e.g. 'kubejs/assets/logo.png'
+	size: number
+	sha256: string
+	environment?: AddonEnvironment // side the file is needed on, unknown files are installed everywhere
+	url?: string // release asset URL, set by upload_update for files too large for the repository tree
+}
+
+/**
+ * Override file with its content for upload, download and install.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/overrides.rs (OverrideFileWithContent struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface OverrideFileWithContent extends OverrideFile
+{
+	content: string // base64 of the file's bytes
+}
+
 export interface Manifest
 {
 	updateType?: 'full' | 'config' // 'full' = addons + config, 'config' = config only
@@ -53,6 +151,42 @@ export interface Manifest
 	shaderpacks: Addon[]
 	datapacks: Addon[]
 	config_files: ConfigFile[]
+	removed_config_files?: string[] // config files the update deletes, relative to the modpack directory
+	overrides?: OverrideFile[] // files of any type installed relative to the instance root
+	gameVersion?: string // Minecraft version the modpack targets, e.g. '1.20.1'
+	baseModLoader?: string // mod loader as named by CurseForge, e.g. 'forge-47.2.0'
+	baseModpack?: BaseModpack // CurseForge modpack version the update was built on
+}
+
+/**
+ * A CurseForge modpack version, from `installedModpack` in minecraftinstance.json.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/composables/manifest.rs (BaseModpack struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface BaseModpack
+{
+	project_id: number
+	file_id: number
+	version_name: string
+}
+
+/**
+ * Differences between the Minecraft version, mod loader and base modpack of a manifest and an instance.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/instance.rs (Compatibility struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface Compatibility
+{
+	mismatches: string[] // install_update refuses these unless allowIncompatible is set
+	warnings: string[]
 }
 
 export interface ManifestUpdateInfo
@@ -61,7 +195,10 @@ export interface ManifestUpdateInfo
 	timestamp: string
 	addedAddons: Addon[]
 	removedAddons: string[]
+	toggledAddons: string[] // enabled or disabled, installed as .disabled when disabled
 	configFiles: string[]
+	removedConfigFiles: string[]
+	missingDependencies: MissingDependency[] // required dependencies the new manifest drops
 }
 
 /**
@@ -78,6 +215,23 @@ export interface UpdateDiff
 	removed_addons: string[] // addon names to remove
 	updated_addon_ids: number[] // project IDs of addons that were updated (matched by project_id for reliability)
 	new_addons: string[] // completely new addon names
+	toggled: ToggledAddon[] // addons that were only enabled or disabled
+}
+
+/**
+ * An addon whose enabled state differs between two manifests.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/installer.rs (ToggledAddon struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface ToggledAddon
+{
+	addon_project_id: number
+	addon_name: string
+	disabled: boolean // the new state
 }
 
 export interface UpdatePreview
@@ -87,4 +241,409 @@ export interface UpdatePreview
 	diff: UpdateDiff
 	hasChanges: boolean
 	configFiles?: ConfigFileWithContent[]
+	overrides?: OverrideFileWithContent[]
+	compatibility?: Compatibility | null
+}
+
+export interface InstallUpdateOptions
+{
+	oldManifest?: Manifest | null // only used when the instance has no install receipt yet
+	cleanupOld?: boolean
+	updateId?: string // recorded in the install receipt
+	maxConcurrentDownloads?: number
+	transactional?: boolean // stage files and roll back on failure (default: true)
+	retry?: RetryPolicy
+	useCache?: boolean // serve addons from the shared download cache (default: true)
+	snapshot?: boolean // snapshot the instance before installing (default: true)
+	snapshotRetention?: number // snapshots kept per instance (default: 5)
+	mergeConfigs?: boolean // keep local config edits with a three-way merge (default: true)
+	updateInstanceFile?: boolean // record changed addons in minecraftinstance.json, backed up first (default: false)
+	allowIncompatible?: boolean // install despite a different Minecraft version or mod loader (default: false)
+	side?: InstallSide // skip addons and configs for the other side (default: 'client')
+	preflight?: boolean // check disk space, write access and locked files first (default: true)
+}
+
+/**
+ * Retry behaviour for failed HTTP requests.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/retry.rs (RetryPolicy struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface RetryPolicy
+{
+	max_retries?: number // retries after the first attempt (default: 3)
+	initial_delay_ms?: number // first backoff delay, doubled per retry (default: 500)
+	max_delay_ms?: number // cap for a single delay (default: 10000)
+}
+
+export type PlanAction = 'download' | 'overwrite' | 'delete' | 'rename' | 'skip'
+
+export type PlanReason =
+	| 'fresh_install'
+	| 'new_addon'
+	| 'updated_addon'
+	| 'missing_on_disk'
+	| 'unchanged'
+	| 'disabled'
+	| 'enabled'
+	| 'removed_addon'
+	| 'outdated_version'
+	| 'config_file'
+	| 'removed_config'
+	| 'override_file'
+	| 'removed_override'
+	| 'other_side'
+
+/**
+ * A single file in an install plan.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/installer.rs (PlannedFile struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface PlannedFile
+{
+	action: PlanAction
+	reason: PlanReason
+	category: string // 'mods' | 'resourcepacks' | 'shaderpacks' | 'datapacks' | 'config' | 'overrides'
+	name: string
+	relative_path: string
+	expected_size: number | null
+	renamed_from?: string // for renames, the current path relative to the modpack directory
+}
+
+/**
+ * Result of a dry-run install.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/installer.rs (InstallPlan struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface InstallPlan
+{
+	files: PlannedFile[]
+	total_download_bytes: number
+	unknown_size_downloads: number
+}
+
+export type PreflightIssueKind = 'insufficient_space' | 'not_writable' | 'file_locked'
+
+/**
+ * A problem that would make an install fail halfway through.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/preflight.rs (PreflightIssue struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface PreflightIssue
+{
+	kind: PreflightIssueKind
+	path: string | null // relative to the modpack directory, null for the whole volume
+	message: string
+}
+
+/**
+ * Result of preflight_install.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/preflight.rs (PreflightReport struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface PreflightReport
+{
+	required_bytes: number
+	available_bytes: number | null // null if the free space could not be determined
+	unknown_size_downloads: number
+	issues: PreflightIssue[] // empty if the install can go ahead
+}
+
+/**
+ * Size of the shared download cache.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/cache.rs (DownloadCacheInfo struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface DownloadCacheInfo
+{
+	path: string
+	file_count: number
+	total_bytes: number
+}
+
+/**
+ * Result of pruning the download cache.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/cache.rs (CachePruneResult struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface CachePruneResult
+{
+	removed_files: number
+	freed_bytes: number
+	remaining_bytes: number
+}
+
+/**
+ * A pre-install snapshot of an instance.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/snapshot.rs (SnapshotInfo struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface SnapshotInfo
+{
+	id: string
+	modpack_path: string
+	created_at: string // RFC 3339
+	file_count: number
+	total_bytes: number
+}
+
+/**
+ * A file CEMM installed, as recorded in the install receipt.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/receipt.rs (ReceiptFile struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface ReceiptFile
+{
+	relative_path: string
+	kind: 'addon' | 'config' | 'override'
+	sha256: string
+	size: number
+	addon_project_id?: number
+	addon_file_id?: number
 }
+
+/**
+ * Contents of `.cemm/installed.json` in an instance.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/receipt.rs (InstallReceipt struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface InstallReceipt
+{
+	update_id: string | null
+	manifest_hash: string
+	installed_at: string // RFC 3339
+	manifest: Manifest
+	files: ReceiptFile[]
+}
+
+/**
+ * Payload of the `install-cancelled` event.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/installer.rs (InstallCancelled struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface InstallCancelled
+{
+	message: string
+	rolled_back: boolean // true if the instance was left unchanged
+}
+
+/**
+ * A config setting that both the user and the update changed. The update's value is installed.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/merge.rs (ConfigConflict struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface ConfigConflict
+{
+	relative_path: string
+	key: string // prefixed with its section or parent keys
+	base: string | null // previously installed value
+	local: string | null // the user's value
+	incoming: string | null // the update's value
+}
+
+/**
+ * Result of install_update.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/installer.rs (InstallResult struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface InstallResult
+{
+	merged_configs: string[] // config files that kept local changes
+	config_conflicts: ConfigConflict[]
+	instance_file_updated: boolean // true if minecraftinstance.json was changed
+	compatibility_warnings: string[] // Minecraft version or mod loader differences to the instance
+}
+
+export type FileStatus =
+	| 'ok'
+	| 'missing'
+	| 'wrong_hash'
+	| 'unexpectedly_disabled'
+	| 'unexpectedly_enabled'
+	| 'extra'
+
+/**
+ * A file checked by verify_instance.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/verify.rs (VerifiedFile struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface VerifiedFile
+{
+	status: FileStatus
+	category: string // 'mods' | 'resourcepacks' | 'shaderpacks' | 'datapacks' | 'config' | 'overrides'
+	name: string
+	relative_path: string // where the file is on disk, or should be if missing
+	addon_project_id?: number
+}
+
+/**
+ * Result of verify_instance.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/verify.rs (VerifyReport struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface VerifyReport
+{
+	files: VerifiedFile[]
+	problem_count: number
+}
+
+/**
+ * Options for repair_instance. Converted to snake_case in useTauri.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/verify.rs (RepairOptions struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface RepairOptions
+{
+	removeExtraFiles?: boolean // delete files no addon of the manifest installed (default: false)
+	maxConcurrentDownloads?: number
+	retry?: RetryPolicy
+	useCache?: boolean
+	side?: InstallSide // default: 'client'
+}
+
+/**
+ * Result of repair_instance.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/verify.rs (RepairResult struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface RepairResult
+{
+	repaired: VerifiedFile[] // problems that were fixed, as found before the repair
+	report: VerifyReport // state after the repair
+}
+
+/**
+ * Options for export_server_pack. Converted to snake_case in useTauri.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/server_pack.rs (ServerPackOptions struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface ServerPackOptions
+{
+	outputPath: string // directory to create, or the zip file to write
+	zip?: boolean // default: false
+	maxConcurrentDownloads?: number
+	retry?: RetryPolicy
+	useCache?: boolean
+}
+
+/**
+ * Result of export_server_pack.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/server_pack.rs (ServerPackResult struct)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export interface ServerPackResult
+{
+	output_path: string
+	addon_count: number
+	config_count: number
+	override_count: number
+	skipped_addons: string[] // client-only addons that were left out
+}
+
+export type CemmErrorCode =
+	| 'network'
+	| 'http_status'
+	| 'not_found'
+	| 'auth'
+	| 'rate_limited'
+	| 'path_traversal'
+	| 'io'
+	| 'parse'
+	| 'validation'
+	| 'cancelled'
+
+/**
+ * Error every Tauri command rejects with. Branch on `code`, `message` is for display.
+ *
+ * This type is mirrored in:
+ * - TypeScript: app/types/index.ts (this file)
+ * - Rust: src-tauri/src/error.rs (CemmError enum)
+ *
+ * When modifying this type, ensure all definitions remain consistent.
+ */
+export type CemmError =
+	| { code: 'network', message: string, url: string | null }
+	| { code: 'http_status', message: string, status: number, url: string | null }
+	| { code: 'not_found', message: string, resource: string | null }
+	| { code: 'auth', message: string }
+	| { code: 'rate_limited', message: string, retry_after_secs: number | null }
+	| { code: 'path_traversal', message: string, path: string }
+	| { code: 'io', message: string, path: string | null }
+	| { code: 'parse', message: string, location: string | null } // location: file path or URL
+	| { code: 'validation', message: string }
+	| { code: 'cancelled', message: string }
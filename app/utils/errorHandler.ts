@@ -3,6 +3,54 @@
  * Provides basic error state management without over-engineering
  */
 
+import type { CemmError, CemmErrorCode } from '~/types'
+
+const CEMM_ERROR_CODES: CemmErrorCode[] = [
+	'network',
+	'http_status',
+	'not_found',
+	'auth',
+	'rate_limited',
+	'path_traversal',
+	'io',
+	'parse',
+	'validation',
+	'cancelled'
+]
+
+/**
+ * Whether a rejected Tauri command returned a CemmError
+ */
+export function isCemmError(error: unknown): error is CemmError
+{
+	return typeof error === 'object'
+		&& error !== null
+		&& CEMM_ERROR_CODES.includes((error as { code?: unknown }).code as CemmErrorCode)
+		&& typeof (error as { message?: unknown }).message === 'string'
+}
+
+/**
+ * Whether retrying the same request may succeed
+ */
+function isTransientError(error: unknown): boolean
+{
+	if (isCemmError(error))
+	{
+		return error.code === 'network'
+			|| error.code === 'rate_limited'
+			|| (error.code === 'http_status' && error.status >= 500)
+	}
+
+	// Errors thrown by the frontend itself, e.g. a failed fetch
+	const errorMessage = error instanceof Error ? error.message.toLowerCase() : ''
+	return (
+		errorMessage.includes('network')
+		|| errorMessage.includes('fetch')
+		|| errorMessage.includes('timeout')
+		|| errorMessage.includes('connection')
+	)
+}
+
 export interface ErrorState
 {
 	error: {
@@ -37,22 +85,14 @@ export async function withNetworkRetry<T>(
 				throw error
 			}
 
-			// Check if it's a network-related error worth retrying
-			const errorMessage = error instanceof Error ? error.message.toLowerCase() : ''
-			const isNetworkError = (
-				errorMessage.includes('network')
-				|| errorMessage.includes('fetch')
-				|| errorMessage.includes('timeout')
-				|| errorMessage.includes('connection')
-			)
-
-			if (!isNetworkError)
+			if (!isTransientError(error))
 			{
 				throw error // Don't retry non-network errors
 			}
 
-			// Exponential backoff
-			const delay = backoffMs * Math.pow(2, attempt)
+			// Exponential backoff, or as long as a rate limit asks for
+			const retryAfterSecs = isCemmError(error) && error.code === 'rate_limited' ? error.retry_after_secs : null
+			const delay = retryAfterSecs !== null ? retryAfterSecs * 1000 : backoffMs * Math.pow(2, attempt)
 			await new Promise((resolve) => setTimeout(resolve, delay))
 		}
 	}
@@ -60,11 +100,46 @@ export async function withNetworkRetry<T>(
 	throw new Error('Max retries exceeded')
 }
 
+/**
+ * Message of a CemmError or Error as is, or the fallback for anything else
+ */
+export function describeError(error: unknown, fallback: string): string
+{
+	if (isCemmError(error) || error instanceof Error)
+	{
+		return error.message
+	}
+	return fallback
+}
+
 /**
  * Get user-friendly error message from an error
  */
 export function getErrorMessage(error: unknown, context?: string): string
 {
+	if (isCemmError(error))
+	{
+		switch (error.code)
+		{
+			case 'network':
+				return 'Network connection failed. Please check your internet connection and try again.'
+			case 'auth':
+				return 'GitHub authentication failed. Please check your token in settings.'
+			case 'rate_limited':
+				return error.retry_after_secs !== null
+					? `GitHub rate limit reached. Please try again in ${error.retry_after_secs} seconds.`
+					: 'GitHub rate limit reached. Please try again later.'
+			case 'not_found':
+				if (context === 'download')
+				{
+					return `${error.message}\n\nConfirm the folder exists under the repo (often \`modpack-folder/update-id\`). You can paste that full path from the repo root in the update field, or pick a modpack folder whose name matches the folder used when the update was published.`
+				}
+				return error.message
+			default:
+				return error.message
+		}
+	}
+
 	if (error instanceof Error)
 	{
 		const message = error.message.toLowerCase()
@@ -0,0 +1,505 @@
+use crate::composables::manifest::{Addon, HashAlgorithm};
+use crate::download::partial_path;
+use crate::error::CemmError;
+use crate::integrity::verify_addon_file;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{command, AppHandle, Manager};
+use tokio::fs as async_fs;
+use uuid::Uuid;
+
+/// Name of the cache directory inside the app data dir
+const CACHE_DIR_NAME: &str = "download-cache";
+/// Marker in the names of entries that are still being written
+const TEMP_MARKER: &str = ".tmp-";
+
+/// Content-addressed store of downloaded addon files shared by all instances.
+///
+/// Entries are named `<file id>-<hash>`, so a file is only ever served for the
+/// exact CurseForge file and content it was stored for. Addons without a known
+/// hash are never cached since there is nothing to address them by.
+#[derive(Debug, Clone)]
+pub struct DownloadCache {
+    dir: PathBuf,
+}
+
+/// Size of the download cache, returned by get_download_cache_info.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/cache.rs (this file)
+/// - TypeScript: app/types/index.ts (DownloadCacheInfo interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadCacheInfo {
+    pub path: String,
+    pub file_count: usize,
+    pub total_bytes: u64,
+}
+
+/// Outcome of prune_download_cache.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/cache.rs (this file)
+/// - TypeScript: app/types/index.ts (CachePruneResult interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachePruneResult {
+    pub removed_files: usize,
+    pub freed_bytes: u64,
+    pub remaining_bytes: u64,
+}
+
+/// A file in the cache directory.
+struct CacheEntry {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+impl DownloadCache {
+    /// Opens the cache below the application's data directory.
+    pub fn for_app(app: &AppHandle) -> Result<Self, CemmError> {
+        let app_data_dir = app.path().app_data_dir()
+            .map_err(|e| CemmError::io_message(format!("Failed to resolve the app data directory: {}", e), None))?;
+        Ok(Self::new(&app_data_dir))
+    }
+
+    pub fn new(app_data_dir: &Path) -> Self {
+        Self { dir: app_data_dir.join(CACHE_DIR_NAME) }
+    }
+
+    /// Returns where `addon` is stored in the cache, or `None` if it cannot be cached.
+    fn entry_path(&self, addon: &Addon) -> Option<PathBuf> {
+        cache_key(addon).map(|key| self.dir.join(key))
+    }
+
+    /// Places the cached copy of `addon` at `dest`, hardlinking when possible.
+    ///
+    /// The entry is verified first; corrupted entries are removed. Returns the size
+    /// of the file on a hit and `None` when the addon has to be downloaded.
+    pub async fn restore(&self, addon: &Addon, dest: &Path) -> Option<u64> {
+        let entry = self.entry_path(addon)?;
+        let size = async_fs::metadata(&entry).await.ok()?.len();
+
+        if let Err(e) = verify_addon_file(addon, &entry).await {
+            log::warn!("Discarding corrupted cache entry {}: {}", entry.display(), e);
+            remove_entry(&entry).await;
+            return None;
+        }
+
+        // Link next to the destination first so an existing file is replaced atomically
+        let part_path = partial_path(dest);
+        let result = async {
+            if let Some(parent) = dest.parent() {
+                async_fs::create_dir_all(parent).await
+                    .map_err(|e| CemmError::io("Failed to create directory", parent, e))?;
+            }
+            remove_entry(&part_path).await;
+            link_or_copy(&entry, &part_path).await?;
+            async_fs::rename(&part_path, dest).await
+                .map_err(|e| CemmError::io(&format!("Failed to move {} to", part_path.display()), dest, e))
+        }
+        .await;
+
+        match result {
+            Ok(()) => {
+                touch(&entry);
+                log::info!("Using cached copy of '{}'", addon.addon_name);
+                Some(size)
+            }
+            Err(e) => {
+                log::warn!("Failed to use cached copy of '{}': {}", addon.addon_name, e);
+                remove_entry(&part_path).await;
+                None
+            }
+        }
+    }
+
+    /// Adds a verified download to the cache. Failures are logged and otherwise ignored.
+    pub async fn store(&self, addon: &Addon, src: &Path) {
+        let Some(entry) = self.entry_path(addon) else {
+            return;
+        };
+        if async_fs::try_exists(&entry).await.unwrap_or(false) {
+            return;
+        }
+
+        // Write under a temporary name so a crash never leaves a truncated entry behind
+        let temp = self.dir.join(format!(
+            "{}{}{}",
+            entry.file_name().unwrap_or_default().to_string_lossy(),
+            TEMP_MARKER,
+            Uuid::new_v4().simple()
+        ));
+        let result = async {
+            async_fs::create_dir_all(&self.dir).await
+                .map_err(|e| CemmError::io("Failed to create cache directory", &self.dir, e))?;
+            link_or_copy(src, &temp).await?;
+            async_fs::rename(&temp, &entry).await
+                .map_err(|e| CemmError::io(&format!("Failed to move {} to", temp.display()), &entry, e))
+        }
+        .await;
+
+        if let Err(e) = result {
+            log::warn!("Failed to cache '{}': {}", addon.addon_name, e);
+            remove_entry(&temp).await;
+        }
+    }
+
+    pub async fn info(&self) -> Result<DownloadCacheInfo, CemmError> {
+        let entries = self.entries().await?;
+        Ok(DownloadCacheInfo {
+            path: self.dir.to_string_lossy().to_string(),
+            file_count: entries.len(),
+            total_bytes: entries.iter().map(|e| e.size).sum(),
+        })
+    }
+
+    /// Removes entries older than `max_age`, then the least recently used ones until
+    /// the cache fits in `max_bytes`. Without any limit the whole cache is cleared.
+    pub async fn prune(&self, max_bytes: Option<u64>, max_age: Option<Duration>) -> Result<CachePruneResult, CemmError> {
+        let mut entries = self.entries().await?;
+        // Oldest first
+        entries.sort_by_key(|e| e.modified);
+
+        let now = SystemTime::now();
+        let mut remaining_bytes: u64 = entries.iter().map(|e| e.size).sum();
+        let mut removed_files = 0usize;
+        let mut freed_bytes = 0u64;
+
+        for entry in &entries {
+            let expired = match max_age {
+                Some(max_age) => now.duration_since(entry.modified).unwrap_or_default() > max_age,
+                None => max_bytes.is_none(),
+            };
+            let over_limit = max_bytes.is_some_and(|max| remaining_bytes > max);
+            let abandoned = entry.path.to_string_lossy().contains(TEMP_MARKER);
+            if !(expired || over_limit || abandoned) {
+                continue;
+            }
+
+            match async_fs::remove_file(&entry.path).await {
+                Ok(()) => {
+                    removed_files += 1;
+                    freed_bytes += entry.size;
+                    remaining_bytes -= entry.size;
+                }
+                Err(e) => log::warn!("Failed to remove cache entry {}: {}", entry.path.display(), e),
+            }
+        }
+
+        log::info!("Pruned download cache: removed {} files, freed {} bytes", removed_files, freed_bytes);
+        Ok(CachePruneResult {
+            removed_files,
+            freed_bytes,
+            remaining_bytes,
+        })
+    }
+
+    async fn entries(&self) -> Result<Vec<CacheEntry>, CemmError> {
+        let mut entries = Vec::new();
+        let mut dir = match async_fs::read_dir(&self.dir).await {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(entries),
+            Err(e) => return Err(CemmError::io("Failed to read cache directory", &self.dir, e)),
+        };
+
+        while let Some(entry) = dir.next_entry().await
+            .map_err(|e| CemmError::io("Failed to read cache directory", &self.dir, e))?
+        {
+            let metadata = entry.metadata().await
+                .map_err(|e| CemmError::io("Failed to read", &entry.path(), e))?;
+            if !metadata.is_file() {
+                continue;
+            }
+            entries.push(CacheEntry {
+                path: entry.path(),
+                size: metadata.len(),
+                modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            });
+        }
+        Ok(entries)
+    }
+}
+
+/// Builds the cache key for an addon from its file id and strongest known hash.
+fn cache_key(addon: &Addon) -> Option<String> {
+    let hash = [HashAlgorithm::Sha1, HashAlgorithm::Md5]
+        .iter()
+        .find_map(|algorithm| addon.hashes.iter().find(|h| h.algorithm == *algorithm))
+        .map(|h| h.value.to_lowercase())
+        .or_else(|| addon.file_fingerprint.map(|fp| format!("fp{}", fp)))?;
+
+    // Hashes come from the manifest, so never let them form a path
+    if hash.is_empty() || !hash.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    Some(format!("{}-{}", addon.addon_file_id, hash))
+}
+
+/// Hardlinks `src` to `dest`, copying instead when linking is not possible
+/// (for example across volumes or on filesystems without hardlinks).
+pub async fn link_or_copy(src: &Path, dest: &Path) -> Result<(), CemmError> {
+    if async_fs::hard_link(src, dest).await.is_ok() {
+        return Ok(());
+    }
+    async_fs::copy(src, dest).await
+        .map(|_| ())
+        .map_err(|e| CemmError::io(&format!("Failed to copy {} to", src.display()), dest, e))
+}
+
+/// Marks an entry as recently used so pruning removes it last.
+fn touch(path: &Path) {
+    let result = std::fs::File::options()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_modified(SystemTime::now()));
+    if let Err(e) = result {
+        log::debug!("Failed to update timestamp of {}: {}", path.display(), e);
+    }
+}
+
+async fn remove_entry(path: &Path) {
+    if let Err(e) = async_fs::remove_file(path).await {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            log::warn!("Failed to remove {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Returns the location, number of files and total size of the download cache.
+#[command]
+pub async fn get_download_cache_info(app: AppHandle) -> Result<DownloadCacheInfo, CemmError> {
+    DownloadCache::for_app(&app)?.info().await
+}
+
+/// Frees space in the download cache.
+///
+/// Entries not used for `max_age_days` are removed first, then the least recently
+/// used entries until the cache is at most `max_bytes`. With neither limit set the
+/// cache is cleared completely.
+#[command]
+pub async fn prune_download_cache(
+    app: AppHandle,
+    max_bytes: Option<u64>,
+    max_age_days: Option<u64>,
+) -> Result<CachePruneResult, CemmError> {
+    DownloadCache::for_app(&app)?.prune(max_bytes, max_age_days.map(days_to_duration)).await
+}
+
+/// Converts a number of days to a duration, saturating instead of overflowing.
+fn days_to_duration(days: u64) -> Duration {
+    Duration::from_secs(days.saturating_mul(24 * 60 * 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::composables::manifest::FileHash;
+
+    /// A fresh directory below the system temp dir, removed again on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            let dir = std::env::temp_dir().join(format!("cemm-cache-test-{}", Uuid::new_v4().simple()));
+            std::fs::create_dir_all(&dir).expect("create temp dir");
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn addon(file_id: u64, hashes: Vec<FileHash>) -> Addon {
+        Addon {
+            addon_file_id: file_id,
+            addon_name: format!("addon-{}", file_id),
+            addon_project_id: 1,
+            cdn_download_url: String::new(),
+            mod_folder_path: "mods".to_string(),
+            version: "1.0".to_string(),
+            web_site_url: None,
+            disabled: None,
+            file_name_on_disk: format!("addon-{}.jar", file_id),
+            file_length: None,
+            file_fingerprint: None,
+            hashes,
+            alternative_download_urls: Vec::new(),
+            dependencies: Vec::new(),
+            environment: None,
+        }
+    }
+
+    fn hash(algorithm: HashAlgorithm, value: &str) -> FileHash {
+        FileHash { algorithm, value: value.to_string() }
+    }
+
+    /// An addon whose SHA-1 matches `content`.
+    fn addon_for(file_id: u64, content: &[u8]) -> Addon {
+        use sha1::{Digest, Sha1};
+        let sha1: String = Sha1::digest(content).iter().map(|b| format!("{:02x}", b)).collect();
+        addon(file_id, vec![hash(HashAlgorithm::Sha1, &sha1)])
+    }
+
+    fn write_entry(dir: &Path, name: &str, size: usize, age: Duration) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, vec![0u8; size]).expect("write entry");
+        let file = std::fs::File::options().write(true).open(&path).expect("open entry");
+        file.set_modified(SystemTime::now() - age).expect("set mtime");
+        path
+    }
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    #[test]
+    fn cache_key_prefers_sha1_and_lowercases() {
+        let addon = addon(42, vec![hash(HashAlgorithm::Md5, "abc"), hash(HashAlgorithm::Sha1, "DEF")]);
+        assert_eq!(cache_key(&addon).as_deref(), Some("42-def"));
+    }
+
+    #[test]
+    fn cache_key_falls_back_to_the_fingerprint() {
+        let mut addon = addon(42, Vec::new());
+        assert_eq!(cache_key(&addon), None);
+        addon.file_fingerprint = Some(7);
+        assert_eq!(cache_key(&addon).as_deref(), Some("42-fp7"));
+    }
+
+    #[test]
+    fn cache_key_rejects_hashes_that_are_not_plain() {
+        assert_eq!(cache_key(&addon(42, vec![hash(HashAlgorithm::Sha1, "../x")])), None);
+        assert_eq!(cache_key(&addon(42, vec![hash(HashAlgorithm::Sha1, "")])), None);
+    }
+
+    #[tokio::test]
+    async fn stored_file_is_restored() {
+        let temp = TempDir::new();
+        let cache = DownloadCache::new(&temp.0);
+        let content = b"jar contents";
+        let addon = addon_for(1, content);
+        let src = temp.0.join("download.jar");
+        std::fs::write(&src, content).unwrap();
+
+        cache.store(&addon, &src).await;
+        let dest = temp.0.join("instance/mods/addon-1.jar");
+        assert_eq!(cache.restore(&addon, &dest).await, Some(content.len() as u64));
+        assert_eq!(std::fs::read(&dest).unwrap(), content);
+        assert!(!partial_path(&dest).exists());
+    }
+
+    #[tokio::test]
+    async fn other_file_id_or_hash_misses() {
+        let temp = TempDir::new();
+        let cache = DownloadCache::new(&temp.0);
+        let content = b"jar contents";
+        let src = temp.0.join("download.jar");
+        std::fs::write(&src, content).unwrap();
+        cache.store(&addon_for(1, content), &src).await;
+
+        let dest = temp.0.join("addon.jar");
+        assert_eq!(cache.restore(&addon_for(2, content), &dest).await, None);
+        assert_eq!(cache.restore(&addon_for(1, b"other contents"), &dest).await, None);
+        assert!(!dest.exists());
+    }
+
+    #[tokio::test]
+    async fn corrupted_entry_is_discarded() {
+        let temp = TempDir::new();
+        let cache = DownloadCache::new(&temp.0);
+        let content = b"jar contents";
+        let addon = addon_for(1, content);
+        let src = temp.0.join("download.jar");
+        std::fs::write(&src, content).unwrap();
+        cache.store(&addon, &src).await;
+
+        let entry = cache.entry_path(&addon).unwrap();
+        // Replace rather than write through, the entry may be a hardlink to src
+        std::fs::remove_file(&entry).unwrap();
+        std::fs::write(&entry, b"tampered").unwrap();
+
+        assert_eq!(cache.restore(&addon, &temp.0.join("addon.jar")).await, None);
+        assert!(!entry.exists());
+    }
+
+    #[tokio::test]
+    async fn link_or_copy_links_or_falls_back_to_copying() {
+        let temp = TempDir::new();
+        let src = temp.0.join("src");
+        std::fs::write(&src, b"new").unwrap();
+
+        let linked = temp.0.join("linked");
+        link_or_copy(&src, &linked).await.unwrap();
+        assert_eq!(std::fs::read(&linked).unwrap(), b"new");
+
+        // Linking onto an existing file fails, copying replaces it
+        let existing = temp.0.join("existing");
+        std::fs::write(&existing, b"old").unwrap();
+        link_or_copy(&src, &existing).await.unwrap();
+        assert_eq!(std::fs::read(&existing).unwrap(), b"new");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let ino = |path: &Path| std::fs::metadata(path).unwrap().ino();
+            assert_eq!(ino(&linked), ino(&src));
+            assert_ne!(ino(&existing), ino(&src));
+        }
+    }
+
+    #[tokio::test]
+    async fn prune_removes_expired_then_oldest_entries() {
+        let temp = TempDir::new();
+        let cache = DownloadCache::new(&temp.0);
+        std::fs::create_dir_all(&cache.dir).unwrap();
+        let expired = write_entry(&cache.dir, "1-a", 10, DAY * 30);
+        let oldest = write_entry(&cache.dir, "2-b", 10, DAY * 3);
+        let older = write_entry(&cache.dir, "3-c", 10, DAY * 2);
+        let newest = write_entry(&cache.dir, "4-d", 10, DAY);
+        let abandoned = write_entry(&cache.dir, &format!("5-e{}x", TEMP_MARKER), 10, Duration::ZERO);
+
+        let result = cache.prune(Some(30), Some(DAY * 7)).await.unwrap();
+        assert!(!expired.exists());
+        assert!(!oldest.exists());
+        assert!(!abandoned.exists());
+        assert!(older.exists());
+        assert!(newest.exists());
+        assert_eq!(result.removed_files, 3);
+        assert_eq!(result.freed_bytes, 30);
+        assert_eq!(result.remaining_bytes, 20);
+    }
+
+    #[tokio::test]
+    async fn prune_without_limits_clears_the_cache() {
+        let temp = TempDir::new();
+        let cache = DownloadCache::new(&temp.0);
+        std::fs::create_dir_all(&cache.dir).unwrap();
+        write_entry(&cache.dir, "1-a", 10, Duration::ZERO);
+        write_entry(&cache.dir, "2-b", 10, DAY);
+
+        let result = cache.prune(None, None).await.unwrap();
+        assert_eq!(result.removed_files, 2);
+        assert_eq!(cache.info().await.unwrap().file_count, 0);
+    }
+
+    #[tokio::test]
+    async fn huge_max_age_keeps_everything() {
+        assert_eq!(days_to_duration(2), DAY * 2);
+        assert_eq!(days_to_duration(u64::MAX), Duration::from_secs(u64::MAX));
+
+        let temp = TempDir::new();
+        let cache = DownloadCache::new(&temp.0);
+        std::fs::create_dir_all(&cache.dir).unwrap();
+        let entry = write_entry(&cache.dir, "1-a", 10, DAY * 365);
+
+        let result = cache.prune(None, Some(days_to_duration(u64::MAX))).await.unwrap();
+        assert_eq!(result.removed_files, 0);
+        assert!(entry.exists());
+    }
+}
@@ -0,0 +1,147 @@
+use crate::error::CemmError;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{command, AppHandle, Manager};
+use tokio::sync::Notify;
+
+/// Shared flag that long-running commands check to stop early.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<TokenState>,
+}
+
+#[derive(Debug, Default)]
+struct TokenState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Returns `Err(CemmError::Cancelled)` once the token was cancelled.
+    pub fn check(&self) -> Result<(), CemmError> {
+        if self.is_cancelled() {
+            Err(CemmError::cancelled())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Completes when the token is cancelled.
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.inner.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Long-running operations that can be cancelled from the frontend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationKind {
+    Install,
+    Upload,
+}
+
+impl OperationKind {
+    fn label(self) -> &'static str {
+        match self {
+            OperationKind::Install => "install",
+            OperationKind::Upload => "upload",
+        }
+    }
+}
+
+/// Cancellation tokens of the currently running operations, kept as Tauri managed state.
+#[derive(Debug, Default)]
+pub struct Operations {
+    install: Mutex<Option<CancellationToken>>,
+    upload: Mutex<Option<CancellationToken>>,
+}
+
+impl Operations {
+    fn slot(&self, kind: OperationKind) -> &Mutex<Option<CancellationToken>> {
+        match kind {
+            OperationKind::Install => &self.install,
+            OperationKind::Upload => &self.upload,
+        }
+    }
+
+    /// Registers a new operation. Only one operation of each kind can run at a time.
+    pub fn start(&self, kind: OperationKind) -> Result<OperationGuard<'_>, CemmError> {
+        let mut slot = self.slot(kind).lock().map_err(|e| CemmError::validation(e.to_string()))?;
+        if slot.is_some() {
+            return Err(CemmError::validation(format!("An {} is already running", kind.label())));
+        }
+        let token = CancellationToken::new();
+        *slot = Some(token.clone());
+        Ok(OperationGuard { operations: self, kind, token })
+    }
+
+    /// Cancels the running operation of `kind`. Returns false if none was running.
+    pub fn cancel(&self, kind: OperationKind) -> bool {
+        let slot = match self.slot(kind).lock() {
+            Ok(slot) => slot,
+            Err(e) => {
+                log::error!("Failed to lock {} state: {}", kind.label(), e);
+                return false;
+            }
+        };
+        match slot.as_ref() {
+            Some(token) => {
+                log::info!("Cancelling {}", kind.label());
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Unregisters the operation when dropped, however the command returns.
+pub struct OperationGuard<'a> {
+    operations: &'a Operations,
+    kind: OperationKind,
+    token: CancellationToken,
+}
+
+impl OperationGuard<'_> {
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl Drop for OperationGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut slot) = self.operations.slot(self.kind).lock() {
+            *slot = None;
+        }
+    }
+}
+
+/// Cancels the running install_update. Returns false if no install is running.
+#[command]
+pub fn cancel_install(app: AppHandle) -> bool {
+    app.state::<Operations>().cancel(OperationKind::Install)
+}
+
+/// Cancels the running upload_update. Returns false if no upload is running.
+#[command]
+pub fn cancel_upload(app: AppHandle) -> bool {
+    app.state::<Operations>().cancel(OperationKind::Upload)
+}
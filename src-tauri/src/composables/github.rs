@@ -1,8 +1,22 @@
 use anyhow::Result;
+use reqwest::Response;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use tauri::{command, AppHandle, Emitter};
+use std::collections::HashMap;
+use tauri::{command, AppHandle, Emitter, Manager};
 
-use crate::composables::manifest::Manifest;
+use crate::cancel::{CancellationToken, OperationKind, Operations};
+use crate::composables::manifest::{Manifest, ReleaseAsset};
+use crate::error::CemmError;
+use crate::integrity::sha256_hex;
+use crate::overrides::{normalize_override_path, verify_override_bytes, OverrideFileWithContent, OVERRIDES_DIR};
+use crate::retry::{send_with_retry, RetryPolicy};
+
+/// Files larger than this are uploaded as release assets instead of git blobs
+const RELEASE_ASSET_THRESHOLD: usize = 10 * 1024 * 1024;
+
+/// Prefix of binary config file content
+const BINARY_CONTENT_PREFIX: &str = "data:application/octet-stream;base64,";
 
 /// Configuration file with content for GitHub upload/download operations.
 ///
@@ -25,6 +39,8 @@ pub struct ConfigFileWithContent {
 pub struct DownloadResult {
     pub manifest: Manifest,
     pub config_files: Vec<ConfigFileWithContent>,
+    #[serde(default)]
+    pub overrides: Vec<OverrideFileWithContent>,
 }
 
 /// Progress event payload for upload operations
@@ -42,6 +58,408 @@ fn emit_progress(app: &AppHandle, progress: u8, message: &str) {
     });
 }
 
+/// Stops the upload if it was cancelled, emitting `upload_cancelled`.
+///
+/// Commits only become visible once the branch reference is updated, and a release
+/// created for large files is deleted again by upload_update, so stopping at any
+/// earlier step leaves the repository unchanged. Assets replaced in the release of
+/// an earlier upload of the same update stay replaced.
+fn check_cancelled(app: &AppHandle, cancel: &CancellationToken) -> Result<(), CemmError> {
+    if !cancel.is_cancelled() {
+        return Ok(());
+    }
+    log::info!("Upload cancelled");
+    let _ = app.emit("upload_cancelled", UploadProgress {
+        progress: 0,
+        message: "Upload cancelled, the update was not published".to_string(),
+    });
+    Err(CemmError::cancelled())
+}
+
+/// Fails with the `CemmError` matching the status (auth, not found, rate limited, ...)
+/// unless GitHub answered with success.
+async fn expect_success(resp: Response, context: &str) -> Result<Response, CemmError> {
+    if resp.status().is_success() {
+        Ok(resp)
+    } else {
+        Err(CemmError::from_response(context, resp).await)
+    }
+}
+
+/// Reads and parses a JSON response body.
+async fn read_json<T: DeserializeOwned>(resp: Response, context: &str) -> Result<T, CemmError> {
+    let url = resp.url().to_string();
+    let text = resp.text().await.map_err(|e| CemmError::network(context, e))?;
+    serde_json::from_str(&text).map_err(|e| CemmError::parse(format!("{}: {}", context, e), url))
+}
+
+/// Uploads base64 `content` as a git blob and returns its SHA.
+async fn create_blob(
+    client: &reqwest::Client,
+    policy: &RetryPolicy,
+    blobs_url: &str,
+    token: &str,
+    content: &str,
+    context: &str,
+) -> Result<String, CemmError> {
+    let response = send_with_retry(policy, || {
+        client
+            .post(blobs_url)
+            .header("Authorization", format!("token {}", token))
+            .header("User-Agent", "cemm-app-tauri")
+            .json(&serde_json::json!({
+                "content": content,
+                "encoding": "base64"
+            }))
+    })
+    .await
+    .map_err(|e| CemmError::network(context, e))?;
+    let response = expect_success(response, context).await?;
+
+    let blob_json: serde_json::Value = read_json(response, context).await?;
+    blob_json["sha"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| CemmError::parse(format!("{}: no blob SHA in the response", context), blobs_url))
+}
+
+/// Downloads `relative_path` from the first update path that has it.
+///
+/// Each candidate is looked up through the contents API and fetched from its
+/// `download_url`. If no candidate has the file, the error of the last one is returned.
+async fn fetch_update_file(
+    client: &reqwest::Client,
+    policy: &RetryPolicy,
+    contents_url: &str,
+    base_paths: &[String],
+    relative_path: &str,
+) -> Result<Response, CemmError> {
+    let user_agent = "cemm-app-tauri";
+    let list_context = format!("Failed to list {}", relative_path);
+    let download_context = format!("Failed to download {}", relative_path);
+    let mut last_error: Option<CemmError> = None;
+
+    for base_path in base_paths {
+        let file_url = format!("{}/{}/{}", contents_url, base_path, relative_path);
+        log::debug!("Downloading update file from: {}", file_url);
+
+        let file_res = send_with_retry(policy, || {
+            client
+                .get(&file_url)
+                .header("User-Agent", user_agent)
+        })
+        .await
+        .map_err(|e| CemmError::network(&list_context, e))?;
+
+        if !file_res.status().is_success() {
+            last_error = Some(CemmError::from_response(&list_context, file_res).await);
+            continue;
+        }
+
+        let file_data: serde_json::Value = read_json(file_res, &list_context).await?;
+        let Some(download_url) = file_data["download_url"].as_str() else {
+            last_error = Some(CemmError::parse(format!("No download_url for {}", relative_path), &file_url));
+            continue;
+        };
+
+        let content_res = send_with_retry(policy, || {
+            client
+                .get(download_url)
+                .header("User-Agent", user_agent)
+        })
+        .await
+        .map_err(|e| CemmError::network(&download_context, e))?;
+
+        if !content_res.status().is_success() {
+            last_error = Some(CemmError::from_response(&download_context, content_res).await);
+            continue;
+        }
+        return Ok(content_res);
+    }
+
+    Err(last_error.unwrap_or_else(|| CemmError::not_found(download_context, relative_path)))
+}
+
+/// Prefix of the tags of update releases, followed by the update path with `-` for `/`
+const RELEASE_TAG_PREFIX: &str = "cemm-";
+
+/// A release that holds the large files of an update.
+struct OpenRelease {
+    id: u64,
+    tag: String,
+    /// Where assets are uploaded, without the `{?name,label}` template
+    upload_url: String,
+    /// Download URLs of the release's assets by name
+    assets: HashMap<String, String>,
+    /// Whether this upload created the release (and its tag)
+    created: bool,
+}
+
+/// Reads a release from the releases API.
+fn parse_release(json: &serde_json::Value, created: bool, url: &str) -> Result<OpenRelease, CemmError> {
+    let missing = |field: &str| CemmError::parse(format!("Release response has no {}", field), url);
+    let id = json["id"].as_u64().ok_or_else(|| missing("id"))?;
+    let tag = json["tag_name"].as_str().ok_or_else(|| missing("tag_name"))?.to_string();
+    // "https://uploads.github.com/repos/o/r/releases/1/assets{?name,label}"
+    let upload_url = json["upload_url"]
+        .as_str()
+        .map(|url| url.split('{').next().unwrap_or(url).to_string())
+        .ok_or_else(|| missing("upload_url"))?;
+    let assets = json["assets"]
+        .as_array()
+        .map(|assets| {
+            assets.iter()
+                .filter_map(|a| Some((a["name"].as_str()?.to_string(), a["browser_download_url"].as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(OpenRelease { id, tag, upload_url, assets, created })
+}
+
+/// Returns the release of an update, creating it if an earlier upload did not.
+///
+/// New releases are prereleases tagged `cemm-<update path>` at `target_sha`, so they
+/// never show up as the repository's latest release.
+async fn open_update_release(
+    client: &reqwest::Client,
+    policy: &RetryPolicy,
+    api_url: &str,
+    token: &str,
+    update_base_path: &str,
+    target_sha: &str,
+) -> Result<OpenRelease, CemmError> {
+    let context = "Failed to create release for large files";
+    let tag = format!("{}{}", RELEASE_TAG_PREFIX, update_base_path.replace('/', "-"));
+
+    // Uploading the same update again reuses its release
+    let existing_url = format!("{}/releases/tags/{}", api_url, tag);
+    let existing = send_with_retry(policy, || {
+        client
+            .get(&existing_url)
+            .header("Authorization", format!("token {}", token))
+            .header("User-Agent", "cemm-app-tauri")
+    })
+    .await
+    .map_err(|e| CemmError::network(context, e))?;
+    if existing.status() != reqwest::StatusCode::NOT_FOUND {
+        let existing = expect_success(existing, context).await?;
+        let release_json: serde_json::Value = read_json(existing, context).await?;
+        log::info!("Reusing release {} for large files", tag);
+        return parse_release(&release_json, false, &existing_url);
+    }
+
+    let releases_url = format!("{}/releases", api_url);
+    let response = send_with_retry(policy, || {
+        client
+            .post(&releases_url)
+            .header("Authorization", format!("token {}", token))
+            .header("User-Agent", "cemm-app-tauri")
+            .json(&serde_json::json!({
+                "tag_name": tag,
+                "target_commitish": target_sha,
+                "name": format!("CEMM update {}", update_base_path),
+                "body": "Large files of a CEMM update. The update's manifest links to them.",
+                "prerelease": true,
+                "make_latest": "false"
+            }))
+    })
+    .await
+    .map_err(|e| CemmError::network(context, e))?;
+    let response = expect_success(response, context).await?;
+
+    let release_json: serde_json::Value = read_json(response, context).await?;
+    parse_release(&release_json, true, &releases_url)
+}
+
+/// The release of an update that large files are uploaded to, opened on first use.
+///
+/// If the upload fails after this created the release, `discard` deletes the release
+/// and its tag again. A release of an earlier upload of the same update is kept.
+struct UpdateRelease {
+    /// "https://api.github.com/repos/{owner}/{repo}"
+    api_url: String,
+    update_base_path: String,
+    release: Option<OpenRelease>,
+    asset_count: usize,
+}
+
+impl UpdateRelease {
+    /// Uploads `bytes` as an asset. An asset of the same name already holds the same
+    /// content, since the name contains its hash, and is reused instead.
+    async fn upload(
+        &mut self,
+        client: &reqwest::Client,
+        policy: &RetryPolicy,
+        token: &str,
+        target_sha: &str,
+        relative_path: &str,
+        bytes: &[u8],
+    ) -> Result<ReleaseAsset, CemmError> {
+        let release = match &mut self.release {
+            Some(release) => release,
+            None => {
+                let release = open_update_release(
+                    client,
+                    policy,
+                    &self.api_url,
+                    token,
+                    &self.update_base_path,
+                    target_sha,
+                ).await?;
+                self.release.insert(release)
+            }
+        };
+
+        let sha256 = sha256_hex(bytes);
+        let name = asset_name(relative_path, &sha256);
+        let url = match release.assets.get(&name) {
+            Some(url) => {
+                log::info!("{} is already uploaded as {}", relative_path, name);
+                url.clone()
+            }
+            None => {
+                let url = upload_release_asset(client, policy, &release.upload_url, token, &name, bytes).await?;
+                release.assets.insert(name, url.clone());
+                url
+            }
+        };
+        self.asset_count += 1;
+        Ok(ReleaseAsset {
+            url,
+            size: bytes.len() as u64,
+            sha256,
+        })
+    }
+
+    /// Deletes the release and its tag if this upload created them. Failures are only logged.
+    async fn discard(&self, client: &reqwest::Client, policy: &RetryPolicy, token: &str) {
+        let Some(release) = self.release.as_ref().filter(|r| r.created) else {
+            return;
+        };
+        let urls = [
+            format!("{}/releases/{}", self.api_url, release.id),
+            format!("{}/git/refs/tags/{}", self.api_url, release.tag),
+        ];
+        for url in &urls {
+            let result = send_with_retry(policy, || {
+                client
+                    .delete(url)
+                    .header("Authorization", format!("token {}", token))
+                    .header("User-Agent", "cemm-app-tauri")
+            })
+            .await;
+            match result {
+                Ok(response) if response.status().is_success() => {}
+                Ok(response) => log::warn!("Failed to delete {}: HTTP {}", url, response.status()),
+                Err(e) => log::warn!("Failed to delete {}: {}", url, e),
+            }
+        }
+        log::info!("Deleted release {} of the failed upload", release.tag);
+    }
+}
+
+/// Asset names are unique per release and flat, so the hash keeps equal file names apart.
+fn asset_name(relative_path: &str, sha256: &str) -> String {
+    let file_name: String = relative_path.rsplit('/').next().unwrap_or(relative_path)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect();
+    format!("{}-{}", &sha256[..12], file_name)
+}
+
+/// Uploads `bytes` as an asset named `name` and returns the asset's download URL.
+async fn upload_release_asset(
+    client: &reqwest::Client,
+    policy: &RetryPolicy,
+    upload_url: &str,
+    token: &str,
+    name: &str,
+    bytes: &[u8],
+) -> Result<String, CemmError> {
+    let context = format!("Failed to upload {}", name);
+    let response = send_with_retry(policy, || {
+        client
+            .post(upload_url)
+            .query(&[("name", name)])
+            .header("Authorization", format!("token {}", token))
+            .header("User-Agent", "cemm-app-tauri")
+            .header("Content-Type", "application/octet-stream")
+            .body(bytes.to_vec())
+    })
+    .await
+    .map_err(|e| CemmError::network(&context, e))?;
+    let response = expect_success(response, &context).await?;
+
+    let asset_json: serde_json::Value = read_json(response, &context).await?;
+    asset_json["browser_download_url"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| CemmError::parse(format!("{}: no download URL in the response", context), upload_url))
+}
+
+/// Downloads a release asset of an update.
+async fn fetch_release_asset(
+    client: &reqwest::Client,
+    policy: &RetryPolicy,
+    url: &str,
+    relative_path: &str,
+) -> Result<Vec<u8>, CemmError> {
+    let context = format!("Failed to download {}", relative_path);
+    log::debug!("Downloading release asset from: {}", url);
+    let response = send_with_retry(policy, || {
+        client
+            .get(url)
+            .header("User-Agent", "cemm-app-tauri")
+    })
+    .await
+    .map_err(|e| CemmError::network(&context, e))?;
+    let response = expect_success(response, &context).await?;
+    let bytes = response.bytes().await.map_err(|e| CemmError::network(&context, e))?;
+    Ok(bytes.to_vec())
+}
+
+/// Checks a downloaded release asset against the size and SHA-256 in the manifest.
+fn verify_asset_bytes(asset: &ReleaseAsset, relative_path: &str, bytes: &[u8]) -> Result<(), CemmError> {
+    if bytes.len() as u64 != asset.size {
+        return Err(CemmError::validation(format!(
+            "Size mismatch for {}: expected {} bytes, got {}",
+            relative_path,
+            asset.size,
+            bytes.len()
+        )));
+    }
+    let actual = sha256_hex(bytes);
+    if !actual.eq_ignore_ascii_case(&asset.sha256) {
+        return Err(CemmError::validation(format!(
+            "SHA-256 mismatch for {}: expected {}, got {}",
+            relative_path, asset.sha256, actual
+        )));
+    }
+    Ok(())
+}
+
+/// Returns the bytes a config file is stored as, decoding binary content.
+fn config_file_bytes(file: &ConfigFileWithContent) -> Result<Vec<u8>, CemmError> {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+
+    match file.content.strip_prefix(BINARY_CONTENT_PREFIX) {
+        Some(encoded) => STANDARD.decode(encoded).map_err(|e| CemmError::parse(
+            format!("Failed to decode binary config file {}: {}", file.relative_path, e),
+            &file.relative_path,
+        )),
+        None => Ok(file.content.as_bytes().to_vec()),
+    }
+}
+
+/// Splits "owner/repo".
+fn split_repo(repo: &str) -> Result<(&str, &str), CemmError> {
+    match repo.split_once('/') {
+        Some((owner, name)) if !owner.is_empty() && !name.is_empty() => Ok((owner, name)),
+        _ => Err(CemmError::validation(format!("Invalid repo format '{}', expected owner/repo", repo))),
+    }
+}
+
 fn sanitize_modpack_key(name: &str) -> String {
     let lowered = name.trim().to_lowercase();
     let mut out = String::new();
@@ -127,237 +545,293 @@ fn update_base_path_candidates(modpack_key: Option<&str>, uuid: &str) -> Vec<Str
     candidates
 }
 
-fn normalize_update_uuid_arg(uuid: String) -> Result<String, String> {
+fn normalize_update_uuid_arg(uuid: String) -> Result<String, CemmError> {
     let normalized = uuid.trim().replace('\\', "/");
     if normalized.contains("..") {
-        return Err("Invalid update path: path traversal is not allowed".to_string());
+        return Err(CemmError::path_traversal("Invalid update path: path traversal is not allowed", normalized));
     }
     if normalized.is_empty() {
-        return Err("Update UUID or path is empty".to_string());
+        return Err(CemmError::validation("Update UUID or path is empty"));
     }
     Ok(normalized)
 }
 
+/// Uploads an update: the manifest, its config files and its override files below
+/// `overrides/`, all in a single commit. `manifest.overrides` is set from `overrides`.
+///
+/// Files larger than 10 MiB are uploaded as assets of a release for the update instead,
+/// and the manifest links to them with their size and SHA-256.
 #[command]
+#[allow(clippy::too_many_arguments)]
 pub async fn upload_update(
     app: AppHandle,
     repo: String,
     token: String,
     uuid: String,
     modpack_key: Option<String>,
-    manifest: Manifest,
+    mut manifest: Manifest,
     config_files: Vec<ConfigFileWithContent>,
-) -> Result<(), String> {
+    overrides: Option<Vec<OverrideFileWithContent>>,
+) -> Result<(), CemmError> {
     use base64::engine::general_purpose::STANDARD;
     use base64::Engine;
     use reqwest::Client;
     use serde_json::json;
 
+    let operations = app.state::<Operations>();
+    let operation = operations.start(OperationKind::Upload)?;
+    let cancel = operation.token();
+
     emit_progress(&app, 5, "Preparing upload...");
 
     let uuid = normalize_update_uuid_arg(uuid)?;
+    let mut overrides = overrides.unwrap_or_default();
+    for file in &mut overrides {
+        file.file.relative_path = normalize_override_path(&file.file.relative_path)?;
+    }
+    manifest.overrides = overrides.iter().map(|o| o.file.clone()).collect();
 
     // Parse repo as "owner/repo"
-    let mut parts = repo.splitn(2, '/');
-    let owner = parts.next().ok_or("Invalid repo format")?;
-    let repo_name = parts.next().ok_or("Invalid repo format")?;
+    let (owner, repo_name) = split_repo(&repo)?;
     let client = Client::new();
+    let policy = RetryPolicy::default();
     let user_agent = "cemm-app-tauri";
 
-    // Step 1: Get the current commit SHA of main branch
-    emit_progress(&app, 10, "Getting branch reference...");
-    let refs_url = format!("https://api.github.com/repos/{owner}/{repo_name}/git/refs/heads/main");
-    let refs_response = client
-        .get(&refs_url)
-        .header("Authorization", format!("token {}", token))
-        .header("User-Agent", user_agent)
-        .send()
+    let mut release = UpdateRelease {
+        api_url: format!("https://api.github.com/repos/{owner}/{repo_name}"),
+        update_base_path: primary_update_base_path(modpack_key.as_deref(), &uuid),
+        release: None,
+        asset_count: 0,
+    };
+
+    // Runs the upload so a release created for large files can be deleted if it fails
+    let result = async {
+        // Step 1: Get the current commit SHA of main branch
+        check_cancelled(&app, cancel)?;
+        emit_progress(&app, 10, "Getting branch reference...");
+        let refs_url = format!("https://api.github.com/repos/{owner}/{repo_name}/git/refs/heads/main");
+        let refs_response = send_with_retry(&policy, || {
+            client
+                .get(&refs_url)
+                .header("Authorization", format!("token {}", token))
+                .header("User-Agent", user_agent)
+        })
         .await
-        .map_err(|e| e.to_string())?;
-    
-    if !refs_response.status().is_success() {
-        return Err(format!(
-            "Failed to get main branch ref: {}",
-            refs_response.text().await.unwrap_or_default()
-        ));
-    }
+        .map_err(|e| CemmError::network("Failed to get main branch ref", e))?;
+        let refs_response = expect_success(refs_response, "Failed to get main branch ref").await?;
 
-    let refs_json: serde_json::Value = refs_response.json().await.map_err(|e| e.to_string())?;
-    let base_commit_sha = refs_json["object"]["sha"]
-        .as_str()
-        .ok_or("Could not find main branch SHA")?;
-
-    // Step 2: Get the base tree SHA
-    emit_progress(&app, 15, "Getting tree structure...");
-    let commit_url = format!("https://api.github.com/repos/{owner}/{repo_name}/git/commits/{base_commit_sha}");
-    let commit_response = client
-        .get(&commit_url)
-        .header("Authorization", format!("token {}", token))
-        .header("User-Agent", user_agent)
-        .send()
+        let refs_json: serde_json::Value = read_json(refs_response, "Failed to read main branch ref").await?;
+        let base_commit_sha = refs_json["object"]["sha"]
+            .as_str()
+            .ok_or_else(|| CemmError::parse("Could not find main branch SHA", &refs_url))?;
+
+        // Step 2: Get the base tree SHA
+        check_cancelled(&app, cancel)?;
+        emit_progress(&app, 15, "Getting tree structure...");
+        let commit_url = format!("https://api.github.com/repos/{owner}/{repo_name}/git/commits/{base_commit_sha}");
+        let commit_response = send_with_retry(&policy, || {
+            client
+                .get(&commit_url)
+                .header("Authorization", format!("token {}", token))
+                .header("User-Agent", user_agent)
+        })
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| CemmError::network("Failed to get base commit", e))?;
+        let commit_response = expect_success(commit_response, "Failed to get base commit").await?;
 
-    let commit_json: serde_json::Value = commit_response.json().await.map_err(|e| e.to_string())?;
-    let base_tree_sha = commit_json["tree"]["sha"]
-        .as_str()
-        .ok_or("Could not find base tree SHA")?;
+        let commit_json: serde_json::Value = read_json(commit_response, "Failed to read base commit").await?;
+        let base_tree_sha = commit_json["tree"]["sha"]
+            .as_str()
+            .ok_or_else(|| CemmError::parse("Could not find base tree SHA", &commit_url))?;
+
+        // Step 3: Create blobs for all files. Large files become release assets, and the
+        // manifest is uploaded last so it can link to them.
+        let update_base_path = primary_update_base_path(modpack_key.as_deref(), &uuid);
+        let blobs_url = format!("https://api.github.com/repos/{owner}/{repo_name}/git/blobs");
+        // Create blobs for config and override files with progress
+        let total_files = config_files.len() + overrides.len();
+        let mut config_blobs: Vec<(&ConfigFileWithContent, String)> = Vec::new();
+        for (index, file) in config_files.iter().enumerate() {
+            // Calculate progress: 20-70% for config and override files
+            let progress = 20 + ((index + 1) as f32 / total_files as f32 * 50.0) as u8;
+            check_cancelled(&app, cancel)?;
+            emit_progress(&app, progress, &format!("Uploading config file {}/{}...", index + 1, config_files.len()));
+
+            let bytes = config_file_bytes(file)?;
+            if bytes.len() > RELEASE_ASSET_THRESHOLD {
+                let asset = release.upload(&client, &policy, &token, base_commit_sha, &file.relative_path, &bytes).await?;
+                match manifest.config_files.iter_mut().find(|c| c.relative_path == file.relative_path) {
+                    Some(entry) => entry.asset = Some(asset),
+                    None => log::warn!("{} is not listed in the manifest, users will not download it", file.relative_path),
+                }
+                continue;
+            }
 
-    // Step 3: Create blobs for all files
-    emit_progress(&app, 20, "Uploading manifest...");
-    let manifest_json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
-    
-    // Create blob for manifest
-    let manifest_blob_url = format!("https://api.github.com/repos/{owner}/{repo_name}/git/blobs");
-    let manifest_blob_response = client
-        .post(&manifest_blob_url)
-        .header("Authorization", format!("token {}", token))
-        .header("User-Agent", user_agent)
-        .json(&json!({
-            "content": STANDARD.encode(manifest_json),
-            "encoding": "base64"
-        }))
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+            let config_blob_sha = create_blob(
+                &client,
+                &policy,
+                &blobs_url,
+                &token,
+                &STANDARD.encode(&bytes),
+                &format!("Failed to upload {}", file.relative_path),
+            ).await?;
+            config_blobs.push((file, config_blob_sha));
+        }
 
-    let manifest_blob_json: serde_json::Value = manifest_blob_response.json().await.map_err(|e| e.to_string())?;
-    let manifest_blob_sha = manifest_blob_json["sha"]
-        .as_str()
-        .ok_or("Could not get manifest blob SHA")?;
-
-    // Create blobs for config files with progress
-    let total_config_files = config_files.len();
-    let mut config_blob_shas = Vec::new();
-    for (index, file) in config_files.iter().enumerate() {
-        // Calculate progress: 20-70% for config files
-        let progress = 20 + ((index + 1) as f32 / total_config_files as f32 * 50.0) as u8;
-        emit_progress(&app, progress, &format!("Uploading config file {}/{}...", index + 1, total_config_files));
-
-        // Check if content is already base64-encoded (binary files)
-        let (content, encoding) = if file.content.starts_with("data:application/octet-stream;base64,") {
-            // Already base64-encoded binary content, extract the base64 part
-            let base64_content = file.content.strip_prefix("data:application/octet-stream;base64,").unwrap_or(&file.content);
-            (base64_content.to_string(), "base64")
-        } else {
-            // Text content, encode as base64
-            (STANDARD.encode(&file.content), "base64")
-        };
-        
-        let config_blob_response = client
-            .post(&manifest_blob_url) // Same URL for creating blobs
-            .header("Authorization", format!("token {}", token))
-            .header("User-Agent", user_agent)
-            .json(&json!({
-                "content": content,
-                "encoding": encoding
-            }))
-            .send()
-            .await
-            .map_err(|e| e.to_string())?;
+        let mut override_blobs: Vec<(&OverrideFileWithContent, String)> = Vec::new();
+        for (index, file) in overrides.iter().enumerate() {
+            let progress = 20 + ((config_files.len() + index + 1) as f32 / total_files as f32 * 50.0) as u8;
+            check_cancelled(&app, cancel)?;
+            emit_progress(&app, progress, &format!("Uploading override file {}/{}...", index + 1, overrides.len()));
 
-        let config_blob_json: serde_json::Value = config_blob_response.json().await.map_err(|e| e.to_string())?;
-        let config_blob_sha = config_blob_json["sha"]
-            .as_str()
-            .ok_or("Could not get config blob SHA")?
-            .to_string();
-        config_blob_shas.push(config_blob_sha);
-    }
+            let bytes = file.decode()?;
+            if bytes.len() > RELEASE_ASSET_THRESHOLD {
+                let asset = release.upload(&client, &policy, &token, base_commit_sha, &file.file.relative_path, &bytes).await?;
+                manifest.overrides[index].url = Some(asset.url);
+                continue;
+            }
 
-    let update_base_path = primary_update_base_path(modpack_key.as_deref(), &uuid);
-
-    // Step 4: Create a new tree with all files
-    emit_progress(&app, 75, "Creating file tree...");
-    // Note: This will automatically overwrite any existing files at the same paths
-    // because Git tree creation replaces the entire directory structure
-    let mut tree_items = vec![
-        json!({
-            "path": format!("{}/cemm-manifest.json", update_base_path),
-            "mode": "100644",
-            "type": "blob",
-            "sha": manifest_blob_sha
-        })
-    ];
-
-    // Add config files to tree (will overwrite existing config files if same UUID)
-    for (i, file) in config_files.iter().enumerate() {
-        tree_items.push(json!({
-            "path": format!("{}/{}", update_base_path, file.relative_path),
-            "mode": "100644",
-            "type": "blob",
-            "sha": config_blob_shas[i]
-        }));
-    }
+            // Override content is base64 already
+            let override_blob_sha = create_blob(
+                &client,
+                &policy,
+                &blobs_url,
+                &token,
+                &file.content,
+                &format!("Failed to upload {}", file.file.relative_path),
+            ).await?;
+            override_blobs.push((file, override_blob_sha));
+        }
+        if release.asset_count > 0 {
+            log::info!("upload_update: {} large files uploaded as release assets", release.asset_count);
+        }
 
-    let tree_url = format!("https://api.github.com/repos/{owner}/{repo_name}/git/trees");
-    let tree_response = client
-        .post(&tree_url)
-        .header("Authorization", format!("token {}", token))
-        .header("User-Agent", user_agent)
-        .json(&json!({
-            "base_tree": base_tree_sha,
-            "tree": tree_items
-        }))
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+        check_cancelled(&app, cancel)?;
+        emit_progress(&app, 72, "Uploading manifest...");
+        let manifest_json = serde_json::to_string_pretty(&manifest)
+            .map_err(|e| CemmError::validation(format!("Failed to serialize manifest: {}", e)))?;
+        let manifest_blob_sha = create_blob(
+            &client,
+            &policy,
+            &blobs_url,
+            &token,
+            &STANDARD.encode(&manifest_json),
+            "Failed to upload manifest",
+        ).await?;
+
+        // Step 4: Create a new tree with all files
+        check_cancelled(&app, cancel)?;
+        emit_progress(&app, 75, "Creating file tree...");
+        // Note: This will automatically overwrite any existing files at the same paths
+        // because Git tree creation replaces the entire directory structure
+        let mut tree_items = vec![
+            json!({
+                "path": format!("{}/cemm-manifest.json", update_base_path),
+                "mode": "100644",
+                "type": "blob",
+                "sha": manifest_blob_sha
+            })
+        ];
+
+        // Add config files to tree (will overwrite existing config files if same UUID)
+        for (file, sha) in &config_blobs {
+            tree_items.push(json!({
+                "path": format!("{}/{}", update_base_path, file.relative_path),
+                "mode": "100644",
+                "type": "blob",
+                "sha": sha
+            }));
+        }
 
-    let tree_json: serde_json::Value = tree_response.json().await.map_err(|e| e.to_string())?;
-    let new_tree_sha = tree_json["sha"]
-        .as_str()
-        .ok_or("Could not get new tree SHA")?;
+        for (file, sha) in &override_blobs {
+            tree_items.push(json!({
+                "path": format!("{}/{}/{}", update_base_path, OVERRIDES_DIR, file.file.relative_path),
+                "mode": "100644",
+                "type": "blob",
+                "sha": sha
+            }));
+        }
 
-    // Step 5: Create a commit
-    emit_progress(&app, 85, "Creating commit...");
-    let config_count = config_files.len();
-    let commit_message = if config_count > 0 {
-        format!("Upload update {} (manifest + {} config files)", uuid, config_count)
-    } else {
-        format!("Upload update {} (manifest only)", uuid)
-    };
+        let tree_url = format!("https://api.github.com/repos/{owner}/{repo_name}/git/trees");
+        let tree_response = send_with_retry(&policy, || {
+            client
+                .post(&tree_url)
+                .header("Authorization", format!("token {}", token))
+                .header("User-Agent", user_agent)
+                .json(&json!({
+                    "base_tree": base_tree_sha,
+                    "tree": tree_items
+                }))
+        })
+        .await
+        .map_err(|e| CemmError::network("Failed to create file tree", e))?;
+        let tree_response = expect_success(tree_response, "Failed to create file tree").await?;
 
-    let commit_url = format!("https://api.github.com/repos/{owner}/{repo_name}/git/commits");
-    let commit_response = client
-        .post(&commit_url)
-        .header("Authorization", format!("token {}", token))
-        .header("User-Agent", user_agent)
-        .json(&json!({
-            "message": commit_message,
-            "tree": new_tree_sha,
-            "parents": [base_commit_sha]
-        }))
-        .send()
+        let tree_json: serde_json::Value = read_json(tree_response, "Failed to read file tree").await?;
+        let new_tree_sha = tree_json["sha"]
+            .as_str()
+            .ok_or_else(|| CemmError::parse("Could not get new tree SHA", &tree_url))?;
+
+        // Step 5: Create a commit
+        check_cancelled(&app, cancel)?;
+        emit_progress(&app, 85, "Creating commit...");
+        let config_count = config_files.len();
+        let commit_message = match (config_count, overrides.len()) {
+            (0, 0) => format!("Upload update {} (manifest only)", uuid),
+            (_, 0) => format!("Upload update {} (manifest + {} config files)", uuid, config_count),
+            (_, override_count) => format!(
+                "Upload update {} (manifest + {} config files + {} override files)",
+                uuid, config_count, override_count
+            ),
+        };
+
+        let commit_url = format!("https://api.github.com/repos/{owner}/{repo_name}/git/commits");
+        let commit_response = send_with_retry(&policy, || {
+            client
+                .post(&commit_url)
+                .header("Authorization", format!("token {}", token))
+                .header("User-Agent", user_agent)
+                .json(&json!({
+                    "message": commit_message,
+                    "tree": new_tree_sha,
+                    "parents": [base_commit_sha]
+                }))
+        })
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| CemmError::network("Failed to create commit", e))?;
+        let commit_response = expect_success(commit_response, "Failed to create commit").await?;
 
-    let new_commit_json: serde_json::Value = commit_response.json().await.map_err(|e| e.to_string())?;
-    let new_commit_sha = new_commit_json["sha"]
-        .as_str()
-        .ok_or("Could not get new commit SHA")?;
-
-    // Step 6: Update the main branch reference
-    emit_progress(&app, 95, "Finalizing...");
-    let update_ref_response = client
-        .patch(&refs_url)
-        .header("Authorization", format!("token {}", token))
-        .header("User-Agent", user_agent)
-        .json(&json!({
-            "sha": new_commit_sha,
-            "force": false
-        }))
-        .send()
+        let new_commit_json: serde_json::Value = read_json(commit_response, "Failed to read new commit").await?;
+        let new_commit_sha = new_commit_json["sha"]
+            .as_str()
+            .ok_or_else(|| CemmError::parse("Could not get new commit SHA", &commit_url))?;
+
+        // Step 6: Update the main branch reference
+        check_cancelled(&app, cancel)?;
+        emit_progress(&app, 95, "Finalizing...");
+        let update_ref_response = send_with_retry(&policy, || {
+            client
+                .patch(&refs_url)
+                .header("Authorization", format!("token {}", token))
+                .header("User-Agent", user_agent)
+                .json(&json!({
+                    "sha": new_commit_sha,
+                    "force": false
+                }))
+        })
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| CemmError::network("Failed to update main branch", e))?;
+        expect_success(update_ref_response, "Failed to update main branch").await?;
 
-    if !update_ref_response.status().is_success() {
-        return Err(format!(
-            "Failed to update main branch: {}",
-            update_ref_response.text().await.unwrap_or_default()
-        ));
+        emit_progress(&app, 100, "Upload complete");
+        Ok(())
     }
+    .await;
 
-    emit_progress(&app, 100, "Upload complete");
-    Ok(())
+    if result.is_err() {
+        release.discard(&client, &policy, &token).await;
+    }
+    result
 }
 
 #[command]
@@ -365,7 +839,8 @@ pub async fn download_manifest(
     repo: String,
     uuid: String,
     modpack_key: Option<String>,
-) -> Result<Manifest, String> {
+    retry: Option<RetryPolicy>,
+) -> Result<Manifest, CemmError> {
     use reqwest::Client;
     use serde_json::Value;
 
@@ -374,46 +849,45 @@ pub async fn download_manifest(
     // Debug logging
     eprintln!("download_manifest called with repo: '{}', uuid: '{}'", repo, uuid);
     
-    let mut parts = repo.splitn(2, '/');
-    let owner = parts.next().ok_or("Invalid repo format")?;
-    let repo_name = parts.next().ok_or("Invalid repo format")?;
+    let (owner, repo_name) = split_repo(&repo)?;
     let base_paths = update_base_path_candidates(modpack_key.as_deref(), &uuid);
     let client = Client::new();
+    let policy = retry.unwrap_or_default();
     let user_agent = "cemm-app-tauri";
-    let mut last_error = String::new();
+    let mut last_error: Option<CemmError> = None;
 
     for base_path in base_paths {
         let api_base = format!("https://api.github.com/repos/{owner}/{repo_name}/contents/{base_path}");
         eprintln!("Trying manifest path: {}", api_base);
 
-        let list_res = client
-            .get(&api_base)
-            .header("User-Agent", user_agent)
-            .send()
-            .await
-            .map_err(|e| {
-                eprintln!("Request error: {}", e);
-                e.to_string()
-            })?;
+        let list_res = send_with_retry(&policy, || {
+            client
+                .get(&api_base)
+                .header("User-Agent", user_agent)
+        })
+        .await
+        .map_err(|e| {
+            eprintln!("Request error: {}", e);
+            CemmError::network("Failed to list update files", e)
+        })?;
 
         if !list_res.status().is_success() {
-            last_error = format!(
-                "Failed to list update files (status {}): {}",
-                list_res.status(),
-                list_res.text().await.unwrap_or_default()
-            );
+            last_error = Some(CemmError::from_response("Failed to list update files", list_res).await);
             continue;
         }
 
-        let files: Vec<Value> = list_res.json().await.map_err(|e| {
+        let files: Vec<Value> = read_json(list_res, "Failed to read update file list").await.map_err(|e| {
             eprintln!("JSON parsing error: {}", e);
-            e.to_string()
+            e
         })?;
 
         let manifest_file = match files.iter().find(|f| f["name"] == "cemm-manifest.json") {
             Some(file) => file,
             None => {
-                last_error = "cemm-manifest.json not found".to_string();
+                last_error = Some(CemmError::not_found(
+                    format!("cemm-manifest.json not found in {}", base_path),
+                    format!("{}/cemm-manifest.json", base_path),
+                ));
                 continue;
             }
         };
@@ -421,139 +895,88 @@ pub async fn download_manifest(
         let manifest_url = match manifest_file["download_url"].as_str() {
             Some(url) => url,
             None => {
-                last_error = "No download_url for cemm-manifest.json".to_string();
+                last_error = Some(CemmError::parse("No download_url for cemm-manifest.json", &api_base));
                 continue;
             }
         };
 
-        let manifest_res = client
-            .get(manifest_url)
-            .header("User-Agent", user_agent)
-            .send()
-            .await
-            .map_err(|e| {
-                eprintln!("Manifest download error: {}", e);
-                e.to_string()
-            })?;
+        let manifest_res = send_with_retry(&policy, || {
+            client
+                .get(manifest_url)
+                .header("User-Agent", user_agent)
+        })
+        .await
+        .map_err(|e| {
+            eprintln!("Manifest download error: {}", e);
+            CemmError::network("Failed to download cemm-manifest.json", e)
+        })?;
 
         if !manifest_res.status().is_success() {
-            last_error = format!(
-                "Failed to download cemm-manifest.json (status {}): {}",
-                manifest_res.status(),
-                manifest_res.text().await.unwrap_or_default()
-            );
+            last_error = Some(CemmError::from_response("Failed to download cemm-manifest.json", manifest_res).await);
             continue;
         }
 
-        let manifest_json = manifest_res.text().await.map_err(|e| {
-            eprintln!("Failed to read manifest response text: {}", e);
-            e.to_string()
-        })?;
-
-        let manifest: Manifest = serde_json::from_str(&manifest_json).map_err(|e| {
+        let manifest: Manifest = read_json(manifest_res, "Failed to parse cemm-manifest.json").await.map_err(|e| {
             eprintln!("Failed to parse manifest JSON: {}", e);
-            e.to_string()
+            e
         })?;
 
         return Ok(manifest);
     }
 
-    let hint = "\n\nIf GitHub returned 404: confirm the folder exists under the repo (often `modpack-folder/update-id`). You can paste that full path from the repo root in the update field, or pick a modpack folder whose name matches the folder used when the update was published.";
-
-    Err(if last_error.is_empty() {
-        format!("Failed to find manifest in update path.{hint}")
-    } else {
-        format!("{last_error}{hint}")
-    })
+    // The frontend explains how update paths are resolved when this is `not_found`
+    Err(last_error.unwrap_or_else(|| {
+        CemmError::not_found("Failed to find manifest in update path", uuid.clone())
+    }))
 }
 
+/// Downloads the config files listed in the manifest. Files stored as release assets
+/// are fetched from the update's release and checked against their size and SHA-256.
 #[command]
 pub async fn download_config_files(
     repo: String,
     uuid: String,
     modpack_key: Option<String>,
     manifest: Manifest,
-) -> Result<Vec<ConfigFileWithContent>, String> {
+    retry: Option<RetryPolicy>,
+) -> Result<Vec<ConfigFileWithContent>, CemmError> {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
     use reqwest::Client;
 
     let uuid = normalize_update_uuid_arg(uuid)?;
     
-    let mut parts = repo.splitn(2, '/');
-    let owner = parts.next().ok_or("Invalid repo format")?;
-    let repo_name = parts.next().ok_or("Invalid repo format")?;
+    let (owner, repo_name) = split_repo(&repo)?;
     let client = Client::new();
-    let user_agent = "cemm-app-tauri";
+    let policy = retry.unwrap_or_default();
     let base_paths = update_base_path_candidates(modpack_key.as_deref(), &uuid);
+    let contents_url = format!("https://api.github.com/repos/{owner}/{repo_name}/contents");
 
     eprintln!("Downloading {} config files from manifest", manifest.config_files.len());
 
     // Download config files based on manifest list
     let mut config_files = Vec::new();
     for config_file in manifest.config_files {
-        let mut downloaded_content: Option<String> = None;
-        let mut last_error = String::new();
-
-        for base_path in &base_paths {
-            let file_url = format!(
-                "https://api.github.com/repos/{owner}/{repo_name}/contents/{}/{}",
-                base_path, config_file.relative_path
-            );
-            eprintln!("Downloading config file from: {}", file_url);
-
-            let file_res = client
-                .get(&file_url)
-                .header("User-Agent", user_agent)
-                .send()
-                .await
-                .map_err(|e| e.to_string())?;
-
-            if !file_res.status().is_success() {
-                last_error = format!(
-                    "Failed to list config file {} (status {}): {}",
-                    config_file.relative_path,
-                    file_res.status(),
-                    file_res.text().await.unwrap_or_default()
-                );
-                continue;
-            }
-
-            let file_data: serde_json::Value = file_res.json().await.map_err(|e| e.to_string())?;
-            let download_url = match file_data["download_url"].as_str() {
-                Some(url) => url,
-                None => {
-                    last_error = "No download_url for config file".to_string();
-                    continue;
-                }
+        // Large files live in a release, and binary ones come back as base64 data URIs
+        if let Some(asset) = &config_file.asset {
+            let bytes = fetch_release_asset(&client, &policy, &asset.url, &config_file.relative_path).await?;
+            verify_asset_bytes(asset, &config_file.relative_path, &bytes)?;
+            let (content, is_binary) = match String::from_utf8(bytes) {
+                Ok(text) => (text, false),
+                Err(e) => (format!("{}{}", BINARY_CONTENT_PREFIX, STANDARD.encode(e.into_bytes())), true),
             };
-
-            let content_res = client
-                .get(download_url)
-                .header("User-Agent", user_agent)
-                .send()
-                .await
-                .map_err(|e| e.to_string())?;
-
-            if !content_res.status().is_success() {
-                last_error = format!(
-                    "Failed to download config file {} (status {}): {}",
-                    config_file.relative_path,
-                    content_res.status(),
-                    content_res.text().await.unwrap_or_default()
-                );
-                continue;
-            }
-
-            downloaded_content = Some(content_res.text().await.map_err(|e| e.to_string())?);
-            break;
+            config_files.push(ConfigFileWithContent {
+                filename: config_file.filename,
+                relative_path: config_file.relative_path,
+                content,
+                is_binary: Some(is_binary),
+            });
+            continue;
         }
 
-        let content = downloaded_content.ok_or_else(|| {
-            if last_error.is_empty() {
-                format!("Failed to download config file {}", config_file.relative_path)
-            } else {
-                last_error
-            }
-        })?;
+        let content_res = fetch_update_file(&client, &policy, &contents_url, &base_paths, &config_file.relative_path).await?;
+        let content = content_res.text().await
+            .map_err(|e| CemmError::network(&format!("Failed to download config file {}", config_file.relative_path), e))?;
         
         config_files.push(ConfigFileWithContent {
             filename: config_file.filename,
@@ -567,17 +990,73 @@ pub async fn download_config_files(
     Ok(config_files)
 }
 
+/// Downloads the override files listed in the manifest from `overrides/` of the update,
+/// or from the update's release for large files. Every file is checked against the size
+/// and SHA-256 recorded in the manifest.
+#[command]
+pub async fn download_overrides(
+    repo: String,
+    uuid: String,
+    modpack_key: Option<String>,
+    manifest: Manifest,
+    retry: Option<RetryPolicy>,
+) -> Result<Vec<OverrideFileWithContent>, CemmError> {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use reqwest::Client;
+
+    let uuid = normalize_update_uuid_arg(uuid)?;
+    let (owner, repo_name) = split_repo(&repo)?;
+    let client = Client::new();
+    let policy = retry.unwrap_or_default();
+    let base_paths = update_base_path_candidates(modpack_key.as_deref(), &uuid);
+    let contents_url = format!("https://api.github.com/repos/{owner}/{repo_name}/contents");
+
+    let mut overrides = Vec::new();
+    for file in manifest.overrides {
+        let relative_path = normalize_override_path(&file.relative_path)?;
+        let bytes = match &file.url {
+            Some(url) => fetch_release_asset(&client, &policy, url, &relative_path).await?,
+            None => {
+                let stored_path = format!("{}/{}", OVERRIDES_DIR, relative_path);
+                let content_res = fetch_update_file(&client, &policy, &contents_url, &base_paths, &stored_path).await?;
+                content_res.bytes().await
+                    .map_err(|e| CemmError::network(&format!("Failed to download override file {}", relative_path), e))?
+                    .to_vec()
+            }
+        };
+        verify_override_bytes(&file, &bytes)?;
+
+        overrides.push(OverrideFileWithContent {
+            content: STANDARD.encode(&bytes),
+            file,
+        });
+    }
+
+    log::info!("download_overrides: downloaded {} override files", overrides.len());
+    Ok(overrides)
+}
+
 #[command]
 pub async fn download_update(
     repo: String,
     uuid: String,
     modpack_key: Option<String>,
-) -> Result<DownloadResult, String> {
-    let manifest = download_manifest(repo.clone(), uuid.clone(), modpack_key.clone()).await?;
-    let config_files = download_config_files(repo, uuid, modpack_key, manifest.clone()).await?;
+    retry: Option<RetryPolicy>,
+) -> Result<DownloadResult, CemmError> {
+    let manifest = download_manifest(repo.clone(), uuid.clone(), modpack_key.clone(), retry.clone()).await?;
+    let config_files = download_config_files(
+        repo.clone(),
+        uuid.clone(),
+        modpack_key.clone(),
+        manifest.clone(),
+        retry.clone(),
+    ).await?;
+    let overrides = download_overrides(repo, uuid, modpack_key, manifest.clone(), retry).await?;
     
     Ok(DownloadResult {
         manifest,
         config_files,
+        overrides,
     })
 }
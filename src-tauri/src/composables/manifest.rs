@@ -1,3 +1,5 @@
+use crate::environment::detect_jar_environment;
+use crate::error::CemmError;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -19,12 +21,174 @@ pub struct Addon {
     pub disabled: Option<bool>,
     #[serde(rename = "fileNameOnDisk")]
     pub file_name_on_disk: String,
+    /// Expected size of the file in bytes, used to reject truncated downloads
+    #[serde(rename = "fileLength", default, skip_serializing_if = "Option::is_none")]
+    pub file_length: Option<u64>,
+    /// CurseForge murmur2 fingerprint of the file
+    #[serde(rename = "fileFingerprint", default, skip_serializing_if = "Option::is_none")]
+    pub file_fingerprint: Option<u32>,
+    /// Cryptographic hashes of the file as reported by CurseForge
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hashes: Vec<FileHash>,
+    /// Mirror URLs tried in order when `cdn_download_url` fails (e.g. another CDN host or Modrinth)
+    #[serde(rename = "alternativeDownloadUrls", default, skip_serializing_if = "Vec::is_empty")]
+    pub alternative_download_urls: Vec<String>,
+    /// Other addons this file depends on, as reported by CurseForge
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<AddonDependency>,
+    /// Side of the game the addon is needed on. Unknown addons are installed everywhere.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<AddonEnvironment>,
+}
+
+/// Side of the game an addon, config file or override file is needed on.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AddonEnvironment {
+    Client,
+    Server,
+    Both,
+}
+
+/// CurseForge serves the same files from both of these hosts.
+const CURSEFORGE_CDN_HOSTS: [&str; 2] = ["edge.forgecdn.net", "mediafilez.forgecdn.net"];
+
+impl Addon {
+    /// All URLs this addon can be downloaded from, in the order they should be tried.
+    ///
+    /// The primary CDN URL comes first, then the declared alternatives. A CurseForge
+    /// URL on one CDN host is additionally tried on the other host as a last resort.
+    pub fn download_urls(&self) -> Vec<String> {
+        let mut urls: Vec<String> = Vec::new();
+        let declared = std::iter::once(&self.cdn_download_url).chain(&self.alternative_download_urls);
+        for url in declared {
+            if !url.is_empty() && !urls.contains(url) {
+                urls.push(url.clone());
+            }
+        }
+
+        let mirrored: Vec<String> = urls.iter().filter_map(|url| mirror_curseforge_host(url)).collect();
+        for url in mirrored {
+            if !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+        urls
+    }
+}
+
+/// Swaps a CurseForge CDN URL to the other CDN host, if it uses one of them.
+fn mirror_curseforge_host(url: &str) -> Option<String> {
+    let [first, second] = CURSEFORGE_CDN_HOSTS;
+    for (from, to) in [(first, second), (second, first)] {
+        let pattern = format!("://{}/", from);
+        if url.contains(&pattern) {
+            return Some(url.replacen(&pattern, &format!("://{}/", to), 1));
+        }
+    }
+    None
+}
+
+/// Hash algorithms CurseForge reports for installed files.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    Sha1,
+    Md5,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FileHash {
+    pub algorithm: HashAlgorithm,
+    pub value: String,
+}
+
+/// How strongly an addon needs one of its dependencies.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyKind {
+    Required,
+    Optional,
+}
+
+/// An edge from an addon to a CurseForge project it depends on.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/composables/manifest.rs (this file)
+/// - TypeScript: app/types/index.ts (AddonDependency interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AddonDependency {
+    pub addon_project_id: u64,
+    pub kind: DependencyKind,
+}
+
+/// A required dependency that an update removes, excludes or disables while an addon still needs it.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/composables/manifest.rs (this file)
+/// - TypeScript: app/types/index.ts (MissingDependency interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MissingDependency {
+    /// The addon that needs the dependency
+    pub addon_name: String,
+    pub addon_project_id: u64,
+    pub dependency_name: String,
+    pub dependency_project_id: u64,
+}
+
+/// A file stored as a GitHub release asset because it is too large for the repository tree.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/composables/manifest.rs (this file)
+/// - TypeScript: app/types/index.ts (ReleaseAsset interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReleaseAsset {
+    /// Public download URL of the asset
+    pub url: String,
+    pub size: u64,
+    /// Lowercase hex SHA-256 of the content
+    pub sha256: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ConfigFile {
     pub filename: String,
     pub relative_path: String,
+    /// Overrides the side inferred from the file name, see `config_environment`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<AddonEnvironment>,
+    /// Set when the file is stored as a release asset instead of in the update folder
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset: Option<ReleaseAsset>,
+}
+
+/// A file of the overrides tree: any file an update installs as is, relative to the
+/// instance root, e.g. `kubejs/assets/.../texture.png` or `defaultconfigs/x.toml`.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/composables/manifest.rs (this file)
+/// - TypeScript: app/types/index.ts (OverrideFile interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OverrideFile {
+    /// Path relative to the instance root, using forward slashes
+    pub relative_path: String,
+    pub size: u64,
+    /// Lowercase hex SHA-256 of the content
+    pub sha256: String,
+    /// Side the file is needed on. Files without one are installed everywhere.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<AddonEnvironment>,
+    /// Release asset URL when the file is too large for the repository tree
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -37,21 +201,82 @@ pub struct Manifest {
     pub shaderpacks: Vec<Addon>,
     pub datapacks: Vec<Addon>,
     pub config_files: Vec<ConfigFile>,
+    /// Config files the update deletes, relative to the modpack directory
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed_config_files: Vec<String>,
+    /// Files of any type installed as is, stored below `overrides/` in the update
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<OverrideFile>,
+    /// Minecraft version the modpack targets, e.g. "1.20.1"
+    #[serde(rename = "gameVersion", default, skip_serializing_if = "Option::is_none")]
+    pub game_version: Option<String>,
+    /// Mod loader the modpack targets as named by CurseForge, e.g. "forge-47.2.0"
+    #[serde(rename = "baseModLoader", default, skip_serializing_if = "Option::is_none")]
+    pub base_mod_loader: Option<String>,
+    /// CurseForge modpack version the update was built on top of
+    #[serde(rename = "baseModpack", default, skip_serializing_if = "Option::is_none")]
+    pub base_modpack: Option<BaseModpack>,
 }
 
+/// A CurseForge modpack version, as recorded in `installedModpack` of minecraftinstance.json.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/composables/manifest.rs (this file)
+/// - TypeScript: app/types/index.ts (BaseModpack interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BaseModpack {
+    pub project_id: u64,
+    pub file_id: u64,
+    /// Display name of the modpack file, e.g. "All the Mods 9-0.2.60"
+    pub version_name: String,
+}
+
+/// The result of `compare_manifests`.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/composables/manifest.rs (this file)
+/// - TypeScript: app/types/index.ts (ManifestUpdateInfo interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
 pub struct UpdateInfo {
     pub uuid: String,
     pub timestamp: String,
     pub added_addons: Vec<Addon>,
     pub removed_addons: Vec<String>,
+    /// Addons that were enabled or disabled without being added or removed
+    #[serde(default)]
+    pub toggled_addons: Vec<String>,
     pub config_files: Vec<String>,
+    /// Config files the new manifest deletes
+    #[serde(default)]
+    pub removed_config_files: Vec<String>,
+    /// Required dependencies the new manifest drops while other addons still need them
+    #[serde(default)]
+    pub missing_dependencies: Vec<MissingDependency>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct MinecraftInstance {
+    /// Directory of the instance on the machine that wrote the file
+    #[serde(rename = "installPath", default)]
+    install_path: Option<String>,
     #[serde(rename = "installedAddons")]
     installed_addons: Vec<InstalledAddon>,
+    #[serde(rename = "gameVersion", default)]
+    game_version: Option<String>,
+    #[serde(rename = "baseModLoader", default)]
+    base_mod_loader: Option<BaseModLoader>,
+    #[serde(rename = "installedModpack", default)]
+    installed_modpack: Option<InstalledAddon>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BaseModLoader {
+    name: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -77,6 +302,32 @@ struct InstalledFile {
     file_name: Option<String>,
     #[serde(rename = "downloadUrl")]
     download_url: Option<String>,
+    #[serde(rename = "displayName", default)]
+    display_name: Option<String>,
+    #[serde(rename = "fileLength")]
+    file_length: Option<u64>,
+    #[serde(rename = "fileFingerprint")]
+    file_fingerprint: Option<u64>,
+    hashes: Option<Vec<InstalledFileHash>>,
+    #[serde(default)]
+    dependencies: Option<Vec<InstalledFileDependency>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct InstalledFileDependency {
+    #[serde(rename = "addonId")]
+    addon_id: Option<u64>,
+    /// CurseForge relation type: 2 = optional, 3 = required (embedded libraries, tools etc. are ignored)
+    #[serde(rename = "type")]
+    dependency_type: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct InstalledFileHash {
+    value: Option<String>,
+    /// CurseForge hash algorithm id: 1 = SHA-1, 2 = MD5
+    #[serde(rename = "type")]
+    hash_type: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -85,15 +336,15 @@ struct CategorySection {
 }
 
 #[command]
-pub fn parse_minecraft_instance(path: String) -> Result<Manifest, String> {
+pub fn parse_minecraft_instance(path: String) -> Result<Manifest, CemmError> {
     log::info!("parse_minecraft_instance: reading {path}");
     let content = fs::read_to_string(&path).map_err(|e| {
         log::error!("parse_minecraft_instance: failed to read {path}: {e}");
-        e.to_string()
+        CemmError::io("Failed to read", Path::new(&path), e)
     })?;
     let instance: MinecraftInstance = serde_json::from_str(&content).map_err(|e| {
         log::error!("parse_minecraft_instance: failed to parse JSON: {e}");
-        e.to_string()
+        CemmError::parse(format!("Failed to parse {}: {}", path, e), &path)
     })?;
     // Scan for .disabled files in relevant folders
     let base_dir = Path::new(&path).parent().unwrap_or_else(|| Path::new("."));
@@ -101,6 +352,13 @@ pub fn parse_minecraft_instance(path: String) -> Result<Manifest, String> {
     let disabled_resourcepacks = find_disabled_files(base_dir.join("resourcepacks"));
     let disabled_shaderpacks = find_disabled_files(base_dir.join("shaderpacks"));
     let disabled_datapacks = find_disabled_files(base_dir.join("datapacks"));
+    let mut instance_roots = vec![base_dir.to_string_lossy().to_string()];
+    instance_roots.extend(instance.install_path.clone());
+    let game_version = instance.game_version.clone().filter(|v| !v.is_empty());
+    let base_mod_loader = instance.base_mod_loader.as_ref()
+        .and_then(|loader| loader.name.clone())
+        .filter(|name| !name.is_empty());
+    let base_modpack = instance.installed_modpack.as_ref().and_then(base_modpack_of);
     let mut mods = Vec::new();
     let mut resourcepacks = Vec::new();
     let mut shaderpacks = Vec::new();
@@ -133,6 +391,7 @@ pub fn parse_minecraft_instance(path: String) -> Result<Manifest, String> {
         let Some(cdn_download_url) = &installed_file.download_url else {
             continue;
         };
+        let mod_folder_path = relative_mod_folder(&instance_roots, mod_folder_path);
         // Determine if this addon is disabled by checking for .disabled file
         let mut disabled = None;
         let cat = category_name.to_lowercase();
@@ -155,16 +414,30 @@ pub fn parse_minecraft_instance(path: String) -> Result<Manifest, String> {
                 disabled = Some(true);
             }
         }
+        // Addons in other folders, e.g. config/openloader/data
+        if disabled.is_none() && base_dir.join(&mod_folder_path).join(format!("{}.disabled", file_name)).is_file() {
+            disabled = Some(true);
+        }
+        let environment = detect_addon_environment(base_dir, &mod_folder_path, version);
         let addon_struct = Addon {
             addon_file_id,
             addon_name: addon_name.clone(),
             addon_project_id,
             cdn_download_url: cdn_download_url.clone(),
-            mod_folder_path: mod_folder_path.clone(),
+            mod_folder_path,
             version: version.clone(),
             web_site_url: addon.web_site_url.clone(),
             disabled,
             file_name_on_disk: version.clone(), // Use the version field which contains the filename
+            file_length: installed_file.file_length.filter(|len| *len > 0),
+            file_fingerprint: installed_file
+                .file_fingerprint
+                .and_then(|fp| u32::try_from(fp).ok())
+                .filter(|fp| *fp != 0),
+            hashes: collect_file_hashes(installed_file),
+            alternative_download_urls: Vec::new(),
+            dependencies: collect_dependencies(installed_file),
+            environment,
         };
         if cat.contains("shader") || folder.ends_with("shaderpacks") {
             shaderpacks.push(addon_struct);
@@ -183,9 +456,106 @@ pub fn parse_minecraft_instance(path: String) -> Result<Manifest, String> {
         shaderpacks,
         datapacks,
         config_files: Vec::new(), // Empty for MinecraftInstance conversion
+        removed_config_files: Vec::new(),
+        overrides: Vec::new(),
+        game_version,
+        base_mod_loader,
+        base_modpack,
+    })
+}
+
+/// Converts the `hashes` array from minecraftinstance.json into typed file hashes,
+/// skipping entries with unknown algorithms or empty values.
+fn collect_file_hashes(installed_file: &InstalledFile) -> Vec<FileHash> {
+    let Some(hashes) = &installed_file.hashes else {
+        return Vec::new();
+    };
+    hashes
+        .iter()
+        .filter_map(|hash| {
+            let algorithm = match hash.hash_type? {
+                1 => HashAlgorithm::Sha1,
+                2 => HashAlgorithm::Md5,
+                _ => return None,
+            };
+            let value = hash.value.as_ref()?.trim().to_lowercase();
+            if value.is_empty() {
+                return None;
+            }
+            Some(FileHash { algorithm, value })
+        })
+        .collect()
+}
+
+/// Converts the `dependencies` array from minecraftinstance.json into dependency edges,
+/// keeping only required and optional ones.
+fn collect_dependencies(installed_file: &InstalledFile) -> Vec<AddonDependency> {
+    let Some(dependencies) = &installed_file.dependencies else {
+        return Vec::new();
+    };
+    dependencies
+        .iter()
+        .filter_map(|dependency| {
+            let kind = match dependency.dependency_type? {
+                2 => DependencyKind::Optional,
+                3 => DependencyKind::Required,
+                _ => return None,
+            };
+            Some(AddonDependency {
+                addon_project_id: dependency.addon_id.filter(|id| *id != 0)?,
+                kind,
+            })
+        })
+        .collect()
+}
+
+/// Infers the side of a mod from the metadata in its jar, which may be installed as `.disabled`.
+fn detect_addon_environment(base_dir: &Path, mod_folder_path: &str, file_name: &str) -> Option<AddonEnvironment> {
+    if !file_name.to_lowercase().ends_with(".jar") {
+        return None;
+    }
+    let folder = base_dir.join(mod_folder_path);
+    [folder.join(file_name), folder.join(format!("{}.disabled", file_name))]
+        .iter()
+        .find(|path| path.is_file())
+        .and_then(|path| detect_jar_environment(path))
+}
+
+/// Reads the modpack version an instance was installed from. Returns None for custom profiles.
+fn base_modpack_of(modpack: &InstalledAddon) -> Option<BaseModpack> {
+    let installed_file = modpack.installed_file.as_ref()?;
+    let version_name = installed_file.display_name.clone()
+        .filter(|name| !name.is_empty())
+        .or_else(|| installed_file.file_name.clone())
+        .unwrap_or_default();
+    Some(BaseModpack {
+        project_id: modpack.addon_id?,
+        file_id: installed_file.id?,
+        version_name,
     })
 }
 
+/// CurseForge stores absolute folder paths, but installs resolve `mod_folder_path` relative
+/// to the instance root. Strips the instance directory when the folder lies inside it and
+/// returns the path unchanged otherwise, in which case the installer uses the category folder.
+fn relative_mod_folder(instance_roots: &[String], mod_folder_path: &str) -> String {
+    let folder = mod_folder_path.replace('\\', "/");
+    for root in instance_roots {
+        let root = root.replace('\\', "/");
+        let root = root.trim_end_matches('/');
+        if root.is_empty() {
+            continue;
+        }
+        // Windows paths are case-insensitive
+        let inside = folder.get(..root.len()).is_some_and(|prefix| prefix.eq_ignore_ascii_case(root))
+            && folder[root.len()..].starts_with('/');
+        if inside {
+            return folder[root.len() + 1..].trim_end_matches('/').to_string();
+        }
+    }
+    mod_folder_path.to_string()
+}
+
 fn find_disabled_files(dir: PathBuf) -> Vec<String> {
     let mut result = Vec::new();
     if let Ok(entries) = fs::read_dir(dir) {
@@ -207,8 +577,48 @@ fn find_disabled_files(dir: PathBuf) -> Vec<String> {
     result
 }
 
+/// Finds required dependencies that `old` provided and `new` no longer does, because the
+/// dependency was removed, excluded or disabled while an enabled addon still needs it.
+///
+/// Dependencies `old` did not provide either are not reported, since the pack evidently
+/// works without them (e.g. because another addon bundles them).
+pub fn find_missing_dependencies(old: &Manifest, new: &Manifest) -> Vec<MissingDependency> {
+    fn enabled(manifest: &Manifest) -> Vec<&Addon> {
+        [&manifest.mods, &manifest.resourcepacks, &manifest.shaderpacks, &manifest.datapacks]
+            .into_iter()
+            .flatten()
+            .filter(|a| a.disabled != Some(true))
+            .collect()
+    }
+    let old_addons = enabled(old);
+    let new_addons = enabled(new);
+
+    let mut missing = Vec::new();
+    for addon in &new_addons {
+        for dependency in addon.dependencies.iter().filter(|d| d.kind == DependencyKind::Required) {
+            if new_addons.iter().any(|a| a.addon_project_id == dependency.addon_project_id) {
+                continue;
+            }
+            let Some(provided) = old_addons.iter().find(|a| a.addon_project_id == dependency.addon_project_id) else {
+                continue;
+            };
+            missing.push(MissingDependency {
+                addon_name: addon.addon_name.clone(),
+                addon_project_id: addon.addon_project_id,
+                dependency_name: provided.addon_name.clone(),
+                dependency_project_id: dependency.addon_project_id,
+            });
+        }
+    }
+    missing
+}
+
+/// Compares two manifests.
+///
+/// Required dependencies the new manifest drops are listed in `missing_dependencies`.
+/// With `strict` set, they make the comparison fail instead.
 #[command]
-pub fn compare_manifests(old: Manifest, new: Manifest) -> Result<UpdateInfo, String> {
+pub fn compare_manifests(old: Manifest, new: Manifest, strict: Option<bool>) -> Result<UpdateInfo, CemmError> {
     log::info!("compare_manifests: comparing manifests");
     
     // Helper function to process a single addon category
@@ -217,6 +627,7 @@ pub fn compare_manifests(old: Manifest, new: Manifest) -> Result<UpdateInfo, Str
         new_addons: &[Addon],
         added: &mut Vec<Addon>,
         removed: &mut Vec<String>,
+        toggled: &mut Vec<String>,
     ) {
         let old_ids: std::collections::HashSet<_> = old_addons.iter().map(|a| &a.addon_name).collect();
         let new_ids: std::collections::HashSet<_> = new_addons.iter().map(|a| &a.addon_name).collect();
@@ -228,42 +639,70 @@ pub fn compare_manifests(old: Manifest, new: Manifest) -> Result<UpdateInfo, Str
             }
         }
         
-        // Build a set of disabled addon names in the new manifest
-        let disabled_in_new: std::collections::HashSet<_> = new_addons
-            .iter()
-            .filter(|a| a.disabled == Some(true))
-            .map(|a| &a.addon_name)
-            .collect();
-        
-        // Find removed addons (in old but not in new, or disabled in new)
-        // Skip if old addon was already disabled - can't "remove" something that wasn't active
+        // Find removed addons (in old but not in new). Disabled addons stay on disk as
+        // `.disabled` files, so disabling or enabling one is reported as a toggle instead.
         for old_addon in old_addons {
-            if old_addon.disabled.unwrap_or(false) {
-                continue; // Skip disabled addons in old manifest
-            }
-            if !new_ids.contains(&old_addon.addon_name) || disabled_in_new.contains(&old_addon.addon_name) {
+            if !new_ids.contains(&old_addon.addon_name) {
                 removed.push(old_addon.addon_name.clone());
+                continue;
+            }
+            let now_disabled = new_addons.iter()
+                .find(|a| a.addon_name == old_addon.addon_name)
+                .is_some_and(|a| a.disabled == Some(true));
+            if old_addon.disabled.unwrap_or(false) != now_disabled {
+                toggled.push(old_addon.addon_name.clone());
             }
         }
     }
     
     let mut added: Vec<Addon> = Vec::new();
     let mut removed: Vec<String> = Vec::new();
+    let mut toggled: Vec<String> = Vec::new();
     
     // Process all addon categories
-    process_category(&old.mods, &new.mods, &mut added, &mut removed);
-    process_category(&old.resourcepacks, &new.resourcepacks, &mut added, &mut removed);
-    process_category(&old.shaderpacks, &new.shaderpacks, &mut added, &mut removed);
-    process_category(&old.datapacks, &new.datapacks, &mut added, &mut removed);
+    process_category(&old.mods, &new.mods, &mut added, &mut removed, &mut toggled);
+    process_category(&old.resourcepacks, &new.resourcepacks, &mut added, &mut removed, &mut toggled);
+    process_category(&old.shaderpacks, &new.shaderpacks, &mut added, &mut removed, &mut toggled);
+    process_category(&old.datapacks, &new.datapacks, &mut added, &mut removed, &mut toggled);
     
-    log::info!("compare_manifests: {} added, {} removed", added.len(), removed.len());
+    log::info!(
+        "compare_manifests: {} added, {} removed, {} toggled",
+        added.len(),
+        removed.len(),
+        toggled.len()
+    );
     
+    // Deleting a config the new manifest also ships would be contradictory, so shipping wins
+    let removed_config_files: Vec<String> = new.removed_config_files.iter()
+        .map(|path| path.replace('\\', "/"))
+        .filter(|path| !new.config_files.iter().any(|c| c.relative_path.replace('\\', "/") == *path))
+        .collect();
+    log::info!("compare_manifests: {} config files removed", removed_config_files.len());
+
+    let missing_dependencies = find_missing_dependencies(&old, &new);
+    if !missing_dependencies.is_empty() {
+        let described: Vec<String> = missing_dependencies.iter()
+            .map(|m| format!("{} needs {}", m.addon_name, m.dependency_name))
+            .collect();
+        if strict.unwrap_or(false) {
+            log::error!("compare_manifests: missing dependencies: {}", described.join(", "));
+            return Err(CemmError::validation(format!(
+                "Required dependencies would be missing: {}",
+                described.join(", ")
+            )));
+        }
+        log::warn!("compare_manifests: missing dependencies: {}", described.join(", "));
+    }
+
     let update_info = UpdateInfo {
         uuid: Uuid::new_v4().to_string(),
         timestamp: Utc::now().to_rfc3339(),
         added_addons: added,
         removed_addons: removed,
+        toggled_addons: toggled,
         config_files: vec![], // Placeholder, fill as needed
+        removed_config_files,
+        missing_dependencies,
     };
     log::info!("compare_manifests: update info generated");
     Ok(update_info)
@@ -288,13 +727,14 @@ fn slugify_curseforge_name(name: &str) -> String {
 }
 
 #[tauri::command]
-pub fn open_curseforge_url(addon_name: String) -> Result<(), String> {
+pub fn open_curseforge_url(addon_name: String) -> Result<(), CemmError> {
     let slug = slugify_curseforge_name(&addon_name);
     let url = format!("https://www.curseforge.com/minecraft/mc-mods/{}", slug);
-    opener::open(url).map_err(|e| format!("Failed to open browser: {e}"))
+    open_url(url)
 }
 
 #[tauri::command]
-pub fn open_url(url: String) -> Result<(), String> {
-    opener::open(url).map_err(|e| format!("Failed to open browser: {e}"))
+pub fn open_url(url: String) -> Result<(), CemmError> {
+    opener::open(&url)
+        .map_err(|e| CemmError::io_message(format!("Failed to open {url} in the browser: {e}"), Some(Path::new(&url))))
 }
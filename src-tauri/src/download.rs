@@ -0,0 +1,185 @@
+use crate::error::CemmError;
+use crate::retry::{send_with_retry, RetryPolicy};
+use reqwest::header::{ACCEPT_RANGES, CONTENT_RANGE, RANGE};
+use reqwest::{Client, StatusCode};
+use std::path::{Path, PathBuf};
+use tokio::fs as async_fs;
+use tokio::io::AsyncWriteExt;
+
+/// Progress reported while a file is streamed to disk.
+#[derive(Debug, Clone, Copy)]
+pub enum ProgressUpdate {
+    /// The full size of the file became known
+    Length(u64),
+    /// This many additional bytes are now on disk
+    Bytes(u64),
+}
+
+/// Returns the path a download is written to before it is complete (`<dest>.part`).
+pub fn partial_path(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    dest.with_file_name(name)
+}
+
+/// Streams `url` straight to `dest` without buffering the whole body in memory.
+///
+/// Data is written to `<dest>.part` and renamed once complete. If a partial file
+/// already exists, or the connection drops mid-transfer, the download continues
+/// from where it stopped using an HTTP Range request when the server supports it.
+/// Failed requests and interrupted transfers are retried according to `policy`.
+/// Returns the final size of the file in bytes.
+pub async fn download_to_file<F>(
+    client: &Client,
+    url: &str,
+    dest: &Path,
+    policy: &RetryPolicy,
+    mut on_progress: F,
+) -> Result<u64, CemmError>
+where
+    F: FnMut(ProgressUpdate),
+{
+    if let Some(parent) = dest.parent() {
+        async_fs::create_dir_all(parent).await
+            .map_err(|e| CemmError::io("Failed to create directory", parent, e))?;
+    }
+
+    let part_path = partial_path(dest);
+    let mut attempts = 0u32;
+    let mut reported_length = false;
+    let mut reported_bytes = 0u64;
+
+    loop {
+        let existing = async_fs::metadata(&part_path).await.map(|m| m.len()).unwrap_or(0);
+
+        if existing > 0 {
+            log::debug!("Resuming {} from byte {}", url, existing);
+        }
+
+        let mut resp = send_with_retry(policy, || {
+            let request = client.get(url);
+            if existing > 0 {
+                request.header(RANGE, format!("bytes={}-", existing))
+            } else {
+                request
+            }
+        })
+        .await
+        .map_err(|e| CemmError::network(&format!("Failed to download {}", url), e))?;
+        let status = resp.status();
+
+        if status == StatusCode::RANGE_NOT_SATISFIABLE && existing > 0 && attempts < policy.max_retries {
+            // The partial file does not match what the server has; start over.
+            log::warn!("Server rejected resume of {}, restarting download", url);
+            attempts += 1;
+            remove_partial(&part_path).await;
+            continue;
+        }
+        if !status.is_success() {
+            return Err(CemmError::from_response(&format!("Failed to download {}", url), resp).await);
+        }
+
+        let resumed = status == StatusCode::PARTIAL_CONTENT && existing > 0;
+        let supports_range = resumed
+            || resp.headers().get(ACCEPT_RANGES).and_then(|v| v.to_str().ok()) == Some("bytes");
+
+        let total = if resumed {
+            content_range_total(&resp).or_else(|| resp.content_length().map(|len| existing + len))
+        } else {
+            resp.content_length()
+        };
+        if let (false, Some(total)) = (reported_length, total) {
+            on_progress(ProgressUpdate::Length(total));
+            reported_length = true;
+        }
+
+        let mut file = if resumed {
+            async_fs::OpenOptions::new().append(true).open(&part_path).await
+        } else {
+            async_fs::File::create(&part_path).await
+        }
+        .map_err(|e| CemmError::io("Failed to open", &part_path, e))?;
+
+        let mut downloaded = if resumed { existing } else { 0 };
+        let mut interrupted: Option<String> = None;
+
+        loop {
+            match resp.chunk().await {
+                Ok(Some(chunk)) => {
+                    file.write_all(&chunk).await
+                        .map_err(|e| CemmError::io("Failed to write file", &part_path, e))?;
+                    downloaded += chunk.len() as u64;
+                    if downloaded > reported_bytes {
+                        on_progress(ProgressUpdate::Bytes(downloaded - reported_bytes));
+                        reported_bytes = downloaded;
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    interrupted = Some(format!("Failed to read bytes from {}: {}", url, e));
+                    break;
+                }
+            }
+        }
+
+        file.flush().await
+            .map_err(|e| CemmError::io("Failed to write file", &part_path, e))?;
+        drop(file);
+
+        if interrupted.is_none() {
+            if let Some(total) = total {
+                if downloaded < total {
+                    interrupted = Some(format!(
+                        "Download of {} ended early: got {} of {} bytes",
+                        url, downloaded, total
+                    ));
+                }
+            }
+        }
+
+        if let Some(error) = interrupted {
+            if attempts < policy.max_retries {
+                let delay = policy.backoff(attempts);
+                attempts += 1;
+                if supports_range {
+                    log::warn!("{}; resuming in {:?} (attempt {}/{})", error, delay, attempts, policy.max_retries);
+                } else {
+                    log::warn!("{}; restarting in {:?} (attempt {}/{})", error, delay, attempts, policy.max_retries);
+                    remove_partial(&part_path).await;
+                }
+                tokio::time::sleep(delay).await;
+                continue;
+            }
+            return Err(CemmError::network_message(error, url));
+        }
+
+        async_fs::rename(&part_path, dest).await
+            .map_err(|e| CemmError::io(&format!("Failed to move {} to", part_path.display()), dest, e))?;
+        return Ok(downloaded);
+    }
+}
+
+/// Deletes the `.part` file for `dest`, if any.
+pub async fn remove_partial_for(dest: &Path) {
+    remove_partial(&partial_path(dest)).await;
+}
+
+async fn remove_partial(part_path: &Path) {
+    if let Err(e) = async_fs::remove_file(part_path).await {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            log::warn!("Failed to remove partial file {}: {}", part_path.display(), e);
+        }
+    }
+}
+
+/// Parses the total size from a `Content-Range: bytes start-end/total` header.
+fn content_range_total(resp: &reqwest::Response) -> Option<u64> {
+    resp.headers()
+        .get(CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .rsplit('/')
+        .next()?
+        .parse()
+        .ok()
+}
@@ -0,0 +1,117 @@
+use crate::composables::manifest::{Addon, AddonEnvironment, Manifest, OverrideFile};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Vanilla and OptiFine/Iris settings that only exist on clients
+const CLIENT_CONFIG_FILES: [&str; 4] = ["options.txt", "optionsof.txt", "optionsshaders.txt", "servers.dat"];
+
+/// The kind of instance an install targets.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallSide {
+    #[default]
+    Client,
+    Server,
+}
+
+impl AddonEnvironment {
+    pub fn runs_on(self, side: InstallSide) -> bool {
+        match self {
+            AddonEnvironment::Both => true,
+            AddonEnvironment::Client => side == InstallSide::Client,
+            AddonEnvironment::Server => side == InstallSide::Server,
+        }
+    }
+}
+
+/// Whether `addon` belongs on `side`. Addons without a known environment go everywhere.
+pub fn addon_runs_on(addon: &Addon, side: InstallSide) -> bool {
+    match addon.environment {
+        Some(environment) => environment.runs_on(side),
+        None => true,
+    }
+}
+
+/// Whether an override file belongs on `side`. Files without an environment go everywhere.
+pub fn override_runs_on(file: &OverrideFile, side: InstallSide) -> bool {
+    match file.environment {
+        Some(environment) => environment.runs_on(side),
+        None => true,
+    }
+}
+
+/// Environment of a config file. An environment set in the manifest wins; otherwise
+/// Forge's `*-client.toml` files and the vanilla client settings are client-only and
+/// everything else is needed on both sides.
+pub fn config_environment(relative_path: &str, manifest: &Manifest) -> AddonEnvironment {
+    let relative_path = relative_path.replace('\\', "/");
+    let declared = manifest.config_files.iter()
+        .find(|c| c.relative_path.replace('\\', "/") == relative_path)
+        .and_then(|c| c.environment);
+    if let Some(environment) = declared {
+        return environment;
+    }
+
+    let file_name = relative_path.rsplit('/').next().unwrap_or_default().to_lowercase();
+    if file_name.ends_with("-client.toml") || CLIENT_CONFIG_FILES.contains(&file_name.as_str()) {
+        AddonEnvironment::Client
+    } else {
+        AddonEnvironment::Both
+    }
+}
+
+/// Reads the side a mod jar declares for itself.
+///
+/// Fabric's `environment`, Quilt's `minecraft.environment` and the `clientSideOnly` flag of
+/// Forge/NeoForge `mods.toml` are understood. Returns None if the jar declares nothing
+/// or can't be read, in which case the addon is installed on both sides.
+pub fn detect_jar_environment(path: &Path) -> Option<AddonEnvironment> {
+    let file = File::open(path).ok()?;
+    let mut archive = zip::ZipArchive::new(file).ok()?;
+
+    if let Some(metadata) = read_entry(&mut archive, "fabric.mod.json") {
+        let value: Value = serde_json::from_str(&metadata).ok()?;
+        return match value.get("environment").and_then(Value::as_str) {
+            Some("client") => Some(AddonEnvironment::Client),
+            Some("server") => Some(AddonEnvironment::Server),
+            // "*" and the default when the key is missing
+            _ => Some(AddonEnvironment::Both),
+        };
+    }
+
+    if let Some(metadata) = read_entry(&mut archive, "quilt.mod.json") {
+        let value: Value = serde_json::from_str(&metadata).ok()?;
+        let environment = value.get("minecraft").and_then(|m| m.get("environment"));
+        return match environment.and_then(Value::as_str) {
+            Some("client") => Some(AddonEnvironment::Client),
+            Some("dedicated_server") => Some(AddonEnvironment::Server),
+            _ => Some(AddonEnvironment::Both),
+        };
+    }
+
+    for name in ["META-INF/neoforge.mods.toml", "META-INF/mods.toml"] {
+        let Some(metadata) = read_entry(&mut archive, name) else {
+            continue;
+        };
+        let client_side_only = metadata.lines().any(|line| {
+            let line = line.split('#').next().unwrap_or_default();
+            let Some((key, value)) = line.split_once('=') else {
+                return false;
+            };
+            key.trim() == "clientSideOnly" && value.trim() == "true"
+        });
+        return client_side_only.then_some(AddonEnvironment::Client);
+    }
+
+    None
+}
+
+fn read_entry(archive: &mut zip::ZipArchive<File>, name: &str) -> Option<String> {
+    let mut entry = archive.by_name(name).ok()?;
+    let mut content = String::new();
+    entry.read_to_string(&mut content).ok()?;
+    Some(content)
+}
@@ -0,0 +1,200 @@
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+/// Error returned by every Tauri command.
+///
+/// Serialized with its stable `code` next to a human-readable `message` and any
+/// structured context, e.g. `{ "code": "http_status", "status": 502, "url": "...", "message": "..." }`,
+/// so the frontend can react to the kind of failure instead of matching English text.
+///
+/// This enum is mirrored in:
+/// - Rust: src-tauri/src/error.rs (this file)
+/// - TypeScript: app/types/index.ts (CemmError type)
+///
+/// When modifying this enum, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum CemmError {
+    /// The request never got a response: DNS, connection, TLS or timeout
+    Network { message: String, url: Option<String> },
+    /// The server answered with an unexpected status not covered by a more specific code
+    HttpStatus { message: String, status: u16, url: Option<String> },
+    /// A remote resource or local file does not exist
+    NotFound { message: String, resource: Option<String> },
+    /// The GitHub token is missing, invalid or lacks permissions
+    Auth { message: String },
+    /// The server asked to slow down
+    RateLimited { message: String, retry_after_secs: Option<u64> },
+    /// A path from a manifest or update points outside the directory it belongs to
+    PathTraversal { message: String, path: String },
+    /// Reading, writing, moving or deleting a local file failed
+    Io { message: String, path: Option<String> },
+    /// JSON, TOML or other data could not be parsed; `location` is the file path or URL it came from
+    Parse { message: String, location: Option<String> },
+    /// The input is invalid or the operation is not allowed in the current state
+    Validation { message: String },
+    /// The user stopped the operation: cancel_install, cancel_upload or a dialog closed without a selection
+    Cancelled { message: String },
+}
+
+/// Message of `CemmError::Cancelled`.
+pub const CANCELLED_MESSAGE: &str = "Operation cancelled";
+
+impl CemmError {
+    /// Stable identifier of the error kind, the same as the serialized `code`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            CemmError::Network { .. } => "network",
+            CemmError::HttpStatus { .. } => "http_status",
+            CemmError::NotFound { .. } => "not_found",
+            CemmError::Auth { .. } => "auth",
+            CemmError::RateLimited { .. } => "rate_limited",
+            CemmError::PathTraversal { .. } => "path_traversal",
+            CemmError::Io { .. } => "io",
+            CemmError::Parse { .. } => "parse",
+            CemmError::Validation { .. } => "validation",
+            CemmError::Cancelled { .. } => "cancelled",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            CemmError::Network { message, .. }
+            | CemmError::HttpStatus { message, .. }
+            | CemmError::NotFound { message, .. }
+            | CemmError::Auth { message }
+            | CemmError::RateLimited { message, .. }
+            | CemmError::PathTraversal { message, .. }
+            | CemmError::Io { message, .. }
+            | CemmError::Parse { message, .. }
+            | CemmError::Validation { message }
+            | CemmError::Cancelled { message } => message,
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, CemmError::Cancelled { .. })
+    }
+
+    pub fn cancelled() -> Self {
+        CemmError::Cancelled { message: CANCELLED_MESSAGE.to_string() }
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        CemmError::Validation { message: message.into() }
+    }
+
+    pub fn not_found(message: impl Into<String>, resource: impl Into<String>) -> Self {
+        CemmError::NotFound { message: message.into(), resource: Some(resource.into()) }
+    }
+
+    pub fn path_traversal(message: impl Into<String>, path: impl Into<String>) -> Self {
+        CemmError::PathTraversal { message: message.into(), path: path.into() }
+    }
+
+    /// An IO failure on `path`, e.g. `CemmError::io("Failed to read", &path, e)`.
+    /// Missing files are reported as `NotFound`.
+    pub fn io(action: &str, path: &Path, error: std::io::Error) -> Self {
+        let message = format!("{} {}: {}", action, path.display(), error);
+        let path = path.display().to_string();
+        if error.kind() == std::io::ErrorKind::NotFound {
+            CemmError::NotFound { message, resource: Some(path) }
+        } else {
+            CemmError::Io { message, path: Some(path) }
+        }
+    }
+
+    /// A local failure that did not come with an `std::io::Error`, e.g. a dialog that stopped
+    /// responding. `path` is the file or location it concerns, if there is one.
+    pub fn io_message(message: impl Into<String>, path: Option<&Path>) -> Self {
+        CemmError::Io { message: message.into(), path: path.map(|p| p.display().to_string()) }
+    }
+
+    /// Data from `location` (a file path or URL) that could not be parsed.
+    pub fn parse(message: impl Into<String>, location: impl Into<String>) -> Self {
+        CemmError::Parse { message: message.into(), location: Some(location.into()) }
+    }
+
+    /// A request that failed before a response arrived, or whose body could not be read.
+    pub fn network(context: &str, error: reqwest::Error) -> Self {
+        CemmError::Network {
+            message: format!("{}: {}", context, error),
+            url: error.url().map(|url| url.to_string()),
+        }
+    }
+
+    /// A transfer from `url` that failed after the response arrived, e.g. because it ended early.
+    pub fn network_message(message: impl Into<String>, url: &str) -> Self {
+        CemmError::Network { message: message.into(), url: Some(url.to_string()) }
+    }
+
+    /// Maps an unsuccessful response to the most specific code, consuming its body for the message.
+    pub async fn from_response(context: &str, resp: Response) -> Self {
+        let status = resp.status();
+        let url = resp.url().to_string();
+        let retry_after_secs = resp.headers()
+            .get(RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse().ok());
+        // GitHub reports exhausted rate limits as 403 with no requests remaining
+        let rate_limit_exhausted = resp.headers()
+            .get("x-ratelimit-remaining")
+            .and_then(|v| v.to_str().ok())
+            == Some("0");
+        let body = resp.text().await.unwrap_or_default();
+        let message = if body.trim().is_empty() {
+            format!("{} (HTTP {})", context, status)
+        } else {
+            format!("{} (HTTP {}): {}", context, status, body.trim())
+        };
+        Self::from_status(status, message, url, retry_after_secs, rate_limit_exhausted)
+    }
+
+    fn from_status(
+        status: StatusCode,
+        message: String,
+        url: String,
+        retry_after_secs: Option<u64>,
+        rate_limit_exhausted: bool,
+    ) -> Self {
+        match status {
+            StatusCode::TOO_MANY_REQUESTS => CemmError::RateLimited { message, retry_after_secs },
+            StatusCode::FORBIDDEN if rate_limit_exhausted || retry_after_secs.is_some() => {
+                CemmError::RateLimited { message, retry_after_secs }
+            }
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => CemmError::Auth { message },
+            StatusCode::NOT_FOUND => CemmError::NotFound { message, resource: Some(url) },
+            _ => CemmError::HttpStatus { message, status: status.as_u16(), url: Some(url) },
+        }
+    }
+
+    /// Prefixes the message with what was being done, keeping the code and context.
+    pub fn context(mut self, context: &str) -> Self {
+        let message = match &mut self {
+            CemmError::Network { message, .. }
+            | CemmError::HttpStatus { message, .. }
+            | CemmError::NotFound { message, .. }
+            | CemmError::Auth { message }
+            | CemmError::RateLimited { message, .. }
+            | CemmError::PathTraversal { message, .. }
+            | CemmError::Io { message, .. }
+            | CemmError::Parse { message, .. }
+            | CemmError::Validation { message }
+            | CemmError::Cancelled { message } => message,
+        };
+        *message = format!("{}: {}", context, message);
+        self
+    }
+}
+
+impl fmt::Display for CemmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for CemmError {}
+
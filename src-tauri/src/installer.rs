@@ -1,30 +1,60 @@
-use crate::composables::manifest::Manifest;
+use crate::cache::{link_or_copy, DownloadCache};
+use crate::cancel::{CancellationToken, OperationKind, Operations};
+use crate::composables::manifest::{Addon, Manifest};
+use crate::download::{download_to_file, remove_partial_for, ProgressUpdate};
+use crate::environment::{addon_runs_on, config_environment, override_runs_on, InstallSide};
+use crate::error::CemmError;
+use crate::instance::{
+    check_compatibility, instance_file_path, read_instance_target, sync_installed_addons, Compatibility,
+    INSTANCE_FILE,
+};
+use crate::integrity::{sha256_file, verify_addon_file};
+use crate::merge::{config_base_path, merge_config, ConfigConflict, ConfigFormat, MergeResult};
+use crate::overrides::{normalize_override_path, OverrideFileWithContent, OVERRIDES_CATEGORY};
+use crate::preflight::check_plan;
+use crate::receipt::{manifest_hash, InstallReceipt, ReceiptFile, ReceiptFileKind, RECEIPT_PATH};
+use crate::retry::RetryPolicy;
+use crate::snapshot::{SnapshotStore, DEFAULT_SNAPSHOT_RETENTION, SNAPSHOT_FOLDERS};
+use crate::transaction::InstallTransaction;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use tauri::command;
 use tokio::fs as async_fs;
-use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
 use tauri::Window;
 use tauri::Emitter;
+use tauri::Manager;
 
 /// Validates that a path stays within the base directory (prevents path traversal attacks).
 /// Returns the canonicalized destination path if valid, or an error if path traversal is detected.
-fn validate_path_within_base(base_path: &Path, relative_path: &str) -> Result<PathBuf, String> {
+pub fn validate_path_within_base(base_path: &Path, relative_path: &str) -> Result<PathBuf, CemmError> {
     // Check for obvious path traversal patterns in the relative path
     if relative_path.contains("..") {
-        return Err(format!("Path traversal detected: '{}' contains '..'", relative_path));
+        return Err(CemmError::path_traversal(
+            format!("Path traversal detected: '{}' contains '..'", relative_path),
+            relative_path,
+        ));
     }
     
     // Check for absolute paths (Windows and Unix)
     if relative_path.starts_with('/') ||
        (relative_path.len() > 1 && relative_path.chars().nth(1) == Some(':')) {
-        return Err(format!("Path traversal detected: '{}' is an absolute path", relative_path));
+        return Err(CemmError::path_traversal(
+            format!("Path traversal detected: '{}' is an absolute path", relative_path),
+            relative_path,
+        ));
     }
     
     // Check for home directory expansion
     if relative_path.starts_with('~') {
-        return Err(format!("Path traversal detected: '{}' references home directory", relative_path));
+        return Err(CemmError::path_traversal(
+            format!("Path traversal detected: '{}' references home directory", relative_path),
+            relative_path,
+        ));
     }
     
     // Join the paths
@@ -32,7 +62,7 @@ fn validate_path_within_base(base_path: &Path, relative_path: &str) -> Result<Pa
     
     // Canonicalize the base path (the directory must exist)
     let canonical_base = base_path.canonicalize()
-        .map_err(|e| format!("Failed to canonicalize base path: {}", e))?;
+        .map_err(|e| CemmError::io("Failed to canonicalize base path", base_path, e))?;
     
     // For the destination, we need to handle the case where parent directories don't exist yet
     // We canonicalize the parent if it exists, or check the path components
@@ -47,9 +77,9 @@ fn validate_path_within_base(base_path: &Path, relative_path: &str) -> Result<Pa
         if current.exists() {
             // Rebuild the path from the canonicalized parent
             let canonical_parent = current.canonicalize()
-                .map_err(|e| format!("Failed to canonicalize parent path: {}", e))?;
+                .map_err(|e| CemmError::io("Failed to canonicalize parent path", &current, e))?;
             let remaining = dest.strip_prefix(&current)
-                .map_err(|e| format!("Failed to strip prefix: {}", e))?;
+                .map_err(|e| CemmError::validation(format!("Failed to strip prefix: {}", e)))?;
             canonical_parent.join(remaining)
         } else {
             // No parent exists, use the base path
@@ -60,16 +90,16 @@ fn validate_path_within_base(base_path: &Path, relative_path: &str) -> Result<Pa
     // Try to canonicalize the destination (or its calculation)
     let canonical_dest = if dest.exists() {
         dest.canonicalize()
-            .map_err(|e| format!("Failed to canonicalize destination path: {}", e))?
+            .map_err(|e| CemmError::io("Failed to canonicalize destination path", &dest, e))?
     } else {
         dest_for_check
     };
     
     // Verify the destination is within the base directory
     if !canonical_dest.starts_with(&canonical_base) {
-        return Err(format!(
-            "Path traversal detected: '{}' resolves outside the modpack directory",
-            relative_path
+        return Err(CemmError::path_traversal(
+            format!("Path traversal detected: '{}' resolves outside the modpack directory", relative_path),
+            relative_path,
         ));
     }
     
@@ -91,315 +121,1412 @@ pub struct ConfigFile {
     pub content: String,
 }
 
+/// Default number of addon downloads that run at the same time
+const DEFAULT_MAX_CONCURRENT_DOWNLOADS: usize = 6;
+/// Upper bound for the configurable download worker limit
+const MAX_CONCURRENT_DOWNLOADS_LIMIT: usize = 32;
+
 /// Options for install_update function
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct InstallOptions {
-    /// Old manifest for cleanup of removed/updated addons
+    /// Old manifest for cleanup of removed/updated addons. Only used when the
+    /// instance has no install receipt yet; the receipt always takes precedence.
     pub old_manifest: Option<Manifest>,
-    /// Whether to perform cleanup of old files (default: true when old_manifest provided)
+    /// Whether to perform cleanup of old files
     #[serde(default)]
     pub cleanup_old: bool,
+    /// Id of the update being applied, recorded in the install receipt
+    #[serde(default)]
+    pub update_id: Option<String>,
+    /// Maximum number of addon downloads running in parallel (default: 6, max: 32)
+    #[serde(default)]
+    pub max_concurrent_downloads: Option<usize>,
+    /// Stage all files and swap them in only after everything succeeded (default: true)
+    #[serde(default)]
+    pub transactional: Option<bool>,
+    /// Retry policy for downloads (default: 3 retries with exponential backoff)
+    #[serde(default)]
+    pub retry: Option<RetryPolicy>,
+    /// Serve addons from the shared download cache and add new downloads to it (default: true)
+    #[serde(default)]
+    pub use_cache: Option<bool>,
+    /// Snapshot the addon folders and overwritten configs before changing anything (default: true)
+    #[serde(default)]
+    pub snapshot: Option<bool>,
+    /// Number of snapshots kept per instance (default: 5)
+    #[serde(default)]
+    pub snapshot_retention: Option<usize>,
+    /// Three-way merge config files with the user's local changes instead of overwriting them (default: true)
+    #[serde(default)]
+    pub merge_configs: Option<bool>,
+    /// Record changed and removed addons in minecraftinstance.json after installing (default: false)
+    #[serde(default)]
+    pub update_instance_file: Option<bool>,
+    /// Install even if the manifest targets another Minecraft version or mod loader
+    /// than the instance, reporting the mismatch as a warning (default: false)
+    #[serde(default)]
+    pub allow_incompatible: Option<bool>,
+    /// Kind of instance being installed; addons and configs for the other side are skipped (default: client)
+    #[serde(default)]
+    pub side: Option<InstallSide>,
+    /// Check free space, write access and locked files before changing anything (default: true)
+    #[serde(default)]
+    pub preflight: Option<bool>,
 }
 
-/// Unified install function that handles all installation scenarios
-#[command]
-pub async fn install_update(
-    window: Window,
-    modpack_path: String,
-    manifest: Manifest,
-    config_files: Vec<ConfigFile>,
-    options: Option<InstallOptions>,
-) -> Result<(), String> {
-    let options = options.unwrap_or_default();
-    let client = Client::new();
-
-    // Helper to emit progress
-    fn emit_progress(window: &Window, progress: usize, total: usize, msg: &str) {
-        let _ = Emitter::emit(window, "install-progress", Some(serde_json::json!({
-            "progress": if total > 0 { (progress as f64) / (total as f64) * 100.0 } else { 100.0 },
-            "message": msg
-        })));
-    }
-
-    // Helper to download and save a file
-    async fn download_and_save(
-        client: &Client,
-        url: &str,
-        dest_path: &Path,
-    ) -> Result<(), String> {
-        let resp = client.get(url).send().await
-            .map_err(|e| format!("Failed to download {}: {}", url, e))?;
-        
-        if !resp.status().is_success() {
-            return Err(format!("Failed to download {}: HTTP {}", url, resp.status()));
+impl InstallOptions {
+    pub fn is_transactional(&self) -> bool {
+        self.transactional.unwrap_or(true)
+    }
+
+    pub fn download_concurrency(&self) -> usize {
+        self.max_concurrent_downloads
+            .unwrap_or(DEFAULT_MAX_CONCURRENT_DOWNLOADS)
+            .clamp(1, MAX_CONCURRENT_DOWNLOADS_LIMIT)
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry.clone().unwrap_or_default()
+    }
+
+    pub fn uses_cache(&self) -> bool {
+        self.use_cache.unwrap_or(true)
+    }
+
+    pub fn takes_snapshot(&self) -> bool {
+        self.snapshot.unwrap_or(true)
+    }
+
+    pub fn snapshot_retention(&self) -> usize {
+        self.snapshot_retention.unwrap_or(DEFAULT_SNAPSHOT_RETENTION).max(1)
+    }
+
+    pub fn merges_configs(&self) -> bool {
+        self.merge_configs.unwrap_or(true)
+    }
+
+    pub fn updates_instance_file(&self) -> bool {
+        self.update_instance_file.unwrap_or(false)
+    }
+
+    pub fn allows_incompatible(&self) -> bool {
+        self.allow_incompatible.unwrap_or(false)
+    }
+
+    pub fn install_side(&self) -> InstallSide {
+        self.side.unwrap_or_default()
+    }
+
+    pub fn runs_preflight(&self) -> bool {
+        self.preflight.unwrap_or(true)
+    }
+}
+
+/// An addon category together with the folder its addons install into by default.
+pub struct AddonCategory<'a> {
+    pub folder: &'static str,
+    pub label: &'static str,
+    pub addons: &'a [Addon],
+}
+
+impl AddonCategory<'_> {
+    /// Folder `addon` installs into, relative to the instance root. See `addon_folder`.
+    pub fn folder_for(&self, modpack_path: &Path, addon: &Addon) -> Result<String, CemmError> {
+        addon_folder(modpack_path, addon, self.folder)
+    }
+}
+
+/// Resolves the folder an addon installs into, relative to the instance root.
+///
+/// A relative `mod_folder_path` such as `config/openloader/data` or `global_packs` is used
+/// as is, after the same traversal checks as config files. Empty and absolute paths (as
+/// written by the CurseForge app on the admin's machine) fall back to `default_folder`.
+pub fn addon_folder(modpack_path: &Path, addon: &Addon, default_folder: &str) -> Result<String, CemmError> {
+    let declared = addon.mod_folder_path.trim().replace('\\', "/");
+    let declared = declared.trim_end_matches('/');
+    let is_absolute = declared.starts_with('/') || declared.chars().nth(1) == Some(':');
+    if declared.is_empty() || is_absolute {
+        return Ok(default_folder.to_string());
+    }
+    if declared.split('/').next() == Some(".cemm") {
+        return Err(CemmError::path_traversal(
+            format!("Refusing to install '{}' into {}: it belongs to CEMM", addon.addon_name, declared),
+            declared,
+        ));
+    }
+    validate_path_within_base(modpack_path, declared)?;
+    Ok(declared.to_string())
+}
+
+pub fn addon_categories(manifest: &Manifest) -> [AddonCategory<'_>; 4] {
+    [
+        AddonCategory { folder: "mods", label: "mod", addons: &manifest.mods },
+        AddonCategory { folder: "resourcepacks", label: "resourcepack", addons: &manifest.resourcepacks },
+        AddonCategory { folder: "shaderpacks", label: "shaderpack", addons: &manifest.shaderpacks },
+        AddonCategory { folder: "datapacks", label: "datapack", addons: &manifest.datapacks },
+    ]
+}
+
+/// A single addon download scheduled by install_update.
+#[derive(Debug, Clone)]
+pub struct DownloadJob {
+    pub addon: Addon,
+    pub dest: PathBuf,
+    pub label: &'static str,
+}
+
+/// Everything a download worker needs, cloned into each task.
+#[derive(Debug, Clone)]
+pub struct DownloadContext {
+    pub client: Client,
+    pub policy: RetryPolicy,
+    pub cache: Option<DownloadCache>,
+    pub cancel: CancellationToken,
+}
+
+/// Minimum time between two byte-level `install-progress` events
+const BYTE_PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Payload of the `install-progress` event.
+///
+/// `progress` is the share of files finished, in percent. The byte fields are only
+/// present while addons are being downloaded.
+#[derive(Debug, Clone, Serialize)]
+struct InstallProgress<'a> {
+    progress: f64,
+    message: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bytes_done: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bytes_total: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bytes_per_second: Option<u64>,
+}
+
+fn percent(progress: usize, total: usize) -> f64 {
+    if total > 0 { (progress as f64) / (total as f64) * 100.0 } else { 100.0 }
+}
+
+/// Emits an `install-progress` event to the frontend.
+pub fn emit_progress(window: &Window, progress: usize, total: usize, msg: &str) {
+    let _ = Emitter::emit(window, "install-progress", Some(InstallProgress {
+        progress: percent(progress, total),
+        message: msg,
+        bytes_done: None,
+        bytes_total: None,
+        bytes_per_second: None,
+    }));
+}
+
+/// Byte counters for all downloads of one install.
+struct ByteProgress {
+    done: u64,
+    total: u64,
+    last_emit: Instant,
+    last_emit_done: u64,
+    bytes_per_second: f64,
+}
+
+impl ByteProgress {
+    fn new(total: u64) -> Self {
+        Self {
+            done: 0,
+            total,
+            last_emit: Instant::now(),
+            last_emit_done: 0,
+            bytes_per_second: 0.0,
         }
-        
-        let bytes = resp.bytes().await
-            .map_err(|e| format!("Failed to read bytes from {}: {}", url, e))?;
-        
-        if let Some(parent) = dest_path.parent() {
-            async_fs::create_dir_all(parent).await
-                .map_err(|e| format!("Failed to create directory {}: {}", parent.display(), e))?;
+    }
+
+    fn apply(&mut self, update: ProgressUpdate) {
+        match update {
+            ProgressUpdate::Length(len) => self.total += len,
+            ProgressUpdate::Bytes(bytes) => self.done += bytes,
         }
-        
-        let mut file = async_fs::File::create(dest_path).await
-            .map_err(|e| format!("Failed to create file {}: {}", dest_path.display(), e))?;
-        
-        file.write_all(&bytes).await
-            .map_err(|e| format!("Failed to write file {}: {}", dest_path.display(), e))?;
-        
-        Ok(())
     }
 
-    // Calculate diff once for both cleanup and selective downloads
-    let diff = if let Some(ref old_manifest) = options.old_manifest {
-        Some(calculate_update_diff(old_manifest, &manifest)?)
-    } else {
-        None
-    };
+    /// Emits a byte-level event if enough time has passed since the last one (or `force` is set).
+    fn emit(&mut self, window: &Window, completed: usize, total_files: usize, msg: &str, force: bool) {
+        let elapsed = self.last_emit.elapsed();
+        if !force && elapsed < BYTE_PROGRESS_INTERVAL {
+            return;
+        }
+        if elapsed >= BYTE_PROGRESS_INTERVAL {
+            // Smooth the rate so the UI does not jump around between chunks
+            let instant_rate = (self.done - self.last_emit_done) as f64 / elapsed.as_secs_f64();
+            self.bytes_per_second = if self.bytes_per_second == 0.0 {
+                instant_rate
+            } else {
+                self.bytes_per_second * 0.7 + instant_rate * 0.3
+            };
+            self.last_emit = Instant::now();
+            self.last_emit_done = self.done;
+        }
+
+        let _ = Emitter::emit(window, "install-progress", Some(InstallProgress {
+            progress: percent(completed, total_files),
+            message: msg,
+            bytes_done: Some(self.done),
+            bytes_total: Some(self.total.max(self.done)),
+            bytes_per_second: Some(self.bytes_per_second as u64),
+        }));
+    }
+}
 
-    // Step 1: Cleanup old files if requested and diff was calculated
-    if options.cleanup_old {
-        if let (Some(ref old_manifest), Some(ref diff)) = (options.old_manifest.as_ref(), &diff) {
-            remove_old_files(&modpack_path, old_manifest, diff).await?;
+/// Streams an addon to `dest_path`, rejecting files that fail verification.
+///
+/// A verified copy in `cache` is used instead of downloading when available.
+/// Otherwise each of the addon's download URLs is tried in order until one yields
+/// a file that passes verification, which is then added to the cache.
+async fn download_and_save<F>(
+    ctx: &DownloadContext,
+    addon: &Addon,
+    dest_path: &Path,
+    mut on_progress: F,
+) -> Result<(), CemmError>
+where
+    F: FnMut(ProgressUpdate),
+{
+    if let Some(cache) = &ctx.cache {
+        if let Some(size) = cache.restore(addon, dest_path).await {
+            on_progress(ProgressUpdate::Length(size));
+            on_progress(ProgressUpdate::Bytes(size));
+            return Ok(());
         }
     }
 
-    // Step 2: Install only changed/new addons and all config files
-    let mut installed_paths: Vec<std::path::PathBuf> = Vec::new();
-    let mut current = 0usize;
+    let urls = addon.download_urls();
+    let mut last_error = CemmError::validation(format!("No download URL for '{}'", addon.addon_name));
 
-    /// Determines if an addon needs to be downloaded during an update.
-    /// Returns true if the addon is:
-    /// - New (not in old manifest)
-    /// - Updated (same project_id, different version)
-    /// - File doesn't exist on disk (safety fallback)
-    fn should_download_addon(
-        addon: &crate::composables::manifest::Addon,
-        old_addons: &[crate::composables::manifest::Addon],
-        diff: &UpdateDiff,
-        dest_path: &Path,
-    ) -> bool {
-        // Check if this is a new addon
-        let is_new = !old_addons.iter().any(|old| old.addon_project_id == addon.addon_project_id);
-        if is_new {
-            return true;
+    for (index, url) in urls.iter().enumerate() {
+        if index > 0 {
+            log::warn!("Trying fallback URL {}/{} for '{}': {}", index + 1, urls.len(), addon.addon_name, url);
+            // A partial file from another source cannot be resumed
+            remove_partial_for(dest_path).await;
         }
 
-        // Check if this addon was updated (version changed)
-        let is_updated = diff.updated_addon_ids.contains(&addon.addon_project_id);
-        if is_updated {
-            return true;
+        if let Err(e) = download_to_file(&ctx.client, url, dest_path, &ctx.policy, &mut on_progress).await {
+            log::warn!("Download of '{}' from {} failed: {}", addon.addon_name, url, e);
+            last_error = e;
+            continue;
         }
 
-        // Safety fallback: download if file doesn't exist
-        !dest_path.exists()
+        match verify_addon_file(addon, dest_path).await {
+            Ok(()) => {
+                if let Some(cache) = &ctx.cache {
+                    cache.store(addon, dest_path).await;
+                }
+                return Ok(());
+            }
+            Err(e) => {
+                log::error!("Verification failed for {} from {}: {}", addon.addon_name, url, e);
+                if let Err(remove_err) = async_fs::remove_file(dest_path).await {
+                    log::warn!("Failed to remove rejected file {}: {}", dest_path.display(), remove_err);
+                }
+                last_error = e.context(&format!("Downloaded file for '{}' failed verification", addon.addon_name));
+            }
+        }
     }
 
-    // Count files that actually need downloading for accurate progress
-    let files_to_download = {
-        let mut count = 0usize;
-        
-        // Count mods
-        for addon in &manifest.mods {
-            if addon.disabled == Some(true) {
+    Err(last_error)
+}
+
+/// Runs the download jobs with at most `concurrency` downloads in flight.
+///
+/// Progress is reported from this task only, so events arrive in completion order
+/// with a monotonically increasing count. Workers stream byte counts back over a
+/// channel. On the first failure all remaining downloads are aborted and their
+/// `.part` files are removed.
+pub async fn run_downloads(
+    window: &Window,
+    ctx: &DownloadContext,
+    jobs: Vec<DownloadJob>,
+    concurrency: usize,
+    completed: &mut usize,
+    total: usize,
+) -> Result<(), CemmError> {
+    // Sizes known from the manifest are counted up front; the rest are added
+    // as soon as the server reports them.
+    let known_bytes: u64 = jobs.iter().filter_map(|job| job.addon.file_length).sum();
+    let mut bytes = ByteProgress::new(known_bytes);
+    let (progress_tx, mut progress_rx) = mpsc::unbounded_channel::<ProgressUpdate>();
+
+    let mut pending = jobs.into_iter();
+    let mut in_flight: JoinSet<(DownloadJob, Result<(), CemmError>)> = JoinSet::new();
+    let mut in_flight_dests: HashSet<PathBuf> = HashSet::new();
+
+    loop {
+        while in_flight.len() < concurrency {
+            let Some(job) = pending.next() else {
+                break;
+            };
+            in_flight_dests.insert(job.dest.clone());
+            let ctx = ctx.clone();
+            let progress_tx = progress_tx.clone();
+            let size_known = job.addon.file_length.is_some();
+            in_flight.spawn(async move {
+                let result = download_and_save(&ctx, &job.addon, &job.dest, |update| {
+                    if !(size_known && matches!(update, ProgressUpdate::Length(_))) {
+                        let _ = progress_tx.send(update);
+                    }
+                }).await;
+                (job, result)
+            });
+        }
+
+        let joined = tokio::select! {
+            Some(update) = progress_rx.recv() => {
+                bytes.apply(update);
+                bytes.emit(window, *completed, total, &format!("Downloading {}/{} files", *completed, total), false);
                 continue;
             }
-            let dest = Path::new(&modpack_path).join("mods").join(&addon.file_name_on_disk);
-            if let Some(ref d) = diff {
-                if let Some(ref old_manifest) = options.old_manifest {
-                    if should_download_addon(addon, &old_manifest.mods, d, &dest) {
-                        count += 1;
-                    }
+            joined = in_flight.join_next() => joined,
+            _ = ctx.cancel.cancelled() => {
+                log::info!("Downloads cancelled with {} in flight", in_flight_dests.len());
+                abort_downloads(&mut in_flight, &in_flight_dests).await;
+                return Err(CemmError::cancelled());
+            }
+        };
+
+        let Some(joined) = joined else {
+            break;
+        };
+
+        let error = match joined {
+            Ok((job, Ok(()))) => {
+                in_flight_dests.remove(&job.dest);
+                *completed += 1;
+                while let Ok(update) = progress_rx.try_recv() {
+                    bytes.apply(update);
                 }
-            } else {
-                // No diff means fresh install - download everything
-                count += 1;
+                bytes.emit(window, *completed, total, &format!("Installed {}: {}", job.label, job.addon.addon_name), true);
+                continue;
+            }
+            Ok((job, Err(e))) => {
+                log::error!("Download of {} '{}' failed: {}", job.label, job.addon.addon_name, e);
+                e
+            }
+            Err(e) => CemmError::validation(format!("Download task failed: {}", e)),
+        };
+
+        abort_downloads(&mut in_flight, &in_flight_dests).await;
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+/// Stops all running download tasks and removes the partial files they were writing.
+///
+/// Downloads only replace `dest` once they are complete, so `dest` itself still holds
+/// the file that was there before and is left alone.
+async fn abort_downloads(
+    in_flight: &mut JoinSet<(DownloadJob, Result<(), CemmError>)>,
+    in_flight_dests: &HashSet<PathBuf>,
+) {
+    // Wait for the tasks to stop before removing their partial files
+    in_flight.abort_all();
+    while in_flight.join_next().await.is_some() {}
+    for dest in in_flight_dests {
+        remove_partial_for(dest).await;
+    }
+}
+
+/// What install_update will do with a single file.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanAction {
+    /// The file does not exist yet and will be downloaded or written
+    Download,
+    /// An existing file will be replaced
+    Overwrite,
+    /// An existing file will be deleted
+    Delete,
+    /// An existing addon file is renamed to or from its `.disabled` variant
+    Rename,
+    /// The file is left as it is
+    Skip,
+}
+
+/// Why a file ended up with its planned action.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanReason {
+    /// No old manifest was given, so every enabled addon is installed
+    FreshInstall,
+    /// The addon is not part of the old manifest
+    NewAddon,
+    /// The addon's version differs from the old manifest
+    UpdatedAddon,
+    /// The addon is unchanged but its file is missing on disk
+    MissingOnDisk,
+    /// The addon or override file is unchanged and already on disk
+    Unchanged,
+    /// The addon is disabled in the manifest
+    Disabled,
+    /// The addon is enabled again and its `.disabled` file is renamed back
+    Enabled,
+    /// The addon was removed in the new manifest
+    RemovedAddon,
+    /// The file belongs to an older version of an updated addon
+    OutdatedVersion,
+    /// Config files shipped with the update are always written
+    ConfigFile,
+    /// The update deletes this config file
+    RemovedConfig,
+    /// Override files that differ from the copy on disk are written
+    OverrideFile,
+    /// An override file of an earlier install that the manifest no longer lists
+    RemovedOverride,
+    /// The addon, config or override file is only needed on the other side (client or server)
+    OtherSide,
+}
+
+/// A single file in an install plan.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/installer.rs (this file)
+/// - TypeScript: app/types/index.ts (PlannedFile interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedFile {
+    pub action: PlanAction,
+    pub reason: PlanReason,
+    /// "mods", "resourcepacks", "shaderpacks", "datapacks", "config" or "overrides"
+    pub category: String,
+    /// Addon name, or file name for config and override files
+    pub name: String,
+    /// Path relative to the modpack directory, using forward slashes
+    pub relative_path: String,
+    /// Size in bytes of the file that will be written or deleted, if known
+    pub expected_size: Option<u64>,
+    /// For renames, the path the file currently has, relative to the modpack directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub renamed_from: Option<String>,
+}
+
+/// Everything install_update would do for a given set of inputs.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/installer.rs (this file)
+/// - TypeScript: app/types/index.ts (InstallPlan interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallPlan {
+    pub files: Vec<PlannedFile>,
+    /// Sum of the expected sizes of all files that will be downloaded or written
+    pub total_download_bytes: u64,
+    /// Number of downloads whose size is not known in advance
+    pub unknown_size_downloads: usize,
+}
+
+/// Where the content of a planned file comes from.
+enum StepSource<'a> {
+    Addon { addon: &'a Addon, label: &'static str },
+    /// An addon file already on disk that only changes between enabled and disabled
+    Toggle { addon: &'a Addon, from: PathBuf },
+    Config(&'a ConfigFile),
+    Override(&'a OverrideFileWithContent),
+    Existing,
+}
+
+/// A planned file together with what install_update needs to carry it out.
+struct PlanStep<'a> {
+    file: PlannedFile,
+    source: StepSource<'a>,
+    /// Absolute destination inside the modpack directory
+    dest: PathBuf,
+}
+
+impl PlanStep<'_> {
+    fn writes(&self) -> bool {
+        matches!(self.file.action, PlanAction::Download | PlanAction::Overwrite)
+    }
+}
+
+impl InstallPlan {
+    fn from_steps(steps: &[PlanStep<'_>]) -> Self {
+        let mut total_download_bytes = 0u64;
+        let mut unknown_size_downloads = 0usize;
+        for step in steps.iter().filter(|s| s.writes()) {
+            match step.file.expected_size {
+                Some(size) => total_download_bytes += size,
+                None => unknown_size_downloads += 1,
             }
         }
-        
-        // Count resourcepacks
-        for addon in &manifest.resourcepacks {
-            if addon.disabled == Some(true) {
-                continue;
+        Self {
+            files: steps.iter().map(|s| s.file.clone()).collect(),
+            total_download_bytes,
+            unknown_size_downloads,
+        }
+    }
+}
+
+/// Determines if an addon needs to be downloaded during an update.
+/// Returns the reason if the addon is:
+/// - New (not in old manifest)
+/// - Updated (same project_id, different version)
+/// - File doesn't exist on disk (safety fallback)
+fn download_reason(
+    addon: &Addon,
+    old_addons: &[Addon],
+    diff: &UpdateDiff,
+    dest_path: &Path,
+) -> Option<PlanReason> {
+    // Check if this is a new addon
+    let is_new = !old_addons.iter().any(|old| old.addon_project_id == addon.addon_project_id);
+    if is_new {
+        return Some(PlanReason::NewAddon);
+    }
+
+    // Check if this addon was updated (version changed)
+    let is_updated = diff.updated_addon_ids.contains(&addon.addon_project_id);
+    if is_updated {
+        return Some(PlanReason::UpdatedAddon);
+    }
+
+    // Safety fallback: download if file doesn't exist
+    if !dest_path.exists() {
+        return Some(PlanReason::MissingOnDisk);
+    }
+
+    None
+}
+
+fn write_action(dest: &Path) -> PlanAction {
+    if dest.exists() {
+        PlanAction::Overwrite
+    } else {
+        PlanAction::Download
+    }
+}
+
+/// Works out every file install_update will delete, write or skip. Only reads from disk.
+async fn build_plan_steps<'a>(
+    modpack_path: &Path,
+    manifest: &'a Manifest,
+    config_files: &'a [ConfigFile],
+    overrides: &'a [OverrideFileWithContent],
+    options: &'a InstallOptions,
+    receipt: Option<&'a InstallReceipt>,
+) -> Result<Vec<PlanStep<'a>>, CemmError> {
+    let mut steps: Vec<PlanStep<'a>> = Vec::new();
+
+    // The receipt records what is actually installed, so it wins over the caller's old manifest
+    let old_manifest = receipt.map(|r| &r.manifest).or(options.old_manifest.as_ref());
+    let diff = match old_manifest {
+        Some(old_manifest) => Some(calculate_update_diff(old_manifest, manifest)?),
+        None => None,
+    };
+    let diff = diff.as_ref();
+
+    // Cleanup of old files only happens when requested. Config-only updates carry
+    // no addon lists, so they never remove addons.
+    if options.cleanup_old && !is_config_only(manifest) {
+        match (receipt, old_manifest, diff) {
+            (Some(receipt), _, _) => {
+                steps.extend(collect_receipt_removals(modpack_path, receipt, manifest, options.install_side()).await?)
+            }
+            (None, Some(old_manifest), Some(diff)) => {
+                steps.extend(collect_old_files(modpack_path, old_manifest, diff).await?)
             }
-            let dest = Path::new(&modpack_path).join("resourcepacks").join(&addon.file_name_on_disk);
-            if let Some(ref d) = diff {
-                if let Some(ref old_manifest) = options.old_manifest {
-                    if should_download_addon(addon, &old_manifest.resourcepacks, d, &dest) {
-                        count += 1;
+            _ => {}
+        }
+    }
+
+    let side = options.install_side();
+    let old_categories = old_manifest.map(addon_categories);
+    for (index, category) in addon_categories(manifest).into_iter().enumerate() {
+        for addon in category.addons {
+            let enabled_path = format!("{}/{}", category.folder_for(modpack_path, addon)?, addon.file_name_on_disk);
+            let disabled_path = format!("{}.disabled", enabled_path);
+            // Disabled addons live under their `.disabled` name, the same convention find_disabled_files reads
+            let (relative_path, other_path) = if addon.disabled == Some(true) {
+                (disabled_path, enabled_path)
+            } else {
+                (enabled_path, disabled_path)
+            };
+            let dest = modpack_path.join(&relative_path);
+            let other = modpack_path.join(&other_path);
+
+            let (action, reason, source) = if !addon_runs_on(addon, side) {
+                (PlanAction::Skip, PlanReason::OtherSide, StepSource::Existing)
+            } else if addon.disabled == Some(true) {
+                if !dest.exists() && other.exists() {
+                    (PlanAction::Rename, PlanReason::Disabled, StepSource::Toggle { addon, from: other })
+                } else {
+                    // Disabled addons that are not on disk yet are not downloaded
+                    (PlanAction::Skip, PlanReason::Disabled, StepSource::Addon { addon, label: category.label })
+                }
+            } else {
+                let reason = match (diff, &old_categories) {
+                    (Some(d), Some(old)) => download_reason(addon, old[index].addons, d, &dest),
+                    // No old manifest means fresh install - download everything
+                    _ => Some(PlanReason::FreshInstall),
+                };
+                match reason {
+                    // The exact file is still there as `.disabled`, so enabling it needs no download
+                    Some(_) if !dest.exists() && other.exists() => {
+                        (PlanAction::Rename, PlanReason::Enabled, StepSource::Toggle { addon, from: other })
                     }
+                    Some(reason) => (write_action(&dest), reason, StepSource::Addon { addon, label: category.label }),
+                    None => (PlanAction::Skip, PlanReason::Unchanged, StepSource::Addon { addon, label: category.label }),
                 }
+            };
+
+            steps.push(PlanStep {
+                file: PlannedFile {
+                    action,
+                    reason,
+                    category: category.folder.to_string(),
+                    name: addon.addon_name.clone(),
+                    relative_path,
+                    expected_size: addon.file_length,
+                    renamed_from: (action == PlanAction::Rename).then_some(other_path),
+                },
+                source,
+                dest,
+            });
+        }
+    }
+
+    // Config files are always installed on their side (with path traversal protection)
+    for config in config_files {
+        let dest = validate_path_within_base(modpack_path, &config.relative_path)?;
+        let size = decode_config_content(config)?.len() as u64;
+        let (action, reason, source) = if config_environment(&config.relative_path, manifest).runs_on(side) {
+            (write_action(&dest), PlanReason::ConfigFile, StepSource::Config(config))
+        } else {
+            (PlanAction::Skip, PlanReason::OtherSide, StepSource::Existing)
+        };
+        steps.push(PlanStep {
+            file: PlannedFile {
+                action,
+                reason,
+                category: "config".to_string(),
+                name: config.filename.clone(),
+                relative_path: config.relative_path.replace('\\', "/"),
+                expected_size: Some(size),
+                renamed_from: None,
+            },
+            source,
+            dest,
+        });
+    }
+
+    // Config files the update deletes, with the same traversal checks as written ones
+    for relative_path in &manifest.removed_config_files {
+        let relative_path = relative_path.replace('\\', "/");
+        if config_files.iter().any(|c| c.relative_path.replace('\\', "/") == relative_path) {
+            log::warn!("Not deleting {}: the update also ships it", relative_path);
+            continue;
+        }
+        if relative_path.split('/').next() == Some(".cemm") {
+            return Err(CemmError::path_traversal(
+                format!("Refusing to delete {}: it belongs to CEMM", relative_path),
+                relative_path,
+            ));
+        }
+        let dest = validate_path_within_base(modpack_path, &relative_path)?;
+        let Ok(metadata) = async_fs::metadata(&dest).await else {
+            continue;
+        };
+        if !metadata.is_file() {
+            log::warn!("Not deleting {}: it is not a file", relative_path);
+            continue;
+        }
+        steps.push(PlanStep {
+            file: PlannedFile {
+                action: PlanAction::Delete,
+                reason: PlanReason::RemovedConfig,
+                category: "config".to_string(),
+                name: relative_path.rsplit('/').next().unwrap_or_default().to_string(),
+                relative_path,
+                expected_size: Some(metadata.len()),
+                renamed_from: None,
+            },
+            source: StepSource::Existing,
+            dest,
+        });
+    }
+
+    // Override files are written unless the same content is already installed
+    for content in overrides {
+        let relative_path = normalize_override_path(&content.file.relative_path)?;
+        let listed = manifest.overrides.iter()
+            .find(|o| o.relative_path.replace('\\', "/") == relative_path)
+            .ok_or_else(|| CemmError::validation(format!("Override file {} is not listed in the manifest", relative_path)))?;
+        if !listed.sha256.eq_ignore_ascii_case(&content.file.sha256) {
+            return Err(CemmError::validation(format!(
+                "Override file {} does not match the manifest",
+                relative_path
+            )));
+        }
+        let dest = validate_path_within_base(modpack_path, &relative_path)?;
+        let (action, reason, source) = if !override_runs_on(listed, side) {
+            (PlanAction::Skip, PlanReason::OtherSide, StepSource::Existing)
+        } else if is_installed(&dest, &listed.sha256).await {
+            (PlanAction::Skip, PlanReason::Unchanged, StepSource::Override(content))
+        } else {
+            (write_action(&dest), PlanReason::OverrideFile, StepSource::Override(content))
+        };
+        steps.push(PlanStep {
+            file: PlannedFile {
+                action,
+                reason,
+                category: OVERRIDES_CATEGORY.to_string(),
+                name: relative_path.rsplit('/').next().unwrap_or_default().to_string(),
+                relative_path,
+                expected_size: Some(listed.size),
+                renamed_from: None,
+            },
+            source,
+            dest,
+        });
+    }
+
+    Ok(steps)
+}
+
+/// Whether the file at `dest` exists with the given SHA-256.
+async fn is_installed(dest: &Path, sha256: &str) -> bool {
+    dest.is_file() && sha256_file(dest).await.is_ok_and(|hash| hash.eq_ignore_ascii_case(sha256))
+}
+
+/// Computes what install_update would do with the same inputs, without changing anything on disk.
+#[command]
+pub async fn plan_install(
+    modpack_path: String,
+    manifest: Manifest,
+    config_files: Vec<ConfigFile>,
+    overrides: Option<Vec<OverrideFileWithContent>>,
+    options: Option<InstallOptions>,
+) -> Result<InstallPlan, CemmError> {
+    let options = options.unwrap_or_default();
+    let overrides = overrides.unwrap_or_default();
+    let modpack_path_buf = PathBuf::from(&modpack_path);
+    let receipt = load_receipt(&modpack_path_buf).await;
+
+    let steps = build_plan_steps(
+        &modpack_path_buf,
+        &manifest,
+        &config_files,
+        &overrides,
+        &options,
+        receipt.as_ref(),
+    ).await?;
+    let plan = InstallPlan::from_steps(&steps);
+    log::info!(
+        "plan_install: {} files planned, {} bytes to download",
+        plan.files.len(),
+        plan.total_download_bytes
+    );
+    Ok(plan)
+}
+
+/// Payload of the `install-cancelled` event.
+#[derive(Debug, Clone, Serialize)]
+struct InstallCancelled {
+    message: String,
+    /// True if the instance was left exactly as it was before the install
+    rolled_back: bool,
+}
+
+/// What install_update did beyond installing the planned files.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/installer.rs (this file)
+/// - TypeScript: app/types/index.ts (InstallResult interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InstallResult {
+    /// Config files that kept local changes instead of being overwritten
+    pub merged_configs: Vec<String>,
+    /// Settings changed both locally and by the update; the update's value was installed
+    pub config_conflicts: Vec<ConfigConflict>,
+    /// True if minecraftinstance.json was changed, see `InstallOptions::update_instance_file`
+    pub instance_file_updated: bool,
+    /// Differences between the Minecraft version or mod loader of the manifest and the instance
+    pub compatibility_warnings: Vec<String>,
+}
+
+/// Unified install function that handles all installation scenarios
+///
+/// Config files in TOML, JSON/JSON5, `.properties`, Forge `.cfg` and `options.txt`
+/// format are merged with the user's local edits, using the version installed last
+/// time as the base. Conflicting settings are returned in the `InstallResult`.
+///
+/// Override files are written byte for byte after checking them against the manifest,
+/// and skipped when the instance already has the same content.
+///
+/// The install can be stopped with cancel_install. Downloads in progress are
+/// aborted and their partial files removed; transactional installs are rolled back
+/// completely. An `install-cancelled` event is emitted once the install stopped.
+#[command]
+pub async fn install_update(
+    window: Window,
+    modpack_path: String,
+    manifest: Manifest,
+    config_files: Vec<ConfigFile>,
+    overrides: Option<Vec<OverrideFileWithContent>>,
+    options: Option<InstallOptions>,
+) -> Result<InstallResult, CemmError> {
+    let options = options.unwrap_or_default();
+    let overrides = overrides.unwrap_or_default();
+    let operations = window.state::<Operations>();
+    let operation = operations.start(OperationKind::Install)?;
+
+    let inputs = InstallInputs { manifest, config_files, overrides };
+    let result = run_install(&window, modpack_path, inputs, &options, operation.token()).await;
+
+    if let Err(e) = &result {
+        if e.is_cancelled() {
+            let rolled_back = options.is_transactional();
+            let message = if rolled_back {
+                "Installation cancelled, no changes were made".to_string()
             } else {
-                count += 1;
+                "Installation cancelled, files installed so far were kept".to_string()
+            };
+            log::info!("{}", message);
+            let _ = Emitter::emit(&window, "install-cancelled", Some(InstallCancelled { message, rolled_back }));
+        }
+    }
+    result
+}
+
+/// What an update installs.
+struct InstallInputs {
+    manifest: Manifest,
+    config_files: Vec<ConfigFile>,
+    overrides: Vec<OverrideFileWithContent>,
+}
+
+async fn run_install(
+    window: &Window,
+    modpack_path: String,
+    inputs: InstallInputs,
+    options: &InstallOptions,
+    cancel: &CancellationToken,
+) -> Result<InstallResult, CemmError> {
+    let InstallInputs { manifest, config_files, overrides } = inputs;
+    let modpack_path_buf = PathBuf::from(&modpack_path);
+    let compatibility_warnings = check_instance_target(&modpack_path_buf, &manifest, options).await?;
+    let app_data_dir = window.path().app_data_dir()
+        .map_err(|e| CemmError::io_message(format!("Failed to resolve the app data directory: {}", e), None));
+    let cache = match (&app_data_dir, options.uses_cache()) {
+        (Ok(app_data_dir), true) => Some(DownloadCache::new(app_data_dir)),
+        (Err(e), true) => {
+            log::warn!("Download cache unavailable: {}", e);
+            None
+        }
+        (_, false) => None,
+    };
+
+    let receipt = load_receipt(&modpack_path_buf).await;
+
+    let steps = build_plan_steps(
+        &modpack_path_buf,
+        &manifest,
+        &config_files,
+        &overrides,
+        options,
+        receipt.as_ref(),
+    ).await?;
+    if options.runs_preflight() {
+        let report = check_plan(&modpack_path_buf, &InstallPlan::from_steps(&steps)).await?;
+        if !report.passed() {
+            for issue in &report.issues {
+                log::warn!("Preflight: {}", issue.message);
             }
+            return Err(CemmError::validation(report.summary()));
         }
-        
-        // Count shaderpacks
-        for addon in &manifest.shaderpacks {
-            if addon.disabled == Some(true) {
+    }
+    let mut removals: Vec<PathBuf> = steps.iter()
+        .filter(|s| s.file.action == PlanAction::Delete)
+        .map(|s| s.dest.clone())
+        .collect();
+    // The merge base of a deleted config file goes with it
+    for step in steps.iter().filter(|s| s.file.reason == PlanReason::RemovedConfig) {
+        let base_path = config_base_path(&modpack_path_buf, &step.file.relative_path);
+        if base_path.is_file() {
+            removals.push(base_path);
+        }
+    }
+    // Files that are renamed away when an addon is enabled or disabled
+    let renamed: Vec<PathBuf> = steps.iter()
+        .filter_map(|s| match &s.source {
+            StepSource::Toggle { from, .. } => Some(from.clone()),
+            _ => None,
+        })
+        .collect();
+    let replaced: Vec<PathBuf> = removals.iter().chain(&renamed).cloned().collect();
+
+    // Snapshot the instance so the install can be undone with restore_snapshot
+    if options.takes_snapshot() {
+        emit_progress(window, 0, 1, "Creating snapshot...");
+        let store = SnapshotStore::new(&app_data_dir?);
+        let mut overwritten_files: Vec<PathBuf> = steps.iter()
+            .filter(|s| matches!(s.source, StepSource::Config(_)) || s.file.reason == PlanReason::RemovedConfig)
+            .flat_map(|s| [s.dest.clone(), config_base_path(&modpack_path_buf, &s.file.relative_path)])
+            .collect();
+        // Addons outside the folders every snapshot captures are saved one by one
+        for step in &steps {
+            let top_folder = step.file.relative_path.split('/').next().unwrap_or_default();
+            if step.file.category == "config" || SNAPSHOT_FOLDERS.contains(&top_folder) {
                 continue;
             }
-            let dest = Path::new(&modpack_path).join("shaderpacks").join(&addon.file_name_on_disk);
-            if let Some(ref d) = diff {
-                if let Some(ref old_manifest) = options.old_manifest {
-                    if should_download_addon(addon, &old_manifest.shaderpacks, d, &dest) {
-                        count += 1;
-                    }
-                }
-            } else {
-                count += 1;
+            overwritten_files.push(step.dest.clone());
+            if let StepSource::Toggle { from, .. } = &step.source {
+                overwritten_files.push(from.clone());
             }
         }
-        
-        // Count datapacks
-        for addon in &manifest.datapacks {
-            if addon.disabled == Some(true) {
+        overwritten_files.push(InstallReceipt::path(&modpack_path_buf));
+        if options.updates_instance_file() {
+            overwritten_files.push(instance_file_path(&modpack_path_buf));
+        }
+        store.create(&modpack_path_buf, &overwritten_files).await
+            .map_err(|e| e.context("Failed to create snapshot before installing"))?;
+        if let Err(e) = store.prune(&modpack_path_buf, options.snapshot_retention()).await {
+            log::warn!("Failed to prune old snapshots: {}", e);
+        }
+    }
+    cancel.check()?;
+
+    // Transactional installs stage everything first and only touch the instance on commit
+    let transaction = if options.is_transactional() {
+        Some(InstallTransaction::begin(&modpack_path_buf).await?)
+    } else {
+        None
+    };
+
+    // Step 1: Install only changed/new addons and override files, and all config files
+    let staged = async {
+        let mut jobs: Vec<DownloadJob> = Vec::new();
+        let mut configs: Vec<(&ConfigFile, &Path, PathBuf)> = Vec::new();
+        let mut override_writes: Vec<(&OverrideFileWithContent, PathBuf)> = Vec::new();
+        // Destination -> path the file is actually written to
+        let mut written: HashMap<PathBuf, PathBuf> = HashMap::new();
+
+        for step in &steps {
+            if let StepSource::Toggle { from, .. } = &step.source {
+                // In a transaction the new name is staged as a link and the old one removed on commit
+                let write_path = match &transaction {
+                    Some(tx) => {
+                        let staged = tx.staging_path_for(&step.dest)?;
+                        if let Some(parent) = staged.parent() {
+                            async_fs::create_dir_all(parent).await
+                                .map_err(|e| CemmError::io("Failed to create directory", parent, e))?;
+                        }
+                        link_or_copy(from, &staged).await?;
+                        staged
+                    }
+                    None => {
+                        async_fs::rename(from, &step.dest).await
+                            .map_err(|e| CemmError::io(&format!("Failed to rename {} to", from.display()), &step.dest, e))?;
+                        step.dest.clone()
+                    }
+                };
+                log::info!(
+                    "{} {}: {}",
+                    if step.file.reason == PlanReason::Enabled { "Enabling" } else { "Disabling" },
+                    step.file.category,
+                    step.file.name
+                );
+                written.insert(step.dest.clone(), write_path);
                 continue;
             }
-            let dest = Path::new(&modpack_path).join("datapacks").join(&addon.file_name_on_disk);
-            if let Some(ref d) = diff {
-                if let Some(ref old_manifest) = options.old_manifest {
-                    if should_download_addon(addon, &old_manifest.datapacks, d, &dest) {
-                        count += 1;
+
+            if !step.writes() {
+                if let StepSource::Addon { label, .. } = step.source {
+                    if step.file.reason == PlanReason::Unchanged {
+                        log::info!("Skipping unchanged {}: {}", label, step.file.name);
                     }
                 }
-            } else {
-                count += 1;
+                continue;
             }
-        }
-        
-        // Config files are always installed
-        count + config_files.len()
-    };
 
-    // Install mods (selective download)
-    for addon in &manifest.mods {
-        if addon.disabled == Some(true) {
-            continue;
+            let write_path = match &transaction {
+                Some(tx) => tx.staging_path_for(&step.dest)?,
+                None => step.dest.clone(),
+            };
+            written.insert(step.dest.clone(), write_path.clone());
+            match step.source {
+                StepSource::Addon { addon, label } => jobs.push(DownloadJob {
+                    addon: addon.clone(),
+                    dest: write_path,
+                    label,
+                }),
+                StepSource::Config(config) => configs.push((config, &step.dest, write_path)),
+                StepSource::Override(content) => override_writes.push((content, write_path)),
+                StepSource::Toggle { .. } | StepSource::Existing => {}
+            }
         }
-        let dest = Path::new(&modpack_path).join("mods").join(&addon.file_name_on_disk);
-        
-        // Check if we need to download this addon
-        let needs_download = if let (Some(ref d), Some(ref old_manifest)) = (&diff, options.old_manifest.as_ref()) {
-            should_download_addon(addon, &old_manifest.mods, d, &dest)
-        } else {
-            // No old manifest means fresh install - download everything
-            true
+
+        let files_to_download = jobs.len() + configs.len() + override_writes.len();
+        let mut current = 0usize;
+
+        let ctx = DownloadContext {
+            client: Client::new(),
+            policy: options.retry_policy(),
+            cache,
+            cancel: cancel.clone(),
         };
-        
-        if needs_download {
-            download_and_save(&client, &addon.cdn_download_url, &dest).await?;
-            emit_progress(&window, current + 1, files_to_download, &format!("Installed mod: {}", addon.addon_name));
-        } else {
-            log::info!("Skipping unchanged mod: {}", addon.addon_name);
+        run_downloads(
+            window,
+            &ctx,
+            jobs,
+            options.download_concurrency(),
+            &mut current,
+            files_to_download,
+        ).await?;
+
+        let mut result = InstallResult { compatibility_warnings, ..Default::default() };
+        for (config, dest, write_path) in configs {
+            cancel.check()?;
+            let target = ConfigTarget {
+                modpack_path: &modpack_path_buf,
+                dest,
+                write_path: &write_path,
+                transaction: transaction.as_ref(),
+            };
+            let conflicts = install_config_file(config, target, options.merges_configs()).await?;
+            if let Some(conflicts) = conflicts {
+                result.merged_configs.push(config.relative_path.clone());
+                result.config_conflicts.extend(conflicts);
+            }
+            current += 1;
+            emit_progress(window, current, files_to_download, &format!("Installed config: {}", config.relative_path));
         }
-        installed_paths.push(dest);
-        current += 1;
-    }
 
-    // Install resourcepacks (selective download)
-    for addon in &manifest.resourcepacks {
-        if addon.disabled == Some(true) {
-            continue;
+        for (content, write_path) in override_writes {
+            cancel.check()?;
+            write_file(&write_path, &content.decode()?).await?;
+            current += 1;
+            emit_progress(
+                window,
+                current,
+                files_to_download,
+                &format!("Installed override: {}", content.file.relative_path),
+            );
         }
-        let dest = Path::new(&modpack_path).join("resourcepacks").join(&addon.file_name_on_disk);
-        
-        let needs_download = if let (Some(ref d), Some(ref old_manifest)) = (&diff, options.old_manifest.as_ref()) {
-            should_download_addon(addon, &old_manifest.resourcepacks, d, &dest)
-        } else {
-            true
+
+        // Step 2: Cleanup old files once everything new is in place, so a failed or cancelled
+        // download leaves the previous install usable. In a transaction they are removed on commit.
+        if transaction.is_none() {
+            cancel.check()?;
+            // An updated file with an unchanged name has already been overwritten in place
+            let stale: Vec<PathBuf> = removals.iter()
+                .filter(|path| !written.contains_key(*path))
+                .cloned()
+                .collect();
+            log_removals(&steps, &stale);
+            remove_files(&stale).await?;
+        }
+
+        // Step 3: Record what was installed. In a transaction the receipt is committed with the files.
+        let new_receipt = build_receipt(
+            &modpack_path_buf,
+            &manifest,
+            &steps,
+            &written,
+            &replaced,
+            receipt.as_ref(),
+            options.update_id.clone(),
+        ).await?;
+        let receipt_path = match &transaction {
+            Some(tx) => tx.staging_path(Path::new(RECEIPT_PATH)),
+            None => InstallReceipt::path(&modpack_path_buf),
         };
-        
-        if needs_download {
-            download_and_save(&client, &addon.cdn_download_url, &dest).await?;
-            emit_progress(&window, current + 1, files_to_download, &format!("Installed resourcepack: {}", addon.addon_name));
-        } else {
-            log::info!("Skipping unchanged resourcepack: {}", addon.addon_name);
+        new_receipt.save(&receipt_path).await?;
+
+        // Last chance to cancel; once the commit starts it runs to completion
+        cancel.check()?;
+        Ok::<(usize, InstallResult), CemmError>((files_to_download, result))
+    }
+    .await;
+
+    let (files_to_download, mut result) = match (transaction, staged) {
+        (Some(tx), Ok((total, result))) => {
+            emit_progress(window, total, total, "Applying changes...");
+            log_removals(&steps, &removals);
+            tx.commit(&replaced).await?;
+            (total, result)
+        }
+        (Some(tx), Err(e)) => {
+            tx.abort().await;
+            return Err(e);
+        }
+        (None, staged) => staged?,
+    };
+
+    // The addons are installed at this point, so a failure here only leaves the CurseForge app out of date
+    if options.updates_instance_file() && !is_config_only(&manifest) {
+        match sync_installed_addons(&modpack_path_buf, &manifest, &removals).await {
+            Ok(Some(update)) => result.instance_file_updated = update.updated > 0 || update.removed > 0,
+            Ok(None) => log::info!("No {} in the instance, nothing to update", INSTANCE_FILE),
+            Err(e) => log::warn!("Failed to update {}: {}", INSTANCE_FILE, e),
         }
-        installed_paths.push(dest);
-        current += 1;
     }
 
-    // Install shaderpacks (selective download)
-    for addon in &manifest.shaderpacks {
-        if addon.disabled == Some(true) {
-            continue;
+    if !result.config_conflicts.is_empty() {
+        log::warn!(
+            "{} config settings conflicted with local changes, the modpack's values were installed",
+            result.config_conflicts.len()
+        );
+    }
+    emit_progress(window, files_to_download, files_to_download, "Installation complete!");
+    Ok(result)
+}
+
+/// Refuses to install a manifest made for another Minecraft version or mod loader than
+/// the instance, unless `allow_incompatible` is set. Returns the differences to report.
+async fn check_instance_target(
+    modpack_path: &Path,
+    manifest: &Manifest,
+    options: &InstallOptions,
+) -> Result<Vec<String>, CemmError> {
+    let instance = match read_instance_target(modpack_path).await {
+        Ok(Some(instance)) => instance,
+        Ok(None) => {
+            log::info!("No {} in the instance, skipping the compatibility check", INSTANCE_FILE);
+            return Ok(Vec::new());
         }
-        let dest = Path::new(&modpack_path).join("shaderpacks").join(&addon.file_name_on_disk);
-        
-        let needs_download = if let (Some(ref d), Some(ref old_manifest)) = (&diff, options.old_manifest.as_ref()) {
-            should_download_addon(addon, &old_manifest.shaderpacks, d, &dest)
-        } else {
-            true
-        };
-        
-        if needs_download {
-            download_and_save(&client, &addon.cdn_download_url, &dest).await?;
-            emit_progress(&window, current + 1, files_to_download, &format!("Installed shaderpack: {}", addon.addon_name));
-        } else {
-            log::info!("Skipping unchanged shaderpack: {}", addon.addon_name);
+        Err(e) => {
+            log::warn!("Skipping the compatibility check: {}", e);
+            return Ok(Vec::new());
+        }
+    };
+
+    let Compatibility { mismatches, mut warnings } = check_compatibility(manifest, &instance);
+    if !mismatches.is_empty() {
+        if !options.allows_incompatible() {
+            return Err(CemmError::validation(format!("{}. Nothing was installed.", mismatches.join(". "))));
+        }
+        for mismatch in &mismatches {
+            log::warn!("Installing anyway: {}", mismatch);
         }
-        installed_paths.push(dest);
-        current += 1;
     }
+    for warning in &warnings {
+        log::warn!("{}", warning);
+    }
+    warnings.splice(0..0, mismatches);
+    Ok(warnings)
+}
 
-    // Install datapacks (selective download)
-    for addon in &manifest.datapacks {
-        if addon.disabled == Some(true) {
-            continue;
+/// Reads the install receipt of an instance. An unreadable receipt is ignored with a warning.
+pub async fn load_receipt(modpack_path: &Path) -> Option<InstallReceipt> {
+    match InstallReceipt::load(modpack_path).await {
+        Ok(receipt) => receipt,
+        Err(e) => {
+            log::warn!("Ignoring install receipt: {}", e);
+            None
         }
-        let dest = Path::new(&modpack_path).join("datapacks").join(&addon.file_name_on_disk);
-        
-        let needs_download = if let (Some(ref d), Some(ref old_manifest)) = (&diff, options.old_manifest.as_ref()) {
-            should_download_addon(addon, &old_manifest.datapacks, d, &dest)
-        } else {
-            true
+    }
+}
+
+/// Config-only updates ship no addon lists and must leave addons alone.
+pub fn is_config_only(manifest: &Manifest) -> bool {
+    manifest.update_type.as_deref() == Some("config")
+}
+
+/// Builds the install receipt describing the instance after this install.
+///
+/// Files written or renamed now are hashed from `written` (which may point into the
+/// staging directory). Unchanged addons keep their previous entry, and files owned
+/// through an earlier install stay listed as long as they were not removed or renamed.
+async fn build_receipt(
+    modpack_path: &Path,
+    manifest: &Manifest,
+    steps: &[PlanStep<'_>],
+    written: &HashMap<PathBuf, PathBuf>,
+    removals: &[PathBuf],
+    previous: Option<&InstallReceipt>,
+    update_id: Option<String>,
+) -> Result<InstallReceipt, CemmError> {
+    let mut files: Vec<ReceiptFile> = Vec::new();
+
+    for step in steps {
+        let relative_path = &step.file.relative_path;
+        let entry = match step.source {
+            StepSource::Addon { addon, .. } | StepSource::Toggle { addon, .. } => {
+                let previous_entry = previous
+                    .and_then(|r| r.file(relative_path))
+                    .filter(|f| f.addon_file_id == Some(addon.addon_file_id))
+                    .filter(|_| step.dest.exists());
+
+                let mut entry = if let Some(path) = written.get(&step.dest) {
+                    ReceiptFile::from_path(relative_path.clone(), ReceiptFileKind::Addon, path).await?
+                } else if let Some(previous_entry) = previous_entry {
+                    previous_entry.clone()
+                } else if step.dest.exists() {
+                    ReceiptFile::from_path(relative_path.clone(), ReceiptFileKind::Addon, &step.dest).await?
+                } else {
+                    continue;
+                };
+                entry.addon_project_id = Some(addon.addon_project_id);
+                entry.addon_file_id = Some(addon.addon_file_id);
+                entry
+            }
+            StepSource::Config(_) => match written.get(&step.dest) {
+                Some(path) => ReceiptFile::from_path(relative_path.clone(), ReceiptFileKind::Config, path).await?,
+                None => continue,
+            },
+            // Unchanged override files are recorded too, so they are removed once the manifest drops them
+            StepSource::Override(_) => match written.get(&step.dest) {
+                Some(path) => ReceiptFile::from_path(relative_path.clone(), ReceiptFileKind::Override, path).await?,
+                None if step.dest.is_file() => {
+                    ReceiptFile::from_path(relative_path.clone(), ReceiptFileKind::Override, &step.dest).await?
+                }
+                None => continue,
+            },
+            StepSource::Existing => continue,
         };
-        
-        if needs_download {
-            download_and_save(&client, &addon.cdn_download_url, &dest).await?;
-            emit_progress(&window, current + 1, files_to_download, &format!("Installed datapack: {}", addon.addon_name));
-        } else {
-            log::info!("Skipping unchanged datapack: {}", addon.addon_name);
+        files.push(entry);
+    }
+
+    if let Some(previous) = previous {
+        for file in &previous.files {
+            let dest = modpack_path.join(&file.relative_path);
+            let listed = files.iter().any(|f| f.relative_path == file.relative_path);
+            if !listed && !removals.contains(&dest) && dest.exists() {
+                files.push(file.clone());
+            }
         }
-        installed_paths.push(dest);
-        current += 1;
     }
 
-    // Install config files (with path traversal protection)
-    let modpack_path_buf = PathBuf::from(&modpack_path);
-    for config in config_files {
-        // Validate the path to prevent path traversal attacks
-        let dest = validate_path_within_base(&modpack_path_buf, &config.relative_path)?;
-        
-        if let Some(parent) = dest.parent() {
-            async_fs::create_dir_all(parent).await
-                .map_err(|e| format!("Failed to create directory {}: {}", parent.display(), e))?;
+    // A config-only update does not change which addons are installed
+    let recorded_manifest = match previous {
+        Some(previous) if is_config_only(manifest) => {
+            let mut merged = previous.manifest.clone();
+            merged.config_files.retain(|c| !manifest.removed_config_files.contains(&c.relative_path));
+            for config in &manifest.config_files {
+                if !merged.config_files.iter().any(|c| c.relative_path == config.relative_path) {
+                    merged.config_files.push(config.clone());
+                }
+            }
+            for file in &manifest.overrides {
+                merged.overrides.retain(|o| o.relative_path != file.relative_path);
+                merged.overrides.push(file.clone());
+            }
+            merged
         }
-        
-        // Handle binary files that are base64-encoded
-        if config.content.starts_with("data:application/octet-stream;base64,") {
-            let base64_content = config.content.strip_prefix("data:application/octet-stream;base64,")
-                .unwrap_or(&config.content);
-            use base64::engine::general_purpose::STANDARD;
-            use base64::Engine;
-            let binary_data = STANDARD.decode(base64_content)
-                .map_err(|e| format!("Failed to decode base64 config file {}: {}", dest.display(), e))?;
-            async_fs::write(&dest, binary_data).await
-                .map_err(|e| format!("Failed to write binary config file {}: {}", dest.display(), e))?;
-        } else {
-            async_fs::write(&dest, config.content.as_bytes()).await
-                .map_err(|e| format!("Failed to write config file {}: {}", dest.display(), e))?;
+        _ => manifest.clone(),
+    };
+
+    Ok(InstallReceipt::new(update_id, manifest_hash(manifest)?, recorded_manifest, files))
+}
+
+/// Returns the bytes a config file will be written as, decoding base64 data URIs for binary files.
+pub fn decode_config_content(config: &ConfigFile) -> Result<Vec<u8>, CemmError> {
+    // Handle binary files that are base64-encoded
+    if let Some(base64_content) = config.content.strip_prefix("data:application/octet-stream;base64,") {
+        use base64::engine::general_purpose::STANDARD;
+        use base64::Engine;
+        STANDARD.decode(base64_content)
+            .map_err(|e| CemmError::parse(
+                format!("Failed to decode base64 config file {}: {}", config.relative_path, e),
+                &config.relative_path,
+            ))
+    } else {
+        Ok(config.content.as_bytes().to_vec())
+    }
+}
+
+/// Where a config file is installed.
+pub struct ConfigTarget<'a> {
+    pub modpack_path: &'a Path,
+    /// Location of the file inside the instance, holding the user's local copy
+    pub dest: &'a Path,
+    /// Path the result is written to, inside the staging directory for transactional installs
+    pub write_path: &'a Path,
+    pub transaction: Option<&'a InstallTransaction>,
+}
+
+/// Writes a config file, merging it with the user's local changes when possible.
+///
+/// The modpack's version is also stored below CONFIG_BASE_DIR as the base for the
+/// next merge. Returns the conflicts if the file was merged, `None` if it was written as shipped.
+pub async fn install_config_file(
+    config: &ConfigFile,
+    target: ConfigTarget<'_>,
+    merge: bool,
+) -> Result<Option<Vec<ConfigConflict>>, CemmError> {
+    let relative_path = config.relative_path.replace('\\', "/");
+    let incoming = decode_config_content(config)?;
+    let base_path = config_base_path(target.modpack_path, &relative_path);
+
+    let merged = match ConfigFormat::detect(&relative_path).filter(|_| merge) {
+        Some(format) => match merge_with_local(format, &relative_path, target.dest, &base_path, &incoming).await {
+            Ok(merged) => merged,
+            Err(e) => {
+                log::warn!("Overwriting {} without merging: {}", relative_path, e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    let conflicts = match merged {
+        Some(merged) => {
+            log::info!("Merged {} with local changes ({} conflicts)", relative_path, merged.conflicts.len());
+            write_file(target.write_path, merged.content.as_bytes()).await?;
+            Some(merged.conflicts)
         }
-        
-        installed_paths.push(dest.clone());
-        current += 1;
-        emit_progress(&window, current, files_to_download, &format!("Installed config: {}", dest.display()));
+        None => {
+            write_file(target.write_path, &incoming).await?;
+            None
+        }
+    };
+
+    let base_write_path = match target.transaction {
+        Some(tx) => tx.staging_path_for(&base_path)?,
+        None => base_path,
+    };
+    write_file(&base_write_path, &incoming).await?;
+    Ok(conflicts)
+}
+
+/// Merges the incoming version of a config file with the local copy.
+///
+/// Returns `None` when there is nothing to merge: the file is not installed yet, no
+/// base from an earlier install exists, or the user did not change it.
+async fn merge_with_local(
+    format: ConfigFormat,
+    relative_path: &str,
+    dest: &Path,
+    base_path: &Path,
+    incoming: &[u8],
+) -> Result<Option<MergeResult>, CemmError> {
+    let (Some(local), Some(base)) = (read_text(dest).await?, read_text(base_path).await?) else {
+        return Ok(None);
+    };
+    if local == base {
+        return Ok(None);
     }
+    let incoming = std::str::from_utf8(incoming)
+        .map_err(|_| CemmError::validation(format!("{} is not a text file", relative_path)))?;
+    merge_config(format, relative_path, &base, &local, incoming).map(Some)
+}
 
-    emit_progress(&window, files_to_download, files_to_download, "Installation complete!");
-    Ok(())
+/// Reads a text file. Returns `None` if it does not exist.
+async fn read_text(path: &Path) -> Result<Option<String>, CemmError> {
+    match async_fs::read_to_string(path).await {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(CemmError::io("Failed to read", path, e)),
+    }
+}
+
+pub async fn write_file(dest: &Path, data: &[u8]) -> Result<(), CemmError> {
+    if let Some(parent) = dest.parent() {
+        async_fs::create_dir_all(parent).await
+            .map_err(|e| CemmError::io("Failed to create directory", parent, e))?;
+    }
+    async_fs::write(dest, data).await
+        .map_err(|e| CemmError::io("Failed to write file", dest, e))
 }
 
 /// Represents the difference between two manifest versions during an update.
@@ -415,13 +1542,31 @@ pub struct UpdateDiff {
     /// Project IDs of addons that were updated (matched by project_id, not version)
     pub updated_addon_ids: Vec<u64>,
     pub new_addons: Vec<String>,
+    /// Addons that stay installed but were enabled or disabled
+    pub toggled: Vec<ToggledAddon>,
 }
 
-fn calculate_update_diff(old_manifest: &Manifest, new_manifest: &Manifest) -> Result<UpdateDiff, String> {
+/// An addon whose enabled state differs between two manifests.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/installer.rs (this file)
+/// - TypeScript: app/types/index.ts (ToggledAddon interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone)]
+pub struct ToggledAddon {
+    pub addon_project_id: u64,
+    pub addon_name: String,
+    /// The new state: true if the addon is disabled in the new manifest
+    pub disabled: bool,
+}
+
+fn calculate_update_diff(old_manifest: &Manifest, new_manifest: &Manifest) -> Result<UpdateDiff, CemmError> {
     let mut diff = UpdateDiff {
         removed_addons: Vec::new(),
         updated_addon_ids: Vec::new(),
         new_addons: Vec::new(),
+        toggled: Vec::new(),
     };
 
     fn process_addon_category(
@@ -429,19 +1574,22 @@ fn calculate_update_diff(old_manifest: &Manifest, new_manifest: &Manifest) -> Re
         new_addons: &[crate::composables::manifest::Addon],
         diff: &mut UpdateDiff,
     ) {
-        // Find removed addons
+        // Find removed and toggled addons. Disabling an addon keeps it installed as `.disabled`.
         for old_addon in old_addons {
-            if old_addon.disabled.unwrap_or(false) {
-                continue;
-            }
             let maybe_new = new_addons.iter()
                 .find(|new_addon| new_addon.addon_project_id == old_addon.addon_project_id);
-            
-            if maybe_new.is_none() {
-                diff.removed_addons.push(old_addon.addon_name.clone());
-            } else if let Some(new_addon) = maybe_new {
-                if new_addon.disabled == Some(true) {
-                    diff.removed_addons.push(old_addon.addon_name.clone());
+
+            match maybe_new {
+                None => diff.removed_addons.push(old_addon.addon_name.clone()),
+                Some(new_addon) => {
+                    let disabled = new_addon.disabled.unwrap_or(false);
+                    if old_addon.disabled.unwrap_or(false) != disabled {
+                        diff.toggled.push(ToggledAddon {
+                            addon_project_id: new_addon.addon_project_id,
+                            addon_name: new_addon.addon_name.clone(),
+                            disabled,
+                        });
+                    }
                 }
             }
         }
@@ -474,73 +1622,410 @@ fn calculate_update_diff(old_manifest: &Manifest, new_manifest: &Manifest) -> Re
     Ok(diff)
 }
 
-async fn remove_old_files(modpack_path: &str, old_manifest: &Manifest, diff: &UpdateDiff) -> Result<(), String> {
-    log::info!("remove_old_files: Starting removal for {} removed, {} updated addons",
+/// Finds the files on disk that belong to removed or updated addons of the old manifest.
+async fn collect_old_files(modpack_path: &Path, old_manifest: &Manifest, diff: &UpdateDiff) -> Result<Vec<PlanStep<'static>>, CemmError> {
+    log::info!("collect_old_files: Looking for {} removed, {} updated addons",
         diff.removed_addons.len(), diff.updated_addon_ids.len());
 
-    async fn remove_category_files(
-        modpack_path: &str,
-        category_dir: &str,
+    async fn collect_category_files(
+        modpack_path: &Path,
+        category: &str,
+        folder: &str,
         old_addons: &[crate::composables::manifest::Addon],
         diff: &UpdateDiff,
-    ) -> Result<(), String> {
-        let category_path = Path::new(modpack_path).join(category_dir);
+        steps: &mut Vec<PlanStep<'static>>,
+    ) -> Result<(), CemmError> {
+        let category_path = modpack_path.join(folder);
         
         if !category_path.exists() {
             return Ok(());
         }
 
         let mut dir_entries = async_fs::read_dir(&category_path).await
-            .map_err(|e| format!("Failed to read directory {}: {}", category_path.display(), e))?;
+            .map_err(|e| CemmError::io("Failed to read directory", &category_path, e))?;
 
-        while let Some(entry) = dir_entries.next_entry().await.map_err(|e| e.to_string())? {
+        while let Some(entry) = dir_entries.next_entry().await
+            .map_err(|e| CemmError::io("Failed to read directory", &category_path, e))?
+        {
             let file_path = entry.path();
             let file_name = file_path.file_name()
                 .and_then(|name| name.to_str())
-                .unwrap_or("");
+                .unwrap_or("")
+                .to_string();
 
             // Check for removed addons
-            for removed_addon in &diff.removed_addons {
-                if let Some(old_addon) = old_addons.iter().find(|a| &a.addon_name == removed_addon) {
-                    let exact_filename = &old_addon.file_name_on_disk;
-                    let disabled_filename = format!("{}.disabled", exact_filename);
-                    
-                    if file_name == exact_filename || file_name == &disabled_filename {
-                        log::info!("Removing file for addon '{}': {}", removed_addon, file_path.display());
-                        async_fs::remove_file(&file_path).await
-                            .map_err(|e| format!("Failed to remove file {}: {}", file_path.display(), e))?;
-                        break;
-                    }
-                }
-            }
+            let removed = diff.removed_addons.iter().find(|removed_addon| {
+                old_addons.iter()
+                    .find(|a| &&a.addon_name == removed_addon)
+                    .is_some_and(|old_addon| matches_addon_file(&file_name, old_addon))
+            });
 
             // Check for updated addons (match by project_id for reliable identification)
-            for old_addon in old_addons {
-                // Check if this addon has an update by matching project_id
-                let is_updated = diff.updated_addon_ids.contains(&old_addon.addon_project_id);
-                if is_updated {
-                    // Use exact filename matching for safety
-                    let exact_filename = &old_addon.file_name_on_disk;
-                    let disabled_filename = format!("{}.disabled", exact_filename);
-                    
-                    if file_name == exact_filename || file_name == &disabled_filename {
-                        log::info!("Removing old version of '{}': {}", old_addon.addon_name, file_path.display());
-                        async_fs::remove_file(&file_path).await
-                            .map_err(|e| format!("Failed to remove old version {}: {}", file_path.display(), e))?;
-                        break;
-                    }
-                }
-            }
+            let updated = old_addons.iter().find(|old_addon| {
+                diff.updated_addon_ids.contains(&old_addon.addon_project_id)
+                    && matches_addon_file(&file_name, old_addon)
+            });
+
+            let (reason, addon_name) = if let Some(removed_addon) = removed {
+                (PlanReason::RemovedAddon, removed_addon.clone())
+            } else if let Some(old_addon) = updated {
+                (PlanReason::OutdatedVersion, old_addon.addon_name.clone())
+            } else {
+                continue;
+            };
+
+            let size = entry.metadata().await.ok().map(|m| m.len());
+            steps.push(PlanStep {
+                file: PlannedFile {
+                    action: PlanAction::Delete,
+                    reason,
+                    category: category.to_string(),
+                    name: addon_name,
+                    relative_path: format!("{}/{}", folder, file_name),
+                    expected_size: size,
+                    renamed_from: None,
+                },
+                source: StepSource::Existing,
+                dest: file_path,
+            });
         }
 
         Ok(())
     }
 
-    remove_category_files(modpack_path, "mods", &old_manifest.mods, diff).await?;
-    remove_category_files(modpack_path, "resourcepacks", &old_manifest.resourcepacks, diff).await?;
-    remove_category_files(modpack_path, "shaderpacks", &old_manifest.shaderpacks, diff).await?;
-    remove_category_files(modpack_path, "datapacks", &old_manifest.datapacks, diff).await?;
+    let mut steps = Vec::new();
+    for category in addon_categories(old_manifest) {
+        // Addons of one category may install into different folders
+        let mut folders: Vec<(String, Vec<Addon>)> = Vec::new();
+        for addon in category.addons {
+            let folder = category.folder_for(modpack_path, addon)?;
+            match folders.iter_mut().find(|(f, _)| *f == folder) {
+                Some((_, addons)) => addons.push(addon.clone()),
+                None => folders.push((folder, vec![addon.clone()])),
+            }
+        }
+        for (folder, addons) in &folders {
+            collect_category_files(modpack_path, category.folder, folder, addons, diff, &mut steps).await?;
+        }
+    }
+    Ok(steps)
+}
+
+/// Finds addon files listed in the install receipt that the new manifest no longer contains.
+///
+/// A file is only removed if its content still matches the receipt, so files the
+/// user replaced or edited since the install are left alone.
+async fn collect_receipt_removals(
+    modpack_path: &Path,
+    receipt: &InstallReceipt,
+    manifest: &Manifest,
+    side: InstallSide,
+) -> Result<Vec<PlanStep<'static>>, CemmError> {
+    let categories = addon_categories(manifest);
+    // Disabled addons stay installed under their `.disabled` name, so both variants are wanted.
+    // Addons for the other side are not, so switching an instance to server drops client-only ones.
+    let mut wanted: HashSet<String> = HashSet::new();
+    let mut current_projects: HashSet<u64> = HashSet::new();
+    let mut other_side_projects: HashSet<u64> = HashSet::new();
+    for category in &categories {
+        for addon in category.addons {
+            if !addon_runs_on(addon, side) {
+                other_side_projects.insert(addon.addon_project_id);
+                continue;
+            }
+            wanted.insert(format!("{}/{}", category.folder_for(modpack_path, addon)?, addon.file_name_on_disk));
+            current_projects.insert(addon.addon_project_id);
+        }
+    }
+    let previous_categories = addon_categories(&receipt.manifest);
+
+    let mut steps = Vec::new();
+    for file in receipt.files.iter().filter(|f| f.kind == ReceiptFileKind::Addon) {
+        let installed_as = file.relative_path.strip_suffix(".disabled").unwrap_or(&file.relative_path);
+        if wanted.contains(installed_as) {
+            continue;
+        }
+
+        let dest = validate_path_within_base(modpack_path, &file.relative_path)?;
+        if !dest.exists() {
+            continue;
+        }
+        if !file.matches(&dest).await {
+            log::warn!("Keeping {}: it was changed after CEMM installed it", file.relative_path);
+            continue;
+        }
+
+        let reason = match file.addon_project_id {
+            Some(id) if current_projects.contains(&id) => PlanReason::OutdatedVersion,
+            Some(id) if other_side_projects.contains(&id) => PlanReason::OtherSide,
+            _ => PlanReason::RemovedAddon,
+        };
+        let previous_addon = previous_categories.iter()
+            .flat_map(|c| c.addons.iter().map(move |a| (c.folder, a)))
+            .find(|(_, a)| Some(a.addon_project_id) == file.addon_project_id);
+        let name = previous_addon
+            .map(|(_, a)| a.addon_name.clone())
+            .unwrap_or_else(|| installed_as.rsplit('/').next().unwrap_or_default().to_string());
+
+        steps.push(PlanStep {
+            file: PlannedFile {
+                action: PlanAction::Delete,
+                reason,
+                category: previous_addon
+                    .map(|(folder, _)| folder)
+                    .unwrap_or_else(|| file.relative_path.split('/').next().unwrap_or_default())
+                    .to_string(),
+                name,
+                relative_path: file.relative_path.clone(),
+                expected_size: Some(file.size),
+                renamed_from: None,
+            },
+            source: StepSource::Existing,
+            dest,
+        });
+    }
+
+    // Override files the manifest no longer lists, or that now belong to the other side
+    for file in receipt.files.iter().filter(|f| f.kind == ReceiptFileKind::Override) {
+        let listed = manifest.overrides.iter()
+            .find(|o| o.relative_path.replace('\\', "/") == file.relative_path);
+        let reason = match listed {
+            Some(o) if override_runs_on(o, side) => continue,
+            Some(_) => PlanReason::OtherSide,
+            None => PlanReason::RemovedOverride,
+        };
+
+        let dest = validate_path_within_base(modpack_path, &file.relative_path)?;
+        if !dest.exists() {
+            continue;
+        }
+        if !file.matches(&dest).await {
+            log::warn!("Keeping {}: it was changed after CEMM installed it", file.relative_path);
+            continue;
+        }
+
+        steps.push(PlanStep {
+            file: PlannedFile {
+                action: PlanAction::Delete,
+                reason,
+                category: OVERRIDES_CATEGORY.to_string(),
+                name: file.relative_path.rsplit('/').next().unwrap_or_default().to_string(),
+                relative_path: file.relative_path.clone(),
+                expected_size: Some(file.size),
+                renamed_from: None,
+            },
+            source: StepSource::Existing,
+            dest,
+        });
+    }
 
-    log::info!("remove_old_files: Removal complete");
+    Ok(steps)
+}
+
+/// Uses exact filename matching for safety, including the `.disabled` variant.
+fn matches_addon_file(file_name: &str, addon: &Addon) -> bool {
+    let exact_filename = &addon.file_name_on_disk;
+    file_name == exact_filename || file_name == format!("{}.disabled", exact_filename)
+}
+
+/// Logs which addon each of the `removed` files belonged to.
+fn log_removals(steps: &[PlanStep<'_>], removed: &[PathBuf]) {
+    for step in steps.iter().filter(|s| s.file.action == PlanAction::Delete && removed.contains(&s.dest)) {
+        match step.file.reason {
+            PlanReason::RemovedAddon => {
+                log::info!("Removing file for addon '{}': {}", step.file.name, step.dest.display())
+            }
+            PlanReason::OutdatedVersion => {
+                log::info!("Removing old version of '{}': {}", step.file.name, step.dest.display())
+            }
+            _ => log::info!("Removing {} file {}", step.file.category, step.file.relative_path),
+        }
+    }
+}
+
+async fn remove_files(files: &[PathBuf]) -> Result<(), CemmError> {
+    log::info!("remove_files: Removing {} old files", files.len());
+
+    for file_path in files {
+        async_fs::remove_file(file_path).await
+            .map_err(|e| CemmError::io("Failed to remove file", file_path, e))?;
+    }
+
+    log::info!("remove_files: Removal complete");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::composables::manifest::AddonEnvironment;
+    use uuid::Uuid;
+
+    /// An instance directory below the system temp dir, removed again on drop.
+    struct TempInstance(PathBuf);
+
+    impl TempInstance {
+        fn new() -> Self {
+            let dir = std::env::temp_dir().join(format!("cemm-installer-test-{}", Uuid::new_v4().simple()));
+            std::fs::create_dir_all(dir.join("mods")).expect("create instance");
+            Self(dir)
+        }
+
+        fn write(&self, relative_path: &str, content: &[u8]) {
+            std::fs::write(self.0.join(relative_path), content).expect("write file");
+        }
+
+        async fn receipt_file(&self, relative_path: &str, project_id: u64) -> ReceiptFile {
+            let mut file = ReceiptFile::from_path(relative_path.to_string(), ReceiptFileKind::Addon, &self.0.join(relative_path))
+                .await
+                .expect("hash file");
+            file.addon_project_id = Some(project_id);
+            file
+        }
+    }
+
+    impl Drop for TempInstance {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn addon(project_id: u64, file_name: &str) -> Addon {
+        Addon {
+            addon_file_id: project_id * 100,
+            addon_name: format!("Addon {}", project_id),
+            addon_project_id: project_id,
+            cdn_download_url: String::new(),
+            mod_folder_path: String::new(),
+            version: "1.0".to_string(),
+            web_site_url: None,
+            disabled: None,
+            file_name_on_disk: file_name.to_string(),
+            file_length: None,
+            file_fingerprint: None,
+            hashes: Vec::new(),
+            alternative_download_urls: Vec::new(),
+            dependencies: Vec::new(),
+            environment: None,
+        }
+    }
+
+    fn manifest(mods: Vec<Addon>) -> Manifest {
+        Manifest {
+            update_type: None,
+            mods,
+            resourcepacks: Vec::new(),
+            shaderpacks: Vec::new(),
+            datapacks: Vec::new(),
+            config_files: Vec::new(),
+            removed_config_files: Vec::new(),
+            overrides: Vec::new(),
+            game_version: None,
+            base_mod_loader: None,
+            base_modpack: None,
+        }
+    }
+
+    fn receipt(manifest: Manifest, files: Vec<ReceiptFile>) -> InstallReceipt {
+        InstallReceipt::new(None, String::new(), manifest, files)
+    }
+
+    async fn removals(instance: &TempInstance, receipt: &InstallReceipt, manifest: &Manifest) -> Vec<(String, PlanReason, String)> {
+        collect_receipt_removals(&instance.0, receipt, manifest, InstallSide::Client)
+            .await
+            .expect("collect removals")
+            .into_iter()
+            .map(|step| (step.file.relative_path, step.file.reason, step.file.name))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn files_still_in_the_manifest_are_kept() {
+        let instance = TempInstance::new();
+        instance.write("mods/a.jar", b"a");
+        let manifest = manifest(vec![addon(1, "a.jar")]);
+        let receipt = receipt(manifest.clone(), vec![instance.receipt_file("mods/a.jar", 1).await]);
+
+        assert!(removals(&instance, &receipt, &manifest).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn removed_and_updated_addons_are_removed() {
+        let instance = TempInstance::new();
+        instance.write("mods/a-1.jar", b"a");
+        instance.write("mods/b.jar", b"b");
+        let previous = manifest(vec![addon(1, "a-1.jar"), addon(2, "b.jar")]);
+        let receipt = receipt(previous, vec![
+            instance.receipt_file("mods/a-1.jar", 1).await,
+            instance.receipt_file("mods/b.jar", 2).await,
+        ]);
+        let manifest = manifest(vec![addon(1, "a-2.jar")]);
+
+        let mut removals = removals(&instance, &receipt, &manifest).await;
+        removals.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(removals, vec![
+            ("mods/a-1.jar".to_string(), PlanReason::OutdatedVersion, "Addon 1".to_string()),
+            ("mods/b.jar".to_string(), PlanReason::RemovedAddon, "Addon 2".to_string()),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn disabled_renames_are_kept() {
+        let instance = TempInstance::new();
+        instance.write("mods/a.jar.disabled", b"a");
+        instance.write("mods/b.jar", b"b");
+        let receipt = receipt(manifest(vec![addon(1, "a.jar"), addon(2, "b.jar")]), vec![
+            instance.receipt_file("mods/a.jar.disabled", 1).await,
+            instance.receipt_file("mods/b.jar", 2).await,
+        ]);
+        // Re-enabling a and disabling b renames the files, it does not remove them
+        let mut disabled = addon(2, "b.jar");
+        disabled.disabled = Some(true);
+        let manifest = manifest(vec![addon(1, "a.jar"), disabled]);
+
+        assert!(removals(&instance, &receipt, &manifest).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn files_not_in_the_receipt_are_left_alone() {
+        let instance = TempInstance::new();
+        instance.write("mods/a.jar", b"a");
+        instance.write("mods/user-added.jar", b"user");
+        let receipt = receipt(manifest(vec![addon(1, "a.jar")]), vec![instance.receipt_file("mods/a.jar", 1).await]);
+        let manifest = manifest(Vec::new());
+
+        let removals = removals(&instance, &receipt, &manifest).await;
+        assert_eq!(removals.len(), 1);
+        assert_eq!(removals[0].0, "mods/a.jar");
+        assert!(instance.0.join("mods/user-added.jar").exists());
+    }
+
+    #[tokio::test]
+    async fn changed_and_missing_files_are_left_alone() {
+        let instance = TempInstance::new();
+        instance.write("mods/a.jar", b"a");
+        instance.write("mods/b.jar", b"b");
+        let receipt = receipt(manifest(vec![addon(1, "a.jar"), addon(2, "b.jar")]), vec![
+            instance.receipt_file("mods/a.jar", 1).await,
+            instance.receipt_file("mods/b.jar", 2).await,
+        ]);
+        instance.write("mods/a.jar", b"edited by the user");
+        std::fs::remove_file(instance.0.join("mods/b.jar")).unwrap();
+
+        assert!(removals(&instance, &receipt, &manifest(Vec::new())).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn addons_for_the_other_side_are_removed() {
+        let instance = TempInstance::new();
+        instance.write("mods/minimap.jar", b"minimap");
+        let mut minimap = addon(1, "minimap.jar");
+        minimap.environment = Some(AddonEnvironment::Client);
+        let manifest = manifest(vec![minimap]);
+        let receipt = receipt(manifest.clone(), vec![instance.receipt_file("mods/minimap.jar", 1).await]);
+
+        let steps = collect_receipt_removals(&instance.0, &receipt, &manifest, InstallSide::Server).await.unwrap();
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].file.reason, PlanReason::OtherSide);
+    }
+}
@@ -0,0 +1,309 @@
+use crate::composables::manifest::{Addon, BaseModpack, DependencyKind, HashAlgorithm, Manifest};
+use crate::error::CemmError;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tauri::command;
+use tokio::fs as async_fs;
+
+/// CurseForge's description of an instance, inside the modpack directory
+pub const INSTANCE_FILE: &str = "minecraftinstance.json";
+
+/// Copy of the instance file as it was before CEMM first changed it
+pub const INSTANCE_BACKUP_PATH: &str = ".cemm/minecraftinstance.json.bak";
+
+/// What sync_installed_addons changed in the instance file.
+#[derive(Debug, Default)]
+pub struct InstanceFileUpdate {
+    pub updated: usize,
+    pub removed: usize,
+}
+
+pub fn instance_file_path(modpack_path: &Path) -> PathBuf {
+    modpack_path.join(INSTANCE_FILE)
+}
+
+/// Minecraft version, mod loader and modpack an instance was created for.
+#[derive(Debug, Default)]
+pub struct InstanceTarget {
+    pub game_version: Option<String>,
+    /// As named by CurseForge, e.g. "forge-47.2.0" or "neoforge-21.1.77"
+    pub base_mod_loader: Option<String>,
+    /// None for custom profiles that were not installed from a modpack
+    pub base_modpack: Option<BaseModpack>,
+}
+
+/// Reads `gameVersion`, `baseModLoader` and `installedModpack` from the instance's minecraftinstance.json.
+///
+/// Returns None if the instance has no minecraftinstance.json.
+pub async fn read_instance_target(modpack_path: &Path) -> Result<Option<InstanceTarget>, CemmError> {
+    let path = instance_file_path(modpack_path);
+    let content = match async_fs::read_to_string(&path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(CemmError::io("Failed to read", &path, e)),
+    };
+    let instance: Value = serde_json::from_str(&content)
+        .map_err(|e| CemmError::parse(format!("Failed to parse {}: {}", path.display(), e), path.display().to_string()))?;
+    let text = |value: Option<&Value>| {
+        value.and_then(Value::as_str)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    };
+    Ok(Some(InstanceTarget {
+        game_version: text(instance.get("gameVersion")),
+        base_mod_loader: text(instance.get("baseModLoader").and_then(|loader| loader.get("name"))),
+        base_modpack: instance.get("installedModpack").and_then(|modpack| {
+            let installed_file = modpack.get("installedFile")?;
+            Some(BaseModpack {
+                project_id: modpack.get("addonID").and_then(Value::as_u64)?,
+                file_id: installed_file.get("id").and_then(Value::as_u64)?,
+                version_name: text(installed_file.get("displayName"))
+                    .or_else(|| text(installed_file.get("fileName")))
+                    .unwrap_or_default(),
+            })
+        }),
+    }))
+}
+
+/// Differences between what a manifest targets and what an instance runs.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/instance.rs (this file)
+/// - TypeScript: app/types/index.ts (Compatibility interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Compatibility {
+    /// Different Minecraft version or mod loader; installing would break the instance
+    pub mismatches: Vec<String>,
+    /// Other mod loader version or base modpack version, which may still work
+    pub warnings: Vec<String>,
+}
+
+/// Compares the Minecraft version, mod loader and base modpack of `manifest` with the
+/// instance. Anything missing on either side is not checked.
+pub fn check_compatibility(manifest: &Manifest, instance: &InstanceTarget) -> Compatibility {
+    let mut result = Compatibility::default();
+
+    if let (Some(wanted), Some(actual)) = (&manifest.game_version, &instance.game_version) {
+        if wanted != actual {
+            result.mismatches.push(format!(
+                "The modpack is for Minecraft {}, but the instance runs Minecraft {}",
+                wanted, actual
+            ));
+        }
+    }
+
+    if let (Some(wanted), Some(actual)) = (&manifest.base_mod_loader, &instance.base_mod_loader) {
+        if !loader_family(wanted).eq_ignore_ascii_case(loader_family(actual)) {
+            result.mismatches.push(format!(
+                "The modpack is for {}, but the instance uses {}",
+                wanted, actual
+            ));
+        } else if !wanted.eq_ignore_ascii_case(actual) {
+            result.warnings.push(format!(
+                "The modpack was made with {}, the instance uses {}",
+                wanted, actual
+            ));
+        }
+    }
+
+    if let (Some(wanted), Some(actual)) = (&manifest.base_modpack, &instance.base_modpack) {
+        if wanted.project_id != actual.project_id {
+            result.warnings.push(format!(
+                "The update was built on the modpack {}, but the instance was installed from {}",
+                wanted.version_name, actual.version_name
+            ));
+        } else if wanted.file_id != actual.file_id {
+            result.warnings.push(format!(
+                "The update was built on {}, but the instance is on {}. Update the base modpack first",
+                wanted.version_name, actual.version_name
+            ));
+        }
+    }
+
+    result
+}
+
+/// Checks whether `manifest` fits the instance at `modpack_path`, so the preview can
+/// show the same mismatches install_update would refuse or warn about.
+///
+/// Instances without a minecraftinstance.json report no differences.
+#[command]
+pub async fn check_instance_compatibility(modpack_path: String, manifest: Manifest) -> Result<Compatibility, CemmError> {
+    match read_instance_target(Path::new(&modpack_path)).await? {
+        Some(instance) => Ok(check_compatibility(&manifest, &instance)),
+        None => Ok(Compatibility::default()),
+    }
+}
+
+/// "forge" for "forge-47.2.0", "neoforge" for "neoforge-21.1.77"
+fn loader_family(name: &str) -> &str {
+    name.split('-').next().unwrap_or(name)
+}
+
+/// Brings `installedAddons` of minecraftinstance.json in line with an installed manifest,
+/// so the CurseForge app does not report the changed addons as modified or missing.
+///
+/// Entries of addons whose file changed describe the new file. Entries of addons that
+/// `manifest` no longer lists are only removed if their file is among `removed_files`, the
+/// files the install deleted, so addons kept on disk stay listed.
+/// Addons the file does not know yet are left out, the CurseForge app adds them on its next scan.
+/// Everything else in the file is kept as is. Before the first change the original
+/// file is copied to `INSTANCE_BACKUP_PATH`, later syncs keep that copy.
+///
+/// Returns None if the instance has no minecraftinstance.json.
+pub async fn sync_installed_addons(
+    modpack_path: &Path,
+    manifest: &Manifest,
+    removed_files: &[PathBuf],
+) -> Result<Option<InstanceFileUpdate>, CemmError> {
+    let path = instance_file_path(modpack_path);
+    let content = match async_fs::read_to_string(&path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(CemmError::io("Failed to read", &path, e)),
+    };
+    let location = path.display().to_string();
+    let mut instance: Value = serde_json::from_str(&content)
+        .map_err(|e| CemmError::parse(format!("Failed to parse {}: {}", location, e), location.clone()))?;
+    let installed = instance.get_mut("installedAddons")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| CemmError::parse(format!("{} has no installedAddons list", location), location.clone()))?;
+
+    let addons: HashMap<u64, &Addon> = all_addons(manifest)
+        .map(|addon| (addon.addon_project_id, addon))
+        .collect();
+    let removed_names: HashSet<String> = removed_files.iter()
+        .filter_map(|file| file.file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .collect();
+
+    let mut update = InstanceFileUpdate::default();
+    let before = installed.len();
+    installed.retain(|entry| match addon_id(entry) {
+        Some(id) if !addons.contains_key(&id) => !installed_file_names(entry).any(|name| removed_names.contains(name)),
+        _ => true,
+    });
+    update.removed = before - installed.len();
+
+    for entry in installed.iter_mut() {
+        let Some(addon) = addon_id(entry).and_then(|id| addons.get(&id)) else {
+            continue;
+        };
+        let Some(file) = entry.get_mut("installedFile").and_then(Value::as_object_mut) else {
+            continue;
+        };
+        if file.get("id").and_then(Value::as_u64) == Some(addon.addon_file_id) {
+            continue;
+        }
+        update_installed_file(file, addon);
+        update.updated += 1;
+    }
+
+    if update.updated == 0 && update.removed == 0 {
+        return Ok(Some(update));
+    }
+
+    // Only the file the CurseForge app wrote is worth keeping, not one CEMM already changed
+    let backup_path = modpack_path.join(INSTANCE_BACKUP_PATH);
+    if !async_fs::try_exists(&backup_path).await.unwrap_or(false) {
+        if let Some(parent) = backup_path.parent() {
+            async_fs::create_dir_all(parent).await
+                .map_err(|e| CemmError::io("Failed to create directory", parent, e))?;
+        }
+        async_fs::copy(&path, &backup_path).await
+            .map_err(|e| CemmError::io(&format!("Failed to back up {} to", location), &backup_path, e))?;
+    }
+
+    // Keep the file compact if the CurseForge app wrote it that way
+    let serialized = if content.trim_end().contains('\n') {
+        serde_json::to_string_pretty(&instance)
+    } else {
+        serde_json::to_string(&instance)
+    }
+    .map_err(|e| CemmError::validation(format!("Failed to serialize {}: {}", location, e)))?;
+
+    let tmp_path = path.with_extension("json.tmp");
+    async_fs::write(&tmp_path, serialized).await
+        .map_err(|e| CemmError::io("Failed to write", &tmp_path, e))?;
+    async_fs::rename(&tmp_path, &path).await
+        .map_err(|e| CemmError::io(&format!("Failed to move {} to", tmp_path.display()), &path, e))?;
+
+    log::info!(
+        "Updated {}: {} addons changed, {} removed (backup at {})",
+        INSTANCE_FILE,
+        update.updated,
+        update.removed,
+        INSTANCE_BACKUP_PATH
+    );
+    Ok(Some(update))
+}
+
+fn all_addons(manifest: &Manifest) -> impl Iterator<Item = &Addon> {
+    manifest.mods.iter()
+        .chain(&manifest.resourcepacks)
+        .chain(&manifest.shaderpacks)
+        .chain(&manifest.datapacks)
+}
+
+fn addon_id(entry: &Value) -> Option<u64> {
+    entry.get("addonID").and_then(Value::as_u64)
+}
+
+/// Names the entry's file may have on disk.
+fn installed_file_names(entry: &Value) -> impl Iterator<Item = &str> {
+    let file = entry.get("installedFile");
+    ["fileNameOnDisk", "fileName"].into_iter()
+        .filter_map(move |key| file.and_then(|f| f.get(key)).and_then(Value::as_str))
+}
+
+/// Points an `installedFile` object at the file the manifest installed.
+///
+/// Details the manifest does not know about the new file, such as its release date and
+/// game versions, are removed rather than left describing the old file.
+fn update_installed_file(file: &mut Map<String, Value>, addon: &Addon) {
+    file.insert("id".to_string(), json!(addon.addon_file_id));
+    // The manifest's version is the CurseForge file name (see parse_minecraft_instance)
+    file.insert("fileName".to_string(), json!(addon.version));
+    file.insert("displayName".to_string(), json!(addon.version));
+    file.insert("downloadUrl".to_string(), json!(addon.cdn_download_url));
+    if file.contains_key("fileNameOnDisk") {
+        file.insert("fileNameOnDisk".to_string(), json!(addon.file_name_on_disk));
+    }
+    match addon.file_length {
+        Some(length) => file.insert("fileLength".to_string(), json!(length)),
+        None => file.remove("fileLength"),
+    };
+    match addon.file_fingerprint {
+        Some(fingerprint) => file.insert("fileFingerprint".to_string(), json!(fingerprint)),
+        None => file.remove("fileFingerprint"),
+    };
+    // CurseForge hash types: 1 = SHA-1, 2 = MD5
+    let hashes: Vec<Value> = addon.hashes.iter()
+        .map(|hash| {
+            let hash_type = match hash.algorithm {
+                HashAlgorithm::Sha1 => 1,
+                HashAlgorithm::Md5 => 2,
+            };
+            json!({ "value": hash.value, "type": hash_type })
+        })
+        .collect();
+    file.insert("hashes".to_string(), Value::Array(hashes));
+    // CurseForge relation types: 2 = optional, 3 = required
+    let dependencies: Vec<Value> = addon.dependencies.iter()
+        .map(|dependency| {
+            let dependency_type = match dependency.kind {
+                DependencyKind::Optional => 2,
+                DependencyKind::Required => 3,
+            };
+            json!({ "addonId": dependency.addon_project_id, "type": dependency_type })
+        })
+        .collect();
+    file.insert("dependencies".to_string(), Value::Array(dependencies));
+    for stale in ["fileDate", "gameVersion", "modules"] {
+        file.remove(stale);
+    }
+}
@@ -0,0 +1,231 @@
+use crate::composables::manifest::{Addon, FileHash, HashAlgorithm};
+use crate::error::CemmError;
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use tokio::fs as async_fs;
+use tokio::io::AsyncReadExt;
+
+/// Size of the pieces files are read in for hashing, so they are never loaded whole
+const HASH_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Bytes CurseForge leaves out of the fingerprint: tab, newline, carriage return and space.
+fn is_fingerprint_whitespace(byte: u8) -> bool {
+    matches!(byte, 9 | 10 | 13 | 32)
+}
+
+/// Computes the CurseForge file fingerprint (murmur2, seed 1) piece by piece.
+///
+/// CurseForge strips whitespace before hashing, see `is_fingerprint_whitespace`.
+/// murmur2 starts from the length of its input, so the number of bytes that remain
+/// after stripping has to be known up front.
+pub struct FingerprintHasher {
+    h: u32,
+    tail: [u8; 4],
+    tail_len: usize,
+}
+
+impl FingerprintHasher {
+    const M: u32 = 0x5bd1_e995;
+    const R: u32 = 24;
+
+    pub fn new(normalized_len: u64) -> Self {
+        // murmur2 takes a 32-bit length, CurseForge truncates longer files the same way
+        Self { h: 1 ^ (normalized_len as u32), tail: [0; 4], tail_len: 0 }
+    }
+
+    /// Counts the bytes of `bytes` that are part of the fingerprint.
+    pub fn normalized_len(bytes: &[u8]) -> u64 {
+        bytes.iter().filter(|b| !is_fingerprint_whitespace(**b)).count() as u64
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes.iter().filter(|b| !is_fingerprint_whitespace(**b)) {
+            self.tail[self.tail_len] = byte;
+            self.tail_len += 1;
+            if self.tail_len == 4 {
+                let mut k = u32::from_le_bytes(self.tail);
+                k = k.wrapping_mul(Self::M);
+                k ^= k >> Self::R;
+                k = k.wrapping_mul(Self::M);
+                self.h = self.h.wrapping_mul(Self::M);
+                self.h ^= k;
+                self.tail_len = 0;
+            }
+        }
+    }
+
+    pub fn finish(self) -> u32 {
+        let Self { mut h, tail, tail_len } = self;
+        if tail_len >= 3 {
+            h ^= (tail[2] as u32) << 16;
+        }
+        if tail_len >= 2 {
+            h ^= (tail[1] as u32) << 8;
+        }
+        if tail_len >= 1 {
+            h ^= tail[0] as u32;
+            h = h.wrapping_mul(Self::M);
+        }
+
+        h ^= h >> 13;
+        h = h.wrapping_mul(Self::M);
+        h ^= h >> 15;
+        h
+    }
+}
+
+/// Calls `f` with consecutive chunks of the file at `path`.
+async fn for_each_chunk(path: &Path, action: &str, mut f: impl FnMut(&[u8])) -> Result<(), CemmError> {
+    let mut file = async_fs::File::open(path).await
+        .map_err(|e| CemmError::io(action, path, e))?;
+    let mut buffer = vec![0u8; HASH_CHUNK_SIZE];
+    loop {
+        let read = file.read(&mut buffer).await
+            .map_err(|e| CemmError::io(action, path, e))?;
+        if read == 0 {
+            return Ok(());
+        }
+        f(&buffer[..read]);
+    }
+}
+
+fn hex(digest: &[u8]) -> String {
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Returns the lowercase hex SHA-256 digest of `bytes`.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    hex(&Sha256::digest(bytes))
+}
+
+/// Returns the SHA-256 digest of the file at `path`, reading it in chunks.
+pub async fn sha256_file(path: &Path) -> Result<String, CemmError> {
+    let mut hasher = Sha256::new();
+    for_each_chunk(path, "Failed to hash", |chunk| hasher.update(chunk)).await?;
+    Ok(hex(&hasher.finalize()))
+}
+
+/// Returns true if the addon carries any size or hash information that can be checked.
+pub fn has_integrity_info(addon: &Addon) -> bool {
+    addon.file_length.is_some() || addon.file_fingerprint.is_some() || !addon.hashes.is_empty()
+}
+
+/// Verifies the file at `path` against the size, hashes and fingerprint recorded on the addon.
+///
+/// Every piece of information that is present must match. Addons from older
+/// manifests without any integrity info always pass. The file is read in chunks,
+/// twice if it has to be fingerprinted.
+pub async fn verify_addon_file(addon: &Addon, path: &Path) -> Result<(), CemmError> {
+    if !has_integrity_info(addon) {
+        log::debug!("No integrity info for {}, skipping verification", addon.addon_name);
+        return Ok(());
+    }
+    let action = "Failed to read for verification";
+
+    if let Some(expected_len) = addon.file_length {
+        let size = async_fs::metadata(path).await
+            .map_err(|e| CemmError::io(action, path, e))?
+            .len();
+        if size != expected_len {
+            return Err(CemmError::validation(format!(
+                "Size mismatch for {}: expected {} bytes, got {}",
+                addon.file_name_on_disk, expected_len, size
+            )));
+        }
+    }
+
+    let wants = |algorithm| addon.hashes.iter().any(|h| h.algorithm == algorithm);
+    let mut sha1 = wants(HashAlgorithm::Sha1).then(Sha1::new);
+    let mut md5 = wants(HashAlgorithm::Md5).then(Md5::new);
+    let mut normalized_len = 0u64;
+    for_each_chunk(path, action, |chunk| {
+        if let Some(sha1) = &mut sha1 {
+            sha1.update(chunk);
+        }
+        if let Some(md5) = &mut md5 {
+            md5.update(chunk);
+        }
+        if addon.file_fingerprint.is_some() {
+            normalized_len += FingerprintHasher::normalized_len(chunk);
+        }
+    })
+    .await?;
+    let sha1 = sha1.map(|hasher| hex(&hasher.finalize()));
+    let md5 = md5.map(|hasher| hex(&hasher.finalize()));
+
+    for FileHash { algorithm, value } in &addon.hashes {
+        let actual = match algorithm {
+            HashAlgorithm::Sha1 => sha1.as_deref(),
+            HashAlgorithm::Md5 => md5.as_deref(),
+        }
+        .unwrap_or_default();
+        if !actual.eq_ignore_ascii_case(value) {
+            return Err(CemmError::validation(format!(
+                "{:?} mismatch for {}: expected {}, got {}",
+                algorithm, addon.file_name_on_disk, value, actual
+            )));
+        }
+    }
+
+    if let Some(expected_fp) = addon.file_fingerprint {
+        let mut hasher = FingerprintHasher::new(normalized_len);
+        for_each_chunk(path, action, |chunk| hasher.update(chunk)).await?;
+        let actual = hasher.finish();
+        if actual != expected_fp {
+            return Err(CemmError::validation(format!(
+                "Fingerprint mismatch for {}: expected {}, got {}",
+                addon.file_name_on_disk, expected_fp, actual
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fingerprint(bytes: &[u8]) -> u32 {
+        let mut hasher = FingerprintHasher::new(FingerprintHasher::normalized_len(bytes));
+        hasher.update(bytes);
+        hasher.finish()
+    }
+
+    #[test]
+    fn empty_file_has_the_curseforge_fingerprint() {
+        // The fingerprint CurseForge reports for an empty file
+        assert_eq!(fingerprint(b""), 1540447798);
+    }
+
+    #[test]
+    fn fingerprint_covers_every_tail_length() {
+        assert_eq!(fingerprint(b"a"), 626045324);
+        assert_eq!(fingerprint(b"ab"), 1692487918);
+        assert_eq!(fingerprint(b"abc"), 1621425345);
+        assert_eq!(fingerprint(b"abcd"), 3376380438);
+        assert_eq!(fingerprint(b"helloworld"), 2824650221);
+    }
+
+    #[test]
+    fn fingerprint_ignores_whitespace() {
+        assert_eq!(fingerprint(b" a\tb\r\nc d\n"), fingerprint(b"abcd"));
+        assert_eq!(fingerprint(b"hello world"), 2824650221);
+        assert_eq!(fingerprint(b" \t\r\n"), fingerprint(b""));
+        assert_eq!(FingerprintHasher::normalized_len(b" a\tb\r\nc d\n"), 4);
+    }
+
+    #[test]
+    fn fingerprint_does_not_depend_on_chunk_boundaries() {
+        let bytes = b"The quick brown fox\r\njumps over\tthe lazy dog";
+        for chunk_size in 1..=9 {
+            let mut hasher = FingerprintHasher::new(FingerprintHasher::normalized_len(bytes));
+            for chunk in bytes.chunks(chunk_size) {
+                hasher.update(chunk);
+            }
+            assert_eq!(hasher.finish(), 3751777527, "chunk size {}", chunk_size);
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use std::fs;
+use std::path::Path;
 use tauri::Manager;
 use tauri_plugin_dialog::{DialogExt, FileDialogBuilder};
 
@@ -22,13 +23,44 @@ mod composables {
 mod updater;
 pub use updater::{check_for_updates, download_updater_file, install_updater_file};
 
-pub use composables::github::{download_update, download_manifest, download_config_files, upload_update};
+pub use composables::github::{
+    download_update, download_manifest, download_config_files, download_overrides, upload_update,
+};
 pub use composables::manifest::{
     compare_manifests, open_curseforge_url, open_url, parse_minecraft_instance, Addon, Manifest,
-    UpdateInfo,
+    OverrideFile, ReleaseAsset, UpdateInfo,
 };
+mod cache;
+mod cancel;
+mod download;
+mod environment;
+mod error;
+mod instance;
+mod integrity;
+mod merge;
+mod overrides;
+mod preflight;
+mod receipt;
+mod retry;
+mod snapshot;
+mod transaction;
 mod installer;
-pub use installer::{install_update, ConfigFile as InstallerConfigFile, InstallOptions};
+mod server_pack;
+mod verify;
+pub use cancel::{cancel_install, cancel_upload};
+pub use error::CemmError;
+pub use cache::{get_download_cache_info, prune_download_cache, CachePruneResult, DownloadCacheInfo};
+pub use instance::{check_instance_compatibility, Compatibility};
+pub use merge::ConfigConflict;
+pub use overrides::{read_overrides_directory, OverrideFileWithContent};
+pub use preflight::{preflight_install, PreflightIssue, PreflightIssueKind, PreflightReport};
+pub use receipt::{get_install_receipt, InstallReceipt};
+pub use server_pack::{export_server_pack, ServerPackOptions, ServerPackResult};
+pub use snapshot::{delete_snapshot, list_snapshots, prune_snapshots, restore_snapshot, SnapshotInfo};
+pub use installer::{
+    install_update, plan_install, ConfigFile as InstallerConfigFile, InstallOptions, InstallPlan, InstallResult,
+};
+pub use verify::{repair_instance, verify_instance, RepairOptions, RepairResult, VerifyReport};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -37,6 +69,7 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_keyring::init())
+        .manage(cancel::Operations::default())
         .invoke_handler(tauri::generate_handler![
             select_directory,
             select_file,
@@ -51,7 +84,23 @@ pub fn run() {
             download_update,
             download_manifest,
             download_config_files,
+            download_overrides,
             install_update,
+            plan_install,
+            preflight_install,
+            cancel_install,
+            cancel_upload,
+            get_download_cache_info,
+            prune_download_cache,
+            get_install_receipt,
+            list_snapshots,
+            restore_snapshot,
+            delete_snapshot,
+            prune_snapshots,
+            verify_instance,
+            repair_instance,
+            check_instance_compatibility,
+            export_server_pack,
             check_for_updates,
             download_updater_file,
             install_updater_file,
@@ -59,6 +108,7 @@ pub fn run() {
             select_multiple_files,
             select_config_directory,
             read_directory_recursive,
+            read_overrides_directory,
             is_binary_file,
             validate_path
         ])
@@ -104,9 +154,10 @@ pub fn run() {
 ///
 /// # Returns
 /// * `Ok(String)` - The selected directory path
-/// * `Err(String)` - Error message with suggestion to use manual path input
+/// * `Err(CemmError)` - `cancelled` if no directory was picked, otherwise an `io` error
+///   with a suggestion to use manual path input
 #[tauri::command]
-async fn select_directory(app: tauri::AppHandle) -> Result<String, String> {
+async fn select_directory(app: tauri::AppHandle) -> Result<String, CemmError> {
     log::info!("select_directory: attempting to open dialog");
     
     // Use Tauri's built-in dialog with timeout
@@ -122,17 +173,33 @@ async fn select_directory(app: tauri::AppHandle) -> Result<String, String> {
         }
         Ok(Err(e)) => {
             log::error!("select_directory: dialog error: {}", e);
-            Err(format!("Dialog error: {}. Try using manual path input instead.", e))
+            Err(dialog_error(e))
         }
         Err(_) => {
             log::error!("select_directory: dialog timeout");
-            Err("Dialog timeout - the system dialog may be unresponsive. Please try manual path input.".to_string())
+            Err(dialog_timeout())
         }
     }
 }
 
+/// Closing a dialog without picking anything cancels the command.
+fn nothing_selected(message: &str) -> CemmError {
+    CemmError::Cancelled { message: message.to_string() }
+}
+
+fn dialog_error(e: CemmError) -> CemmError {
+    if e.is_cancelled() {
+        return e;
+    }
+    CemmError::io_message(format!("Dialog error: {}. Try using manual path input instead.", e), None)
+}
+
+fn dialog_timeout() -> CemmError {
+    CemmError::io_message("Dialog timeout - the system dialog may be unresponsive. Please try manual path input.", None)
+}
+
 // Async wrapper for Tauri dialog
-async fn async_directory_dialog(app: tauri::AppHandle) -> Result<String, String> {
+async fn async_directory_dialog(app: tauri::AppHandle) -> Result<String, CemmError> {
     let (tx, rx) = tokio::sync::oneshot::channel();
     
     log::info!("Starting Tauri directory dialog");
@@ -147,7 +214,7 @@ async fn async_directory_dialog(app: tauri::AppHandle) -> Result<String, String>
                     let normalized_path = normalize_path(&path_str);
                     Ok(normalized_path)
                 },
-                None => Err("No directory selected".to_string()),
+                None => Err(nothing_selected("No directory selected")),
             };
             let _ = tx.send(result);
         });
@@ -164,13 +231,13 @@ async fn async_directory_dialog(app: tauri::AppHandle) -> Result<String, String>
         },
         Err(_) => {
             log::error!("Tauri directory dialog channel error");
-            Err("Dialog communication error".to_string())
+            Err(CemmError::io_message("Dialog communication error", None))
         }
     }
 }
 
 #[tauri::command]
-async fn select_file(app: tauri::AppHandle) -> Result<String, String> {
+async fn select_file(app: tauri::AppHandle) -> Result<String, CemmError> {
     log::info!("select_file: attempting to open dialog");
     
     // Use Tauri's built-in dialog with timeout
@@ -184,16 +251,16 @@ async fn select_file(app: tauri::AppHandle) -> Result<String, String> {
         }
         Ok(Err(e)) => {
             log::error!("select_file: dialog error: {}", e);
-            Err(format!("Dialog error: {}. Try using manual path input instead.", e))
+            Err(dialog_error(e))
         }
         Err(_) => {
             log::error!("select_file: dialog timeout");
-            Err("Dialog timeout - the system dialog may be unresponsive. Please try manual path input.".to_string())
+            Err(dialog_timeout())
         }
     }
 }
 
-async fn async_file_dialog(app: tauri::AppHandle) -> Result<String, String> {
+async fn async_file_dialog(app: tauri::AppHandle) -> Result<String, CemmError> {
     let (tx, rx) = tokio::sync::oneshot::channel();
     
     log::info!("Starting Tauri file dialog");
@@ -208,7 +275,7 @@ async fn async_file_dialog(app: tauri::AppHandle) -> Result<String, String> {
                     let normalized_path = normalize_path(&path_str);
                     Ok(normalized_path)
                 },
-                None => Err("No file selected".to_string()),
+                None => Err(nothing_selected("No file selected")),
             };
             let _ = tx.send(result);
         });
@@ -225,19 +292,19 @@ async fn async_file_dialog(app: tauri::AppHandle) -> Result<String, String> {
         },
         Err(_) => {
             log::error!("Tauri file dialog channel error");
-            Err("Dialog communication error".to_string())
+            Err(CemmError::io_message("Dialog communication error", None))
         }
     }
 }
 
 #[tauri::command]
-fn read_file(path: String) -> Result<String, String> {
+fn read_file(path: String) -> Result<String, CemmError> {
     log::info!("read_file: attempting to read {path}");
     
     // Check if file exists first
     if !std::path::Path::new(&path).exists() {
         log::error!("read_file: file does not exist: {path}");
-        return Err(format!("File does not exist: {}", path));
+        return Err(CemmError::not_found(format!("File does not exist: {}", path), path));
     }
     
     match fs::read_to_string(&path) {
@@ -263,30 +330,29 @@ fn read_file(path: String) -> Result<String, String> {
                     }
                     Err(read_err) => {
                         log::error!("read_file: failed to read binary file: {path}: {read_err}");
-                        Err(format!("Failed to read file as text or binary: {}", read_err))
+                        Err(CemmError::io("Failed to read binary file", Path::new(&path), read_err))
                     }
                 }
             } else {
-                Err(e.to_string())
+                Err(CemmError::io("Failed to read", Path::new(&path), e))
             }
         }
     }
 }
 
 #[tauri::command]
-fn write_file(path: Option<String>, content: Option<String>, dir: Option<String>, files: Option<Vec<(String, String)>>) -> Result<(), String> {
-    use std::path::Path;
+fn write_file(path: Option<String>, content: Option<String>, dir: Option<String>, files: Option<Vec<(String, String)>>) -> Result<(), CemmError> {
     // Batch mode
     if let (Some(dir), Some(files)) = (dir, files) {
         for (filename, content) in files {
             let file_path = Path::new(&dir).join(&filename);
             if let Some(parent) = file_path.parent() {
                 if let Err(e) = std::fs::create_dir_all(parent) {
-                    return Err(format!("Failed to create directory {}: {}", parent.display(), e));
+                    return Err(CemmError::io("Failed to create directory", parent, e));
                 }
             }
             if let Err(e) = std::fs::write(&file_path, content) {
-                return Err(format!("Failed to write file {}: {}", file_path.display(), e));
+                return Err(CemmError::io("Failed to write file", &file_path, e));
             }
         }
         return Ok(());
@@ -295,17 +361,17 @@ fn write_file(path: Option<String>, content: Option<String>, dir: Option<String>
     if let (Some(path), Some(content)) = (path, content) {
         if let Some(parent) = Path::new(&path).parent() {
             if let Err(e) = std::fs::create_dir_all(parent) {
-                return Err(format!("Failed to create directory {}: {}", parent.display(), e));
+                return Err(CemmError::io("Failed to create directory", parent, e));
             }
         }
         return std::fs::write(&path, content)
-            .map_err(|e| format!("Failed to write file {}: {}", path, e));
+            .map_err(|e| CemmError::io("Failed to write file", Path::new(&path), e));
     }
-    Err("Invalid arguments: must provide either (path, content) or (dir, files)".to_string())
+    Err(CemmError::validation("Invalid arguments: must provide either (path, content) or (dir, files)"))
 }
 
 #[tauri::command]
-fn select_save_file(app: tauri::AppHandle) -> Result<String, String> {
+fn select_save_file(app: tauri::AppHandle) -> Result<String, CemmError> {
     use std::sync::mpsc;
     log::info!("select_save_file: dialog opened");
     let (tx, rx) = mpsc::channel();
@@ -325,24 +391,25 @@ fn select_save_file(app: tauri::AppHandle) -> Result<String, String> {
         }
         Ok(None) => {
             log::info!("select_save_file: no file selected");
-            Err("No file selected".to_string())
+            Err(nothing_selected("No file selected"))
         }
         Err(e) => {
             log::error!("select_save_file: error receiving dialog result: {e}");
-            Err("No file selected".to_string())
+            Err(nothing_selected("No file selected"))
         }
     }
 }
 
 #[tauri::command]
-fn get_app_data_dir(app: tauri::AppHandle) -> Result<String, String> {
-    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+fn get_app_data_dir(app: tauri::AppHandle) -> Result<String, CemmError> {
+    let app_data_dir = app.path().app_data_dir()
+        .map_err(|e| CemmError::io_message(format!("Failed to resolve the app data directory: {}", e), None))?;
     let normalized_path = normalize_path(&app_data_dir.to_string_lossy());
     Ok(normalized_path)
 }
 
 #[tauri::command]
-fn select_multiple_files(window: tauri::Window) -> Result<Vec<String>, String> {
+fn select_multiple_files(window: tauri::Window) -> Result<Vec<String>, CemmError> {
     let dialog = window
         .dialog()
         .file()
@@ -360,7 +427,7 @@ fn select_multiple_files(window: tauri::Window) -> Result<Vec<String>, String> {
 }
 
 #[tauri::command]
-fn select_config_directory(window: tauri::Window) -> Result<String, String> {
+fn select_config_directory(window: tauri::Window) -> Result<String, CemmError> {
     let dialog = window
         .dialog()
         .file()
@@ -368,14 +435,12 @@ fn select_config_directory(window: tauri::Window) -> Result<String, String> {
     
     match dialog.blocking_pick_folder() {
         Some(folder) => Ok(normalize_path(&folder.to_string())),
-        None => Err("No directory selected".to_string()),
+        None => Err(nothing_selected("No directory selected")),
     }
 }
 
 #[tauri::command]
-fn read_directory_recursive(dir_path: String, base_path: String) -> Result<Vec<ConfigFileWithContent>, String> {
-    use std::path::Path;
-    
+fn read_directory_recursive(dir_path: String, base_path: String) -> Result<Vec<ConfigFileWithContent>, CemmError> {
     let mut config_files = Vec::new();
     let dir = Path::new(&dir_path);
     let base = Path::new(&base_path);
@@ -384,12 +449,12 @@ fn read_directory_recursive(dir_path: String, base_path: String) -> Result<Vec<C
         dir: &Path, 
         base: &Path, 
         config_files: &mut Vec<ConfigFileWithContent>
-    ) -> Result<(), String> {
+    ) -> Result<(), CemmError> {
         let entries = std::fs::read_dir(dir)
-            .map_err(|e| format!("Failed to read directory {}: {}", dir.display(), e))?;
+            .map_err(|e| CemmError::io("Failed to read directory", dir, e))?;
             
         for entry in entries {
-            let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
+            let entry = entry.map_err(|e| CemmError::io("Failed to read entry in", dir, e))?;
             let path = entry.path();
             
             if path.is_file() {
@@ -414,18 +479,21 @@ fn read_directory_recursive(dir_path: String, base_path: String) -> Result<Vec<C
                                         let encoded = STANDARD.encode(&bytes);
                                         format!("data:application/octet-stream;base64,{}", encoded)
                                     }
-                                    Err(e) => return Err(format!("Failed to read file {}: {}", path.display(), e))
+                                    Err(e) => return Err(CemmError::io("Failed to read file", &path, e))
                                 }
                             }
                         };
                         
                         // Calculate relative path from base directory
                         let relative_path = path.strip_prefix(base)
-                            .map_err(|_| format!("Failed to make path relative: {}", path.display()))?
+                            .map_err(|_| CemmError::path_traversal(
+                                format!("Failed to make path relative: {}", path.display()),
+                                path.display().to_string(),
+                            ))?
                             .to_string_lossy()
                             .replace('\\', "/"); // Normalize path separators
                         let filename = path.file_name()
-                            .ok_or_else(|| format!("Failed to get filename from path: {}", path.display()))?
+                            .ok_or_else(|| CemmError::validation(format!("Failed to get filename from path: {}", path.display())))?
                             .to_string_lossy()
                             .to_string();
                         
@@ -454,11 +522,11 @@ fn read_directory_recursive(dir_path: String, base_path: String) -> Result<Vec<C
 }
 
 #[tauri::command]
-fn is_binary_file(path: String) -> Result<bool, String> {
+fn is_binary_file(path: String) -> Result<bool, CemmError> {
     log::info!("is_binary_file: checking {path}");
     
     if !std::path::Path::new(&path).exists() {
-        return Err(format!("File does not exist: {}", path));
+        return Err(CemmError::not_found(format!("File does not exist: {}", path), path));
     }
     
     // Read first 512 bytes to check for binary content
@@ -486,13 +554,13 @@ fn is_binary_file(path: String) -> Result<bool, String> {
         }
         Err(e) => {
             log::error!("is_binary_file: failed to read {path}: {e}");
-            Err(e.to_string())
+            Err(CemmError::io("Failed to read", Path::new(&path), e))
         }
     }
 }
 
 #[tauri::command]
-fn validate_path(path: String) -> Result<serde_json::Value, String> {
+fn validate_path(path: String) -> Result<serde_json::Value, CemmError> {
     log::info!("validate_path: checking path {}", path);
     
     let path_obj = std::path::Path::new(&path);
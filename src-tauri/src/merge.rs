@@ -0,0 +1,753 @@
+use crate::error::CemmError;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Where the modpack's version of every installed config file is kept, relative to
+/// the modpack directory. It is the common base for merging the next update.
+pub const CONFIG_BASE_DIR: &str = ".cemm/config-base";
+
+/// Config formats that can be merged setting by setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Json,
+    Json5,
+    /// Java `.properties` files (`key=value` or `key: value`)
+    Properties,
+    /// Forge style `.cfg` files with `category { ... }` blocks and `S:list < ... >` values
+    ForgeCfg,
+    /// Minecraft's `options.txt` and OptiFine's `optionsof.txt` (`key:value`)
+    Options,
+}
+
+impl ConfigFormat {
+    /// Picks the format from the file name. Returns `None` for files that are always overwritten.
+    pub fn detect(relative_path: &str) -> Option<Self> {
+        let file_name = relative_path
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or(relative_path)
+            .to_lowercase();
+        if file_name == "options.txt" || file_name == "optionsof.txt" {
+            return Some(ConfigFormat::Options);
+        }
+        match file_name.rsplit_once('.').map(|(_, extension)| extension) {
+            Some("toml") => Some(ConfigFormat::Toml),
+            Some("json") => Some(ConfigFormat::Json),
+            Some("json5") => Some(ConfigFormat::Json5),
+            Some("properties") => Some(ConfigFormat::Properties),
+            Some("cfg") => Some(ConfigFormat::ForgeCfg),
+            _ => None,
+        }
+    }
+}
+
+/// A setting that both the user and the modpack changed to different values.
+/// The modpack's value is the one that gets installed.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/merge.rs (this file)
+/// - TypeScript: app/types/index.ts (ConfigConflict interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigConflict {
+    /// Config file path relative to the modpack directory
+    pub relative_path: String,
+    /// The conflicting setting, prefixed with its section or parent keys
+    pub key: String,
+    /// Value in the previously installed version, `None` if the setting did not exist
+    pub base: Option<String>,
+    /// The user's value, `None` if they removed the setting
+    pub local: Option<String>,
+    /// The modpack's new value, `None` if the update removes the setting
+    pub incoming: Option<String>,
+}
+
+/// Content to install for a config file, together with the conflicts found while merging.
+#[derive(Debug, Clone)]
+pub struct MergeResult {
+    pub content: String,
+    pub conflicts: Vec<ConfigConflict>,
+}
+
+/// Returns where the base version of a config file is kept. `relative_path` must already be validated.
+pub fn config_base_path(modpack_path: &Path, relative_path: &str) -> PathBuf {
+    modpack_path.join(CONFIG_BASE_DIR).join(relative_path)
+}
+
+/// Three-way merges a config file.
+///
+/// `base` is the version the modpack shipped last time, `local` the file as it is on
+/// disk now and `incoming` the version from the update. Settings only the user changed
+/// keep the user's value, settings the modpack changed get the new value. When both
+/// changed the same setting differently the modpack wins and a conflict is reported.
+pub fn merge_config(
+    format: ConfigFormat,
+    relative_path: &str,
+    base: &str,
+    local: &str,
+    incoming: &str,
+) -> Result<MergeResult, CemmError> {
+    // Nothing to merge if only one side changed
+    if local == base || local == incoming {
+        return Ok(MergeResult { content: incoming.to_string(), conflicts: Vec::new() });
+    }
+    if incoming == base {
+        return Ok(MergeResult { content: local.to_string(), conflicts: Vec::new() });
+    }
+
+    match format {
+        ConfigFormat::Json | ConfigFormat::Json5 => merge_json(format, relative_path, base, local, incoming),
+        _ => Ok(merge_lines(format, relative_path, base, local, incoming)),
+    }
+}
+
+/// Which version a merged setting is taken from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Local,
+    Incoming,
+}
+
+/// Decides a single setting. Returns the side to take and whether it was a conflict.
+fn pick<T: PartialEq>(base: Option<T>, local: Option<T>, incoming: Option<T>) -> (Side, bool) {
+    if local == base || local == incoming {
+        (Side::Incoming, false)
+    } else if incoming == base {
+        (Side::Local, false)
+    } else {
+        (Side::Incoming, true)
+    }
+}
+
+fn parse_json(format: ConfigFormat, relative_path: &str, content: &str) -> Result<Value, CemmError> {
+    let parse_error = |e: &dyn std::fmt::Display| {
+        CemmError::parse(format!("Failed to parse {}: {}", relative_path, e), relative_path)
+    };
+    if format == ConfigFormat::Json5 {
+        json5::from_str(content).map_err(|e| parse_error(&e))
+    } else {
+        serde_json::from_str(content).map_err(|e| parse_error(&e))
+    }
+}
+
+/// Merges JSON objects key by key. Arrays and other values are merged as a whole.
+///
+/// The result is written as plain JSON, which is also valid JSON5, so comments in
+/// JSON5 files only survive if one side left the file unchanged.
+fn merge_json(
+    format: ConfigFormat,
+    relative_path: &str,
+    base: &str,
+    local: &str,
+    incoming: &str,
+) -> Result<MergeResult, CemmError> {
+    let base = parse_json(format, relative_path, base)?;
+    let local = parse_json(format, relative_path, local)?;
+    let incoming_value = parse_json(format, relative_path, incoming)?;
+
+    let mut conflicts = Vec::new();
+    let merged = merge_json_value(relative_path, "", Some(&base), Some(&local), Some(&incoming_value), &mut conflicts)
+        .unwrap_or(incoming_value);
+
+    let mut content = serde_json::to_string_pretty(&merged)
+        .map_err(|e| CemmError::validation(format!("Failed to serialize {}: {}", relative_path, e)))?;
+    if incoming.ends_with('\n') {
+        content.push('\n');
+    }
+    Ok(MergeResult { content, conflicts })
+}
+
+fn merge_json_value(
+    relative_path: &str,
+    key: &str,
+    base: Option<&Value>,
+    local: Option<&Value>,
+    incoming: Option<&Value>,
+    conflicts: &mut Vec<ConfigConflict>,
+) -> Option<Value> {
+    if let (Some(Value::Object(local)), Some(Value::Object(incoming))) = (local, incoming) {
+        let base = base.and_then(Value::as_object);
+        let mut merged = Map::new();
+        // Keep the modpack's key order and append keys only the user has
+        let keys = incoming.keys().chain(local.keys().filter(|k| !incoming.contains_key(*k)));
+        for child in keys {
+            let child_key = if key.is_empty() { child.clone() } else { format!("{}.{}", key, child) };
+            let value = merge_json_value(
+                relative_path,
+                &child_key,
+                base.and_then(|b| b.get(child)),
+                local.get(child),
+                incoming.get(child),
+                conflicts,
+            );
+            if let Some(value) = value {
+                merged.insert(child.clone(), value);
+            }
+        }
+        return Some(Value::Object(merged));
+    }
+
+    let (side, conflict) = pick(base, local, incoming);
+    if conflict {
+        conflicts.push(ConfigConflict {
+            relative_path: relative_path.to_string(),
+            key: key.to_string(),
+            base: base.map(Value::to_string),
+            local: local.map(Value::to_string),
+            incoming: incoming.map(Value::to_string),
+        });
+    }
+    match side {
+        Side::Local => local.cloned(),
+        Side::Incoming => incoming.cloned(),
+    }
+}
+
+/// A setting in a line based config file.
+#[derive(Debug, Clone)]
+struct Entry {
+    /// Section the setting is in, empty for top level settings
+    section: String,
+    /// Section and key, used to match settings between versions
+    key: String,
+    /// Everything up to and including the separator, e.g. `    B:enabled=`
+    prefix: String,
+    /// Raw value, may span several lines
+    value: String,
+    /// Line ending after the value
+    ending: String,
+}
+
+impl Entry {
+    /// Value with whitespace differences ignored, used for comparisons.
+    fn normalized(&self) -> String {
+        self.value.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    /// Comments, blank lines, section headers and anything else that is kept as it is
+    Text(String),
+    Entry(Entry),
+}
+
+/// Splits a line into its content and line ending.
+fn split_ending(line: &str) -> (&str, &str) {
+    let content = line.trim_end_matches(['\r', '\n']);
+    (content, &line[content.len()..])
+}
+
+fn full_key(section: &str, key: &str) -> String {
+    if section.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", section, key)
+    }
+}
+
+fn parse_lines(format: ConfigFormat, content: &str) -> Vec<Segment> {
+    match format {
+        ConfigFormat::Toml => parse_toml(content),
+        ConfigFormat::ForgeCfg => parse_forge_cfg(content),
+        ConfigFormat::Options => parse_key_values(content, &[':'], &[]),
+        _ => parse_key_values(content, &['=', ':'], &['#', '!']),
+    }
+}
+
+/// Parses flat `key<separator>value` files such as `.properties` and `options.txt`.
+fn parse_key_values(content: &str, separators: &[char], comment_chars: &[char]) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut lines = content.split_inclusive('\n');
+
+    while let Some(line) = lines.next() {
+        let (text, ending) = split_ending(line);
+        let trimmed = text.trim_start();
+        let is_comment = trimmed.is_empty() || trimmed.starts_with(comment_chars);
+        let Some(separator) = text.find(separators).filter(|_| !is_comment) else {
+            segments.push(Segment::Text(line.to_string()));
+            continue;
+        };
+
+        let mut value = text[separator + 1..].to_string();
+        let mut ending = ending.to_string();
+        // .properties values continue on the next line after a trailing backslash
+        while value.ends_with('\\') && !value.ends_with("\\\\") {
+            let Some(next) = lines.next() else { break };
+            let (next_text, next_ending) = split_ending(next);
+            value.push_str(&ending);
+            value.push_str(next_text);
+            ending = next_ending.to_string();
+        }
+
+        segments.push(Segment::Entry(Entry {
+            section: String::new(),
+            key: text[..separator].trim().to_string(),
+            prefix: text[..=separator].to_string(),
+            value,
+            ending,
+        }));
+    }
+    segments
+}
+
+/// Parses Forge `.cfg` files. Categories nest with `name {` and `}`, lists span
+/// several lines between `S:name <` and `>`.
+fn parse_forge_cfg(content: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut categories: Vec<String> = Vec::new();
+    let mut lines = content.split_inclusive('\n');
+
+    while let Some(line) = lines.next() {
+        let (text, ending) = split_ending(line);
+        let trimmed = text.trim();
+
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            segments.push(Segment::Text(line.to_string()));
+        } else if trimmed == "}" {
+            categories.pop();
+            segments.push(Segment::Text(line.to_string()));
+        } else if let Some(name) = trimmed.strip_suffix('{') {
+            categories.push(name.trim().trim_matches('"').to_string());
+            segments.push(Segment::Text(line.to_string()));
+        } else if let Some(separator) = text.find('=') {
+            let section = categories.join(".");
+            segments.push(Segment::Entry(Entry {
+                key: full_key(&section, text[..separator].trim()),
+                section,
+                prefix: text[..=separator].to_string(),
+                value: text[separator + 1..].to_string(),
+                ending: ending.to_string(),
+            }));
+        } else if trimmed.ends_with('<') {
+            let separator = text.rfind('<').unwrap_or(text.len() - 1);
+            let mut value = text[separator + 1..].to_string();
+            let mut ending = ending.to_string();
+            for next in lines.by_ref() {
+                let (next_text, next_ending) = split_ending(next);
+                value.push_str(&ending);
+                value.push_str(next_text);
+                ending = next_ending.to_string();
+                if next_text.trim() == ">" {
+                    break;
+                }
+            }
+            let section = categories.join(".");
+            segments.push(Segment::Entry(Entry {
+                key: full_key(&section, text[..separator].trim()),
+                section,
+                prefix: text[..=separator].to_string(),
+                value,
+                ending,
+            }));
+        } else {
+            segments.push(Segment::Text(line.to_string()));
+        }
+    }
+    segments
+}
+
+/// Parses TOML files line by line so comments and formatting survive the merge.
+/// Values that span several lines (arrays, inline tables, multi-line strings) are kept together.
+fn parse_toml(content: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut section = String::new();
+    // `[[array]]` tables repeat the same header, so each occurrence gets its own index
+    let mut array_tables: HashMap<String, usize> = HashMap::new();
+    let mut lines = content.split_inclusive('\n');
+
+    while let Some(line) = lines.next() {
+        let (text, ending) = split_ending(line);
+        let trimmed = text.trim();
+
+        if let Some(name) = trimmed.strip_prefix("[[").and_then(|rest| rest.split("]]").next()) {
+            let name = name.trim().to_string();
+            let index = array_tables.entry(name.clone()).or_insert(0);
+            section = format!("{}[{}]", name, index);
+            *index += 1;
+            segments.push(Segment::Text(line.to_string()));
+            continue;
+        }
+        if let Some(name) = trimmed.strip_prefix('[').and_then(|rest| rest.split(']').next()) {
+            section = name.trim().to_string();
+            segments.push(Segment::Text(line.to_string()));
+            continue;
+        }
+
+        let separator = if trimmed.is_empty() || trimmed.starts_with('#') { None } else { find_toml_separator(text) };
+        let Some(separator) = separator else {
+            segments.push(Segment::Text(line.to_string()));
+            continue;
+        };
+
+        let mut value = text[separator + 1..].to_string();
+        let mut ending = ending.to_string();
+        while !toml_value_complete(&value) {
+            let Some(next) = lines.next() else { break };
+            let (next_text, next_ending) = split_ending(next);
+            value.push_str(&ending);
+            value.push_str(next_text);
+            ending = next_ending.to_string();
+        }
+
+        segments.push(Segment::Entry(Entry {
+            key: full_key(&section, text[..separator].trim()),
+            section: section.clone(),
+            prefix: text[..=separator].to_string(),
+            value,
+            ending,
+        }));
+    }
+    segments
+}
+
+/// Finds the `=` between a TOML key and its value, skipping quoted keys.
+fn find_toml_separator(line: &str) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    for (index, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '=') => return Some(index),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Returns false while a TOML value still has open brackets or an unterminated multi-line string.
+fn toml_value_complete(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    let mut depth = 0i32;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'#' => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            quote @ (b'"' | b'\'') => {
+                let triple = bytes[i..].starts_with(&[quote; 3]);
+                let delimiter = if triple { &bytes[i..i + 3] } else { &bytes[i..i + 1] };
+                i += delimiter.len();
+                loop {
+                    if i >= bytes.len() {
+                        return !triple;
+                    }
+                    if quote == b'"' && bytes[i] == b'\\' {
+                        i += 2;
+                        continue;
+                    }
+                    if !triple && bytes[i] == b'\n' {
+                        break;
+                    }
+                    if bytes[i..].starts_with(delimiter) {
+                        i += delimiter.len();
+                        break;
+                    }
+                    i += 1;
+                }
+                continue;
+            }
+            b'[' | b'{' => depth += 1,
+            b']' | b'}' => depth -= 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    depth <= 0
+}
+
+fn entries(segments: &[Segment]) -> impl Iterator<Item = &Entry> {
+    segments.iter().filter_map(|s| match s {
+        Segment::Entry(entry) => Some(entry),
+        Segment::Text(_) => None,
+    })
+}
+
+fn render(segments: &[Segment]) -> String {
+    let mut content = String::new();
+    for (index, segment) in segments.iter().enumerate() {
+        match segment {
+            Segment::Text(text) => content.push_str(text),
+            Segment::Entry(entry) => {
+                content.push_str(&entry.prefix);
+                content.push_str(&entry.value);
+                content.push_str(&entry.ending);
+            }
+        }
+        // Settings moved from the end of a file may lack a line ending
+        if index + 1 < segments.len() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+    }
+    content
+}
+
+/// Merges line based formats, keeping the layout and comments of the incoming file.
+fn merge_lines(format: ConfigFormat, relative_path: &str, base: &str, local: &str, incoming: &str) -> MergeResult {
+    let base_segments = parse_lines(format, base);
+    let local_segments = parse_lines(format, local);
+    let incoming_segments = parse_lines(format, incoming);
+
+    let base_entries: HashMap<&str, &Entry> = entries(&base_segments).map(|e| (e.key.as_str(), e)).collect();
+    let local_entries: Vec<&Entry> = entries(&local_segments).collect();
+    let local_by_key: HashMap<&str, &Entry> = local_entries.iter().map(|e| (e.key.as_str(), *e)).collect();
+    let incoming_keys: HashSet<&str> = entries(&incoming_segments).map(|e| e.key.as_str()).collect();
+
+    let mut conflicts = Vec::new();
+    let mut report = |key: &str, base: Option<&Entry>, local: Option<&Entry>, incoming: Option<&Entry>| {
+        conflicts.push(ConfigConflict {
+            relative_path: relative_path.to_string(),
+            key: key.to_string(),
+            base: base.map(|e| e.value.trim().to_string()),
+            local: local.map(|e| e.value.trim().to_string()),
+            incoming: incoming.map(|e| e.value.trim().to_string()),
+        });
+    };
+
+    let mut merged: Vec<Segment> = Vec::new();
+    for segment in incoming_segments.iter() {
+        let Segment::Entry(entry) = segment else {
+            merged.push(segment.clone());
+            continue;
+        };
+        let base = base_entries.get(entry.key.as_str()).copied();
+        let local = local_by_key.get(entry.key.as_str()).copied();
+        let (side, conflict) = pick(base.map(Entry::normalized), local.map(Entry::normalized), Some(entry.normalized()));
+        if conflict {
+            report(&entry.key, base, local, Some(entry));
+        }
+        match (side, local) {
+            (Side::Incoming, _) => merged.push(segment.clone()),
+            // Keep the incoming line but with the user's value
+            (Side::Local, Some(local)) => merged.push(Segment::Entry(Entry { value: local.value.clone(), ..entry.clone() })),
+            // The user removed the setting
+            (Side::Local, None) => {}
+        }
+    }
+
+    for local in local_entries {
+        if incoming_keys.contains(local.key.as_str()) {
+            continue;
+        }
+        let base = base_entries.get(local.key.as_str()).copied();
+        let (side, conflict) = pick(base.map(Entry::normalized), Some(local.normalized()), None);
+        if conflict {
+            report(&local.key, base, Some(local), None);
+        }
+        if side == Side::Incoming {
+            continue;
+        }
+
+        // A setting only the user has: put it after the last setting of its section
+        let position = merged.iter()
+            .rposition(|s| matches!(s, Segment::Entry(e) if e.section == local.section))
+            .map(|index| index + 1)
+            .or(match format {
+                ConfigFormat::Properties | ConfigFormat::Options => Some(merged.len()),
+                _ if local.section.is_empty() => Some(0),
+                _ => None,
+            });
+        match position {
+            Some(position) => merged.insert(position, Segment::Entry(local.clone())),
+            None => log::warn!(
+                "Dropping {} from {}: its section no longer exists",
+                local.key,
+                relative_path
+            ),
+        }
+    }
+
+    MergeResult { content: render(&merged), conflicts }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merge(relative_path: &str, base: &str, local: &str, incoming: &str) -> MergeResult {
+        let format = ConfigFormat::detect(relative_path).expect("mergeable format");
+        merge_config(format, relative_path, base, local, incoming).expect("merge succeeds")
+    }
+
+    fn conflict_keys(result: &MergeResult) -> Vec<&str> {
+        result.conflicts.iter().map(|c| c.key.as_str()).collect()
+    }
+
+    #[test]
+    fn toml_keeps_local_and_takes_incoming_changes() {
+        let base = "[client]\n# Show the HUD\nshowHud = true\nscale = 1.0\n";
+        let local = "[client]\n# Show the HUD\nshowHud = false\nscale = 1.0\n";
+        let incoming = "[client]\n# Show the HUD at all\nshowHud = true\nscale = 1.5\n";
+
+        let result = merge("config/mod-client.toml", base, local, incoming);
+        assert_eq!(result.content, "[client]\n# Show the HUD at all\nshowHud = false\nscale = 1.5\n");
+        assert!(result.conflicts.is_empty());
+    }
+
+    #[test]
+    fn toml_conflict_takes_incoming() {
+        let base = "[client]\nshowHud = true\nscale = 1.0\n";
+        let local = "[client]\nshowHud = false\nscale = 2.0\n";
+        let incoming = "[client]\nshowHud = true\nscale = 1.5\n";
+
+        let result = merge("config/mod-client.toml", base, local, incoming);
+        assert_eq!(result.content, "[client]\nshowHud = false\nscale = 1.5\n");
+        assert_eq!(conflict_keys(&result), ["client.scale"]);
+        let conflict = &result.conflicts[0];
+        assert_eq!(conflict.relative_path, "config/mod-client.toml");
+        assert_eq!(conflict.base.as_deref(), Some("1.0"));
+        assert_eq!(conflict.local.as_deref(), Some("2.0"));
+        assert_eq!(conflict.incoming.as_deref(), Some("1.5"));
+    }
+
+    #[test]
+    fn toml_multi_line_arrays_merge_as_one_value() {
+        let base = "[mods]\nblocked = [\n    \"a\",\n    \"b\",\n]\nenabled = true\n";
+        let local = "[mods]\nblocked = [\n    \"a\",\n    \"b\",\n    \"c\",\n]\nenabled = true\n";
+        let incoming = "[mods]\nblocked = [\n    \"a\",\n    \"b\",\n]\nenabled = false\n";
+
+        let result = merge("config/mods.toml", base, local, incoming);
+        assert_eq!(
+            result.content,
+            "[mods]\nblocked = [\n    \"a\",\n    \"b\",\n    \"c\",\n]\nenabled = false\n"
+        );
+        assert!(result.conflicts.is_empty());
+    }
+
+    #[test]
+    fn toml_drops_local_settings_of_removed_sections() {
+        let base = "[old]\nx = 1\n[kept]\ny = 1\n";
+        let local = "[old]\nx = 1\nz = 2\n[kept]\ny = 1\n";
+        let incoming = "[kept]\ny = 1\n";
+
+        let result = merge("config/mod.toml", base, local, incoming);
+        assert_eq!(result.content, "[kept]\ny = 1\n");
+        assert!(result.conflicts.is_empty());
+    }
+
+    #[test]
+    fn parse_toml_indexes_array_tables() {
+        let content = "[[servers]]\nname = \"a\"\n\n[[servers]]\nname = \"b\"\n[other]\nname = \"c\"\n";
+        let segments = parse_toml(content);
+        let keys: Vec<&str> = entries(&segments).map(|e| e.key.as_str()).collect();
+        assert_eq!(keys, ["servers[0].name", "servers[1].name", "other.name"]);
+        assert_eq!(render(&segments), content);
+    }
+
+    #[test]
+    fn toml_value_complete_tracks_brackets_and_strings() {
+        assert!(toml_value_complete(" 1"));
+        assert!(toml_value_complete(" [1, 2]"));
+        assert!(!toml_value_complete(" [\n    1,"));
+        assert!(!toml_value_complete(" { a = [1, 2]"));
+        // Brackets inside strings and comments do not count
+        assert!(toml_value_complete(" \"[\""));
+        assert!(toml_value_complete(" 1 # [unclosed"));
+        assert!(!toml_value_complete(" [ # ]"));
+        // Backslashes only escape in basic strings
+        assert!(toml_value_complete(" \"a\\\"]\""));
+        assert!(toml_value_complete(" 'C:\\'"));
+        assert!(!toml_value_complete(" \"\"\"first line"));
+        assert!(toml_value_complete(" \"\"\"first line\nsecond line\"\"\""));
+    }
+
+    #[test]
+    fn forge_cfg_keeps_local_and_takes_incoming_changes() {
+        let base = "general {\n    B:enabled=true\n    I:count=3\n}\n";
+        let local = "general {\n    B:enabled=false\n    I:count=3\n}\n";
+        let incoming = "general {\n    B:enabled=true\n    I:count=5\n}\n";
+
+        let result = merge("config/mod.cfg", base, local, incoming);
+        assert_eq!(result.content, "general {\n    B:enabled=false\n    I:count=5\n}\n");
+        assert!(result.conflicts.is_empty());
+    }
+
+    #[test]
+    fn forge_cfg_conflict_takes_incoming() {
+        let base = "general {\n    B:enabled=true\n    I:count=3\n}\n";
+        let local = "general {\n    B:enabled=false\n    I:count=4\n}\n";
+        let incoming = "general {\n    B:enabled=true\n    I:count=5\n}\n";
+
+        let result = merge("config/mod.cfg", base, local, incoming);
+        assert_eq!(result.content, "general {\n    B:enabled=false\n    I:count=5\n}\n");
+        assert_eq!(conflict_keys(&result), ["general.I:count"]);
+    }
+
+    #[test]
+    fn forge_cfg_lists_merge_as_one_value() {
+        let base = "general {\n    S:items <\n        minecraft:stone\n     >\n    B:enabled=true\n}\n";
+        let local = "general {\n    S:items <\n        minecraft:stone\n        minecraft:dirt\n     >\n    B:enabled=true\n}\n";
+        let incoming = "general {\n    S:items <\n        minecraft:stone\n     >\n    B:enabled=false\n}\n";
+
+        let result = merge("config/mod.cfg", base, local, incoming);
+        assert_eq!(
+            result.content,
+            "general {\n    S:items <\n        minecraft:stone\n        minecraft:dirt\n     >\n    B:enabled=false\n}\n"
+        );
+        assert!(result.conflicts.is_empty());
+
+        let segments = parse_forge_cfg(local);
+        let keys: Vec<&str> = entries(&segments).map(|e| e.key.as_str()).collect();
+        assert_eq!(keys, ["general.S:items", "general.B:enabled"]);
+    }
+
+    #[test]
+    fn properties_keeps_local_and_takes_incoming_changes() {
+        let base = "# Server settings\nmotd=Hello\nmax-players=20\n";
+        let local = "# Server settings\nmotd=Welcome\nmax-players=20\n";
+        let incoming = "# Server settings\nmotd=Hello\nmax-players=40\n";
+
+        let result = merge("server.properties", base, local, incoming);
+        assert_eq!(result.content, "# Server settings\nmotd=Welcome\nmax-players=40\n");
+        assert!(result.conflicts.is_empty());
+    }
+
+    #[test]
+    fn properties_conflict_takes_incoming() {
+        let base = "motd=Hello\nmax-players=20\n";
+        let local = "motd=Welcome\nmax-players=20\n";
+        let incoming = "motd=Hi\nmax-players=20\n";
+
+        let result = merge("server.properties", base, local, incoming);
+        assert_eq!(result.content, "motd=Hi\nmax-players=20\n");
+        assert_eq!(conflict_keys(&result), ["motd"]);
+    }
+
+    #[test]
+    fn json5_keeps_local_and_takes_incoming_changes() {
+        let base = "{\n  // HUD settings\n  hud: { show: true, scale: 1.0 },\n}\n";
+        let local = "{\n  // HUD settings\n  hud: { show: false, scale: 1.0 },\n}\n";
+        let incoming = "{\n  // HUD settings\n  hud: { show: true, scale: 1.5 },\n}\n";
+
+        let result = merge("config/mod.json5", base, local, incoming);
+        let merged: Value = serde_json::from_str(&result.content).unwrap();
+        assert_eq!(merged, serde_json::json!({ "hud": { "show": false, "scale": 1.5 } }));
+        assert!(result.content.ends_with('\n'));
+        assert!(result.conflicts.is_empty());
+    }
+
+    #[test]
+    fn json5_conflict_takes_incoming() {
+        let base = "{ hud: { show: true, scale: 1 } }";
+        let local = "{ hud: { show: false, scale: 2 } }";
+        let incoming = "{ hud: { show: true, scale: 3 } }";
+
+        let result = merge("config/mod.json5", base, local, incoming);
+        let merged: Value = serde_json::from_str(&result.content).unwrap();
+        assert_eq!(merged, serde_json::json!({ "hud": { "show": false, "scale": 3 } }));
+        assert_eq!(conflict_keys(&result), ["hud.scale"]);
+        let conflict = &result.conflicts[0];
+        assert_eq!(conflict.base.as_deref(), Some("1"));
+        assert_eq!(conflict.local.as_deref(), Some("2"));
+        assert_eq!(conflict.incoming.as_deref(), Some("3"));
+    }
+}
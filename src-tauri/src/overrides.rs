@@ -0,0 +1,147 @@
+use crate::composables::manifest::{AddonEnvironment, OverrideFile};
+use crate::error::CemmError;
+use crate::integrity::sha256_hex;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tauri::command;
+
+/// Folder of an update in the GitHub repository that holds its override files
+pub const OVERRIDES_DIR: &str = "overrides";
+
+/// Category of override files in install plans and verify reports
+pub const OVERRIDES_CATEGORY: &str = "overrides";
+
+/// An override file together with its content.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/overrides.rs (this file)
+/// - TypeScript: app/types/index.ts (OverrideFileWithContent interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverrideFileWithContent {
+    #[serde(flatten)]
+    pub file: OverrideFile,
+    /// Base64 of the file's bytes
+    pub content: String,
+}
+
+impl OverrideFileWithContent {
+    pub fn from_bytes(relative_path: String, bytes: &[u8], environment: Option<AddonEnvironment>) -> Self {
+        Self {
+            file: OverrideFile {
+                relative_path,
+                size: bytes.len() as u64,
+                sha256: sha256_hex(bytes),
+                environment,
+                url: None,
+            },
+            content: STANDARD.encode(bytes),
+        }
+    }
+
+    /// Decodes the content and checks it against the recorded size and SHA-256.
+    pub fn decode(&self) -> Result<Vec<u8>, CemmError> {
+        let bytes = STANDARD.decode(&self.content).map_err(|e| CemmError::parse(
+            format!("Failed to decode override file {}: {}", self.file.relative_path, e),
+            &self.file.relative_path,
+        ))?;
+        verify_override_bytes(&self.file, &bytes)?;
+        Ok(bytes)
+    }
+}
+
+/// Checks `bytes` against the size and SHA-256 recorded for an override file.
+pub fn verify_override_bytes(file: &OverrideFile, bytes: &[u8]) -> Result<(), CemmError> {
+    if bytes.len() as u64 != file.size {
+        return Err(CemmError::validation(format!(
+            "Size mismatch for {}: expected {} bytes, got {}",
+            file.relative_path,
+            file.size,
+            bytes.len()
+        )));
+    }
+    let actual = sha256_hex(bytes);
+    if !actual.eq_ignore_ascii_case(&file.sha256) {
+        return Err(CemmError::validation(format!(
+            "SHA-256 mismatch for {}: expected {}, got {}",
+            file.relative_path, file.sha256, actual
+        )));
+    }
+    Ok(())
+}
+
+/// Normalizes the path of an override file to forward slashes and rejects paths
+/// that leave the instance or point into CEMM's own `.cemm` directory.
+pub fn normalize_override_path(relative_path: &str) -> Result<String, CemmError> {
+    let normalized = relative_path.replace('\\', "/");
+    let is_absolute = normalized.starts_with('/') || normalized.as_bytes().get(1) == Some(&b':');
+    if normalized.is_empty() || is_absolute || normalized.split('/').any(|c| c == "..") {
+        return Err(CemmError::path_traversal(
+            format!("Invalid override path '{}': it must stay inside the instance", relative_path),
+            relative_path,
+        ));
+    }
+    if normalized.split('/').next() == Some(".cemm") {
+        return Err(CemmError::path_traversal(
+            format!("Invalid override path '{}': it belongs to CEMM", relative_path),
+            relative_path,
+        ));
+    }
+    Ok(normalized)
+}
+
+/// Reads every file below `dir_path` as an override file, whatever its type.
+///
+/// Paths are relative to `dir_path`, which mirrors the instance root: a file at
+/// `<dir_path>/kubejs/assets/logo.png` installs to `kubejs/assets/logo.png`.
+#[command]
+pub async fn read_overrides_directory(dir_path: String) -> Result<Vec<OverrideFileWithContent>, CemmError> {
+    let files = tokio::task::spawn_blocking(move || {
+        let base = Path::new(&dir_path);
+        let mut files = Vec::new();
+        collect_files(base, base, &mut files)?;
+        Ok::<_, CemmError>(files)
+    })
+    .await
+    .map_err(|e| CemmError::validation(format!("Reading the overrides directory failed: {}", e)))??;
+
+    log::info!("read_overrides_directory: {} files", files.len());
+    Ok(files)
+}
+
+fn collect_files(dir: &Path, base: &Path, files: &mut Vec<OverrideFileWithContent>) -> Result<(), CemmError> {
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .map_err(|e| CemmError::io("Failed to read directory", dir, e))?
+        .collect::<Result<_, _>>()
+        .map_err(|e| CemmError::io("Failed to read directory", dir, e))?;
+    entries.sort_by_key(|e| e.file_name());
+
+    for entry in entries {
+        let path = entry.path();
+        let file_type = entry.file_type().map_err(|e| CemmError::io("Failed to read", &path, e))?;
+        if file_type.is_dir() {
+            // An instance picked as the source still carries CEMM's own bookkeeping
+            if entry.file_name() != ".cemm" {
+                collect_files(&path, base, files)?;
+            }
+            continue;
+        }
+        if !file_type.is_file() {
+            continue;
+        }
+        let relative_path = path.strip_prefix(base)
+            .map_err(|_| CemmError::path_traversal(
+                format!("Failed to make path relative: {}", path.display()),
+                path.display().to_string(),
+            ))?
+            .to_string_lossy()
+            .replace('\\', "/");
+        let bytes = fs::read(&path).map_err(|e| CemmError::io("Failed to read", &path, e))?;
+        files.push(OverrideFileWithContent::from_bytes(relative_path, &bytes, None));
+    }
+    Ok(())
+}
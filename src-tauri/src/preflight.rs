@@ -0,0 +1,244 @@
+use crate::composables::manifest::Manifest;
+use crate::error::CemmError;
+use crate::installer::{plan_install, ConfigFile, InstallOptions, InstallPlan, PlanAction};
+use crate::overrides::OverrideFileWithContent;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs::{self, OpenOptions};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use tauri::command;
+use uuid::Uuid;
+
+/// Prefix of the empty files written to test whether a directory accepts new files
+const PROBE_FILE_PREFIX: &str = ".cemm-preflight-";
+
+/// Kind of problem found by the preflight checks.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PreflightIssueKind {
+    /// The instance's volume has less free space than the install will write
+    InsufficientSpace,
+    /// A directory or file the install changes can't be written
+    NotWritable,
+    /// A file the install replaces or deletes is held open by another program
+    FileLocked,
+}
+
+/// A single problem that would make install_update fail halfway through.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/preflight.rs (this file)
+/// - TypeScript: app/types/index.ts (PreflightIssue interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreflightIssue {
+    pub kind: PreflightIssueKind,
+    /// Path relative to the modpack directory, using forward slashes. None for the whole volume.
+    pub path: Option<String>,
+    pub message: String,
+}
+
+/// Result of checking an install plan against the instance before anything is changed.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/preflight.rs (this file)
+/// - TypeScript: app/types/index.ts (PreflightReport interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PreflightReport {
+    /// Bytes the install will write, see `InstallPlan::total_download_bytes`
+    pub required_bytes: u64,
+    /// Free space on the instance's volume, None if it could not be determined
+    pub available_bytes: Option<u64>,
+    /// Downloads whose size is not known and are not part of `required_bytes`
+    pub unknown_size_downloads: usize,
+    pub issues: Vec<PreflightIssue>,
+}
+
+impl PreflightReport {
+    pub fn passed(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// All issues as one message, for errors that can only carry a string.
+    pub fn summary(&self) -> String {
+        let messages: Vec<&str> = self.issues.iter().map(|i| i.message.as_str()).collect();
+        format!("Preflight checks failed: {}. Nothing was installed.", messages.join("; "))
+    }
+}
+
+/// Checks that `plan` can be carried out on the instance at `modpack_path`.
+///
+/// Free space on the instance's volume must cover every file the plan writes; staged
+/// files of transactional installs live on the same volume. Every directory a file is
+/// written to or removed from must accept new files, and every file that is replaced,
+/// deleted or renamed must be openable for writing. On Windows the file is opened
+/// without sharing, which fails while a running game holds the jar open.
+///
+/// Nothing is changed; the files written to test directories are removed right away.
+pub async fn check_plan(modpack_path: &Path, plan: &InstallPlan) -> Result<PreflightReport, CemmError> {
+    let modpack_path = modpack_path.to_path_buf();
+    let plan = plan.clone();
+    tokio::task::spawn_blocking(move || run_checks(&modpack_path, &plan))
+        .await
+        .map_err(|e| CemmError::validation(format!("Preflight checks failed to run: {}", e)))
+}
+
+fn run_checks(modpack_path: &Path, plan: &InstallPlan) -> PreflightReport {
+    let mut report = PreflightReport {
+        required_bytes: plan.total_download_bytes,
+        unknown_size_downloads: plan.unknown_size_downloads,
+        ..Default::default()
+    };
+
+    // Directories that receive or lose files; the instance root holds the staging
+    // directory and the install receipt
+    let mut directories: BTreeSet<String> = BTreeSet::new();
+    directories.insert(String::new());
+    // Existing files that are replaced, deleted or renamed away
+    let mut replaced: BTreeSet<String> = BTreeSet::new();
+    for file in &plan.files {
+        match file.action {
+            PlanAction::Skip => continue,
+            PlanAction::Download => {}
+            PlanAction::Overwrite | PlanAction::Delete => {
+                replaced.insert(file.relative_path.clone());
+            }
+            PlanAction::Rename => {
+                if let Some(from) = &file.renamed_from {
+                    replaced.insert(from.clone());
+                }
+            }
+        }
+        directories.insert(parent_of(&file.relative_path).to_string());
+    }
+
+    match fs4::available_space(modpack_path) {
+        Ok(available) => {
+            report.available_bytes = Some(available);
+            if available < report.required_bytes {
+                report.issues.push(PreflightIssue {
+                    kind: PreflightIssueKind::InsufficientSpace,
+                    path: None,
+                    message: format!(
+                        "Not enough disk space: the update needs {} but only {} are free",
+                        format_bytes(report.required_bytes),
+                        format_bytes(available)
+                    ),
+                });
+            }
+        }
+        Err(e) => log::warn!("Could not determine free space for {}: {}", modpack_path.display(), e),
+    }
+
+    // Missing directories are created by the install, so their closest existing ancestor is tested
+    let probed: BTreeSet<PathBuf> = directories.iter()
+        .filter_map(|dir| existing_ancestor(&modpack_path.join(dir)))
+        .collect();
+    for dir in probed {
+        if let Err(e) = probe_directory(&dir) {
+            report.issues.push(PreflightIssue {
+                kind: PreflightIssueKind::NotWritable,
+                path: Some(relative_to(modpack_path, &dir)),
+                message: format!("Cannot write to {}: {}", dir.display(), e),
+            });
+        }
+    }
+
+    for relative_path in replaced {
+        let path = modpack_path.join(&relative_path);
+        if let Err(e) = open_exclusive(&path) {
+            if e.kind() == ErrorKind::NotFound {
+                continue;
+            }
+            let issue = if is_sharing_violation(&e) {
+                PreflightIssue {
+                    kind: PreflightIssueKind::FileLocked,
+                    message: format!("{} is in use by another program. Close Minecraft and try again", relative_path),
+                    path: Some(relative_path),
+                }
+            } else {
+                PreflightIssue {
+                    kind: PreflightIssueKind::NotWritable,
+                    message: format!("Cannot modify {}: {}", relative_path, e),
+                    path: Some(relative_path),
+                }
+            };
+            report.issues.push(issue);
+        }
+    }
+
+    report
+}
+
+/// Checks whether a directory accepts new files by creating and removing an empty one.
+fn probe_directory(dir: &Path) -> std::io::Result<()> {
+    let probe = dir.join(format!("{}{}", PROBE_FILE_PREFIX, Uuid::new_v4().simple()));
+    OpenOptions::new().write(true).create_new(true).open(&probe)?;
+    fs::remove_file(&probe)
+}
+
+/// Opens an existing file for writing without changing it. On Windows no other handle
+/// may be open at the same time.
+fn open_exclusive(path: &Path) -> std::io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true);
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::OpenOptionsExt;
+        options.share_mode(0);
+    }
+    options.open(path).map(|_| ())
+}
+
+/// ERROR_SHARING_VIOLATION or ERROR_LOCK_VIOLATION: another process has the file open
+fn is_sharing_violation(e: &std::io::Error) -> bool {
+    cfg!(windows) && matches!(e.raw_os_error(), Some(32) | Some(33))
+}
+
+fn existing_ancestor(path: &Path) -> Option<PathBuf> {
+    path.ancestors().find(|p| p.is_dir()).map(Path::to_path_buf)
+}
+
+fn parent_of(relative_path: &str) -> &str {
+    relative_path.rsplit_once('/').map(|(parent, _)| parent).unwrap_or("")
+}
+
+fn relative_to(base: &Path, path: &Path) -> String {
+    path.strip_prefix(base)
+        .map(|p| p.to_string_lossy().replace('\\', "/"))
+        .unwrap_or_else(|_| path.to_string_lossy().to_string())
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const MIB: f64 = 1024.0 * 1024.0;
+    if bytes as f64 >= 1024.0 * MIB {
+        format!("{:.1} GiB", bytes as f64 / (1024.0 * MIB))
+    } else {
+        format!("{:.1} MiB", bytes as f64 / MIB)
+    }
+}
+
+/// Runs the preflight checks for what install_update would do with the same inputs,
+/// so every problem can be shown before anything is changed.
+#[command]
+pub async fn preflight_install(
+    modpack_path: String,
+    manifest: Manifest,
+    config_files: Vec<ConfigFile>,
+    overrides: Option<Vec<OverrideFileWithContent>>,
+    options: Option<InstallOptions>,
+) -> Result<PreflightReport, CemmError> {
+    let plan = plan_install(modpack_path.clone(), manifest, config_files, overrides, options).await?;
+    let report = check_plan(Path::new(&modpack_path), &plan).await?;
+    log::info!(
+        "preflight_install: {} issues, {} bytes needed, {:?} bytes free",
+        report.issues.len(),
+        report.required_bytes,
+        report.available_bytes
+    );
+    Ok(report)
+}
@@ -0,0 +1,152 @@
+use crate::composables::manifest::Manifest;
+use crate::error::CemmError;
+use crate::integrity::{sha256_file, sha256_hex};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::command;
+use tokio::fs as async_fs;
+
+/// Location of the receipt inside the modpack directory
+pub const RECEIPT_PATH: &str = ".cemm/installed.json";
+
+/// What a receipt entry was installed as.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiptFileKind {
+    Addon,
+    Config,
+    Override,
+}
+
+/// A file CEMM put into the instance.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/receipt.rs (this file)
+/// - TypeScript: app/types/index.ts (ReceiptFile interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiptFile {
+    /// Path relative to the modpack directory, with forward slashes
+    pub relative_path: String,
+    pub kind: ReceiptFileKind,
+    pub sha256: String,
+    pub size: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub addon_project_id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub addon_file_id: Option<u64>,
+}
+
+/// Record of the last update applied to an instance, stored in `.cemm/installed.json`.
+///
+/// Later installs diff against `manifest` and only ever remove files listed in
+/// `files` whose content is unchanged, instead of guessing from file names.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/receipt.rs (this file)
+/// - TypeScript: app/types/index.ts (InstallReceipt interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallReceipt {
+    /// Id of the applied update, if the caller provided one
+    #[serde(default)]
+    pub update_id: Option<String>,
+    /// SHA-256 of the applied manifest
+    pub manifest_hash: String,
+    /// RFC 3339 timestamp
+    pub installed_at: String,
+    /// The manifest the instance now corresponds to
+    pub manifest: Manifest,
+    pub files: Vec<ReceiptFile>,
+}
+
+impl InstallReceipt {
+    pub fn new(update_id: Option<String>, manifest_hash: String, manifest: Manifest, files: Vec<ReceiptFile>) -> Self {
+        Self {
+            update_id,
+            manifest_hash,
+            installed_at: Utc::now().to_rfc3339(),
+            manifest,
+            files,
+        }
+    }
+
+    /// Returns where the receipt of the instance at `modpack_path` is stored.
+    pub fn path(modpack_path: &Path) -> PathBuf {
+        modpack_path.join(RECEIPT_PATH)
+    }
+
+    /// Reads the receipt of an instance. Returns `None` if nothing was installed by CEMM yet.
+    pub async fn load(modpack_path: &Path) -> Result<Option<Self>, CemmError> {
+        let path = Self::path(modpack_path);
+        let json = match async_fs::read_to_string(&path).await {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(CemmError::io("Failed to read install receipt", &path, e)),
+        };
+        serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|e| CemmError::parse(format!("Invalid install receipt {}: {}", path.display(), e), path.display().to_string()))
+    }
+
+    /// Writes the receipt to `path`, replacing any existing file atomically.
+    pub async fn save(&self, path: &Path) -> Result<(), CemmError> {
+        if let Some(parent) = path.parent() {
+            async_fs::create_dir_all(parent).await
+                .map_err(|e| CemmError::io("Failed to create directory", parent, e))?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| CemmError::validation(format!("Failed to serialize install receipt: {}", e)))?;
+        let temp = path.with_extension("json.tmp");
+        async_fs::write(&temp, json).await
+            .map_err(|e| CemmError::io("Failed to write install receipt", &temp, e))?;
+        async_fs::rename(&temp, path).await
+            .map_err(|e| CemmError::io(&format!("Failed to move {} to", temp.display()), path, e))
+    }
+
+    pub fn file(&self, relative_path: &str) -> Option<&ReceiptFile> {
+        self.files.iter().find(|f| f.relative_path == relative_path)
+    }
+}
+
+impl ReceiptFile {
+    /// Hashes the file at `path` and records it under `relative_path`.
+    pub async fn from_path(relative_path: String, kind: ReceiptFileKind, path: &Path) -> Result<Self, CemmError> {
+        let size = async_fs::metadata(path).await
+            .map_err(|e| CemmError::io("Failed to read metadata of", path, e))?
+            .len();
+        Ok(Self {
+            relative_path,
+            kind,
+            sha256: sha256_file(path).await?,
+            size,
+            addon_project_id: None,
+            addon_file_id: None,
+        })
+    }
+
+    /// Returns true if the file at `path` still has the recorded content.
+    pub async fn matches(&self, path: &Path) -> bool {
+        match async_fs::metadata(path).await {
+            Ok(metadata) if metadata.len() == self.size => {}
+            _ => return false,
+        }
+        sha256_file(path).await.is_ok_and(|hash| hash == self.sha256)
+    }
+}
+
+/// Returns the SHA-256 of the manifest's JSON form.
+pub fn manifest_hash(manifest: &Manifest) -> Result<String, CemmError> {
+    let json = serde_json::to_vec(manifest)
+        .map_err(|e| CemmError::validation(format!("Failed to serialize manifest: {}", e)))?;
+    Ok(sha256_hex(&json))
+}
+
+/// Returns the install receipt of an instance, or `None` if CEMM has not installed an update there yet.
+#[command]
+pub async fn get_install_receipt(modpack_path: String) -> Result<Option<InstallReceipt>, CemmError> {
+    InstallReceipt::load(Path::new(&modpack_path)).await
+}
@@ -0,0 +1,112 @@
+use reqwest::header::RETRY_AFTER;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::time::Duration;
+
+/// How often and how patiently failed HTTP requests are retried.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/retry.rs (this file)
+/// - TypeScript: app/types/index.ts (RetryPolicy interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Number of retries after the first attempt (0 disables retrying)
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for every further retry
+    #[serde(default = "default_initial_delay_ms")]
+    pub initial_delay_ms: u64,
+    /// Upper bound for a single delay, including server-requested Retry-After waits
+    #[serde(default = "default_max_delay_ms")]
+    pub max_delay_ms: u64,
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_initial_delay_ms() -> u64 {
+    500
+}
+
+fn default_max_delay_ms() -> u64 {
+    10_000
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: default_max_retries(),
+            initial_delay_ms: default_initial_delay_ms(),
+            max_delay_ms: default_max_delay_ms(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff with full jitter: a random delay between 0 and
+    /// `initial_delay * 2^retry`, capped at `max_delay`.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let ceiling = self.initial_delay_ms
+            .saturating_mul(1u64 << retry.min(16))
+            .min(self.max_delay_ms);
+        // Saturating so a ceiling of u64::MAX cannot wrap the range to 0; a ceiling of 0 gives no delay
+        let jitter = RandomState::new().hash_one(retry) % ceiling.saturating_add(1);
+        Duration::from_millis(jitter)
+    }
+
+    fn retry_after(&self, resp: &Response) -> Option<Duration> {
+        let seconds: u64 = resp.headers().get(RETRY_AFTER)?.to_str().ok()?.trim().parse().ok()?;
+        Some(Duration::from_millis(seconds.saturating_mul(1000).min(self.max_delay_ms)))
+    }
+}
+
+/// Returns true for responses worth retrying: server errors, timeouts and rate limits.
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+/// Returns true for transport errors that are likely to go away on their own.
+pub fn is_retryable_error(error: &reqwest::Error) -> bool {
+    // is_request() also covers requests that can never succeed, such as an invalid URL
+    error.is_connect() || error.is_timeout() || error.is_body()
+}
+
+/// Sends the request built by `make_request`, retrying transient failures.
+///
+/// Connection errors and retryable statuses are retried according to `policy`.
+/// When retries run out, the last error or response is returned unchanged so the
+/// caller's own status handling still applies.
+pub async fn send_with_retry<F>(policy: &RetryPolicy, mut make_request: F) -> Result<Response, reqwest::Error>
+where
+    F: FnMut() -> RequestBuilder,
+{
+    let mut retry = 0u32;
+    loop {
+        let result = make_request().send().await;
+        let delay = match &result {
+            Ok(resp) if is_retryable_status(resp.status()) && retry < policy.max_retries => {
+                let delay = policy.retry_after(resp).unwrap_or_else(|| policy.backoff(retry));
+                log::warn!(
+                    "{} returned HTTP {}, retrying in {:?} ({}/{})",
+                    resp.url(), resp.status(), delay, retry + 1, policy.max_retries
+                );
+                delay
+            }
+            Err(e) if is_retryable_error(e) && retry < policy.max_retries => {
+                let delay = policy.backoff(retry);
+                log::warn!("Request failed: {}, retrying in {:?} ({}/{})", e, delay, retry + 1, policy.max_retries);
+                delay
+            }
+            _ => return result,
+        };
+        tokio::time::sleep(delay).await;
+        retry += 1;
+    }
+}
@@ -0,0 +1,459 @@
+use crate::cache::DownloadCache;
+use crate::cancel::{CancellationToken, OperationKind, Operations};
+use crate::composables::manifest::Manifest;
+use crate::environment::{addon_runs_on, config_environment, override_runs_on, InstallSide};
+use crate::error::CemmError;
+use crate::installer::{
+    addon_categories, decode_config_content, emit_progress, run_downloads, validate_path_within_base, write_file,
+    ConfigFile, DownloadContext, DownloadJob, InstallOptions,
+};
+use crate::overrides::OverrideFileWithContent;
+use crate::retry::RetryPolicy;
+use crate::transaction::collect_files;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::{command, Manager, Window};
+use tokio::fs as async_fs;
+use zip::write::SimpleFileOptions;
+
+/// Addon folders a dedicated server has no use for
+const CLIENT_ONLY_FOLDERS: [&str; 2] = ["resourcepacks", "shaderpacks"];
+
+/// Fabric installer version whose server launcher the start script fetches
+const FABRIC_INSTALLER_VERSION: &str = "1.0.1";
+
+/// Options for export_server_pack. Converted to snake_case in useTauri.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/server_pack.rs (this file)
+/// - TypeScript: app/types/index.ts (ServerPackOptions interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ServerPackOptions {
+    /// Directory to create, or the zip file to write when `zip` is set
+    pub output_path: String,
+    /// Write a zip archive instead of a directory (default: false)
+    #[serde(default)]
+    pub zip: bool,
+    /// Maximum number of addon downloads running in parallel (default: 6, max: 32)
+    #[serde(default)]
+    pub max_concurrent_downloads: Option<usize>,
+    /// Retry policy for downloads (default: 3 retries with exponential backoff)
+    #[serde(default)]
+    pub retry: Option<RetryPolicy>,
+    /// Serve addons from the shared download cache (default: true)
+    #[serde(default)]
+    pub use_cache: Option<bool>,
+}
+
+/// Result of export_server_pack.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/server_pack.rs (this file)
+/// - TypeScript: app/types/index.ts (ServerPackResult interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerPackResult {
+    pub output_path: String,
+    pub addon_count: usize,
+    pub config_count: usize,
+    pub override_count: usize,
+    /// Addons left out because they only run on clients
+    pub skipped_addons: Vec<String>,
+}
+
+/// Builds a dedicated server from `manifest`, `config_files` and `overrides`.
+///
+/// Only enabled addons that run on servers are included; resource packs, shader packs
+/// and client-only config and override files are left out. A `start.sh` and `start.bat` for the
+/// manifest's mod loader install the loader on first start and then launch the server.
+/// The EULA is not accepted on the user's behalf.
+///
+/// The output directory must not exist yet or be empty, and a zip must not exist yet.
+/// A failed or cancelled export removes what it wrote.
+/// Progress is reported with `install-progress` events, and the export can be stopped
+/// with cancel_install.
+#[command]
+pub async fn export_server_pack(
+    window: Window,
+    manifest: Manifest,
+    config_files: Vec<ConfigFile>,
+    overrides: Option<Vec<OverrideFileWithContent>>,
+    options: ServerPackOptions,
+) -> Result<ServerPackResult, CemmError> {
+    let overrides = overrides.unwrap_or_default();
+    let operations = window.state::<Operations>();
+    let operation = operations.start(OperationKind::Install)?;
+    let cancel = operation.token();
+
+    let output_path = PathBuf::from(&options.output_path);
+    if options.zip && async_fs::try_exists(&output_path).await.unwrap_or(false) {
+        return Err(CemmError::validation(format!("{} already exists", output_path.display())));
+    }
+    // Zips are assembled in a directory next to the archive first
+    let build_dir = if options.zip {
+        let mut name = output_path.as_os_str().to_os_string();
+        name.push(".cemm-build");
+        PathBuf::from(name)
+    } else {
+        output_path.clone()
+    };
+    let existed = async_fs::try_exists(&build_dir).await.unwrap_or(false);
+    ensure_empty_dir(&build_dir).await?;
+
+    let result = build_server_pack(
+        &window,
+        &build_dir,
+        &manifest,
+        &config_files,
+        &overrides,
+        &options,
+        cancel.clone(),
+    ).await;
+    let result = match result {
+        Ok(result) if options.zip => {
+            emit_progress(&window, 1, 1, "Writing zip...");
+            let zipped = write_zip(&build_dir, &output_path).await;
+            let _ = async_fs::remove_dir_all(&build_dir).await;
+            zipped.map(|_| result)
+        }
+        Ok(result) => Ok(result),
+        Err(e) => {
+            // Everything in the directory was written by this export, since it had to be empty
+            let _ = async_fs::remove_dir_all(&build_dir).await;
+            if existed && !options.zip {
+                let _ = async_fs::create_dir(&build_dir).await;
+            }
+            Err(e)
+        }
+    }?;
+
+    emit_progress(&window, 1, 1, "Server pack exported!");
+    log::info!(
+        "export_server_pack: {} addons, {} configs, {} overrides written to {} ({} client-only addons skipped)",
+        result.addon_count,
+        result.config_count,
+        result.override_count,
+        output_path.display(),
+        result.skipped_addons.len()
+    );
+    Ok(ServerPackResult { output_path: options.output_path, ..result })
+}
+
+async fn build_server_pack(
+    window: &Window,
+    dir: &Path,
+    manifest: &Manifest,
+    config_files: &[ConfigFile],
+    overrides: &[OverrideFileWithContent],
+    options: &ServerPackOptions,
+    cancel: CancellationToken,
+) -> Result<ServerPackResult, CemmError> {
+    let mut jobs: Vec<DownloadJob> = Vec::new();
+    let mut skipped_addons: Vec<String> = Vec::new();
+
+    for category in addon_categories(manifest) {
+        if CLIENT_ONLY_FOLDERS.contains(&category.folder) {
+            continue;
+        }
+        for addon in category.addons.iter().filter(|a| a.disabled != Some(true)) {
+            if !addon_runs_on(addon, InstallSide::Server) {
+                skipped_addons.push(addon.addon_name.clone());
+                continue;
+            }
+            let folder = category.folder_for(dir, addon)?;
+            let dest = dir.join(folder).join(&addon.file_name_on_disk);
+            if let Some(parent) = dest.parent() {
+                async_fs::create_dir_all(parent).await
+                    .map_err(|e| CemmError::io("Failed to create directory", parent, e))?;
+            }
+            jobs.push(DownloadJob {
+                addon: addon.clone(),
+                dest,
+                label: category.label,
+            });
+        }
+    }
+
+    let configs: Vec<&ConfigFile> = config_files.iter()
+        .filter(|c| config_environment(&c.relative_path, manifest).runs_on(InstallSide::Server))
+        .collect();
+    let server_overrides: Vec<&OverrideFileWithContent> = overrides.iter()
+        .filter(|o| override_runs_on(&o.file, InstallSide::Server))
+        .collect();
+    let addon_count = jobs.len();
+    let total = addon_count + configs.len() + server_overrides.len() + 1;
+    let mut current = 0usize;
+
+    let install_options = InstallOptions {
+        max_concurrent_downloads: options.max_concurrent_downloads,
+        retry: options.retry.clone(),
+        use_cache: options.use_cache,
+        ..Default::default()
+    };
+    let cache = if install_options.uses_cache() {
+        match DownloadCache::for_app(window.app_handle()) {
+            Ok(cache) => Some(cache),
+            Err(e) => {
+                log::warn!("Download cache unavailable: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let ctx = DownloadContext {
+        client: Client::new(),
+        policy: install_options.retry_policy(),
+        cache,
+        cancel: cancel.clone(),
+    };
+    run_downloads(window, &ctx, jobs, install_options.download_concurrency(), &mut current, total).await?;
+
+    for config in &configs {
+        cancel.check()?;
+        let dest = validate_path_within_base(dir, &config.relative_path)?;
+        if let Some(parent) = dest.parent() {
+            async_fs::create_dir_all(parent).await
+                .map_err(|e| CemmError::io("Failed to create directory", parent, e))?;
+        }
+        async_fs::write(&dest, decode_config_content(config)?).await
+            .map_err(|e| CemmError::io("Failed to write", &dest, e))?;
+        current += 1;
+        emit_progress(window, current, total, &format!("Added config: {}", config.relative_path));
+    }
+
+    for content in &server_overrides {
+        cancel.check()?;
+        let dest = validate_path_within_base(dir, &content.file.relative_path)?;
+        write_file(&dest, &content.decode()?).await?;
+        current += 1;
+        emit_progress(window, current, total, &format!("Added override: {}", content.file.relative_path));
+    }
+
+    write_start_scripts(dir, manifest).await?;
+    emit_progress(window, total, total, "Added start scripts");
+
+    Ok(ServerPackResult {
+        output_path: dir.to_string_lossy().to_string(),
+        addon_count,
+        config_count: configs.len(),
+        override_count: server_overrides.len(),
+        skipped_addons,
+    })
+}
+
+/// Creates `dir`, refusing to write into a directory that already has files in it.
+async fn ensure_empty_dir(dir: &Path) -> Result<(), CemmError> {
+    if let Ok(mut entries) = async_fs::read_dir(dir).await {
+        let first = entries.next_entry().await.map_err(|e| CemmError::io("Failed to read directory", dir, e))?;
+        if first.is_some() {
+            return Err(CemmError::validation(format!("{} is not empty", dir.display())));
+        }
+    }
+    async_fs::create_dir_all(dir).await
+        .map_err(|e| CemmError::io("Failed to create directory", dir, e))
+}
+
+/// Mod loader named by CurseForge's `baseModLoader`, e.g. "forge-47.2.0" or "fabric-0.15.11-1.20.1".
+enum Loader {
+    Forge(String),
+    NeoForge(String),
+    Fabric(String),
+    Unknown,
+}
+
+impl Loader {
+    fn parse(name: Option<&str>) -> Self {
+        let Some((family, rest)) = name.and_then(|n| n.split_once('-')) else {
+            return Loader::Unknown;
+        };
+        let version = rest.split('-').next().unwrap_or(rest).to_string();
+        match family.to_lowercase().as_str() {
+            "forge" => Loader::Forge(version),
+            "neoforge" => Loader::NeoForge(version),
+            "fabric" => Loader::Fabric(version),
+            _ => Loader::Unknown,
+        }
+    }
+}
+
+fn forge_installer_url(game_version: &str, version: &str) -> String {
+    format!(
+        "https://maven.minecraftforge.net/net/minecraftforge/forge/{mc}-{v}/forge-{mc}-{v}-installer.jar",
+        mc = game_version,
+        v = version
+    )
+}
+
+/// Whether the Forge server installer for `game_version` creates `run.sh` and `run.bat`,
+/// which it does since Minecraft 1.17. Unparsable versions are assumed to be recent.
+fn forge_has_run_scripts(game_version: &str) -> bool {
+    let mut parts = game_version.split('.').map(|part| part.parse::<u32>().ok());
+    match (parts.next().flatten(), parts.next().flatten()) {
+        (Some(major), Some(minor)) => major > 1 || minor >= 17,
+        _ => true,
+    }
+}
+
+/// Writes `start.sh` and `start.bat`, which install the mod loader on first start.
+async fn write_start_scripts(dir: &Path, manifest: &Manifest) -> Result<(), CemmError> {
+    let game_version = manifest.game_version.as_deref().unwrap_or("");
+    let loader = Loader::parse(manifest.base_mod_loader.as_deref());
+    if matches!(loader, Loader::Unknown) || game_version.is_empty() {
+        log::warn!("export_server_pack: unknown Minecraft version or mod loader, the start scripts need editing");
+    }
+
+    // Installer URL, marker file that exists once the loader is installed, and the launch command
+    let (installer_url, installed_marker, launch_sh, launch_bat) = match &loader {
+        Loader::Forge(version) if forge_has_run_scripts(game_version) => (
+            forge_installer_url(game_version, version),
+            "run.sh".to_string(),
+            "sh run.sh nogui".to_string(),
+            "call run.bat nogui".to_string(),
+        ),
+        // Before 1.17 the Forge installer puts a server jar next to the world instead of run scripts
+        Loader::Forge(version) => {
+            let jar = format!("forge-{}-{}.jar", game_version, version);
+            (
+                forge_installer_url(game_version, version),
+                jar.clone(),
+                format!("java $JAVA_ARGS -jar {} nogui", jar),
+                format!("java %JAVA_ARGS% -jar {} nogui", jar),
+            )
+        }
+        Loader::NeoForge(version) => (
+            format!(
+                "https://maven.neoforged.net/releases/net/neoforged/neoforge/{v}/neoforge-{v}-installer.jar",
+                v = version
+            ),
+            "run.sh".to_string(),
+            "sh run.sh nogui".to_string(),
+            "call run.bat nogui".to_string(),
+        ),
+        Loader::Fabric(version) => (
+            format!(
+                "https://meta.fabricmc.net/v2/versions/loader/{}/{}/{}/server/jar",
+                game_version, version, FABRIC_INSTALLER_VERSION
+            ),
+            "fabric-server-launch.jar".to_string(),
+            "java $JAVA_ARGS -jar fabric-server-launch.jar nogui".to_string(),
+            "java %JAVA_ARGS% -jar fabric-server-launch.jar nogui".to_string(),
+        ),
+        Loader::Unknown => (
+            String::new(),
+            "server.jar".to_string(),
+            "java $JAVA_ARGS -jar server.jar nogui".to_string(),
+            "java %JAVA_ARGS% -jar server.jar nogui".to_string(),
+        ),
+    };
+    let loader_name = manifest.base_mod_loader.as_deref().unwrap_or("unknown loader");
+
+    let install_sh = match &loader {
+        Loader::Forge(_) | Loader::NeoForge(_) => format!(
+            "if [ ! -f {marker} ]; then\n  curl -fL -o installer.jar \"{url}\" || exit 1\n  java -jar installer.jar --installServer || exit 1\n  rm -f installer.jar\nfi\n",
+            marker = installed_marker,
+            url = installer_url
+        ),
+        Loader::Fabric(_) => format!(
+            "if [ ! -f {marker} ]; then\n  curl -fL -o {marker} \"{url}\" || exit 1\nfi\n",
+            marker = installed_marker,
+            url = installer_url
+        ),
+        Loader::Unknown => "# Put the server jar for this modpack here as server.jar\n".to_string(),
+    };
+    // run.sh and run.bat are created together, the batch file checks for its own
+    let bat_marker = if installed_marker == "run.sh" { "run.bat" } else { installed_marker.as_str() };
+    let install_bat = match &loader {
+        Loader::Forge(_) | Loader::NeoForge(_) => format!(
+            "if not exist {marker} (\r\n  curl -fL -o installer.jar \"{url}\" || exit /b 1\r\n  java -jar installer.jar --installServer || exit /b 1\r\n  del installer.jar\r\n)\r\n",
+            marker = bat_marker,
+            url = installer_url
+        ),
+        Loader::Fabric(_) => format!(
+            "if not exist {marker} (\r\n  curl -fL -o {marker} \"{url}\" || exit /b 1\r\n)\r\n",
+            marker = installed_marker,
+            url = installer_url
+        ),
+        Loader::Unknown => "rem Put the server jar for this modpack here as server.jar\r\n".to_string(),
+    };
+
+    let start_sh = format!(
+        "#!/bin/sh\n# Server for Minecraft {mc} with {loader}, generated by CEMM.\n# Accept the Minecraft EULA in eula.txt before the first start.\ncd \"$(dirname \"$0\")\"\nJAVA_ARGS=\"${{JAVA_ARGS:--Xms2G -Xmx4G}}\"\n{install}{launch}\n",
+        mc = game_version,
+        loader = loader_name,
+        install = install_sh,
+        launch = launch_sh
+    );
+    let start_bat = format!(
+        "@echo off\r\nrem Server for Minecraft {mc} with {loader}, generated by CEMM.\r\nrem Accept the Minecraft EULA in eula.txt before the first start.\r\ncd /d \"%~dp0\"\r\nif \"%JAVA_ARGS%\"==\"\" set JAVA_ARGS=-Xms2G -Xmx4G\r\n{install}{launch}\r\n",
+        mc = game_version,
+        loader = loader_name,
+        install = install_bat,
+        launch = launch_bat
+    );
+
+    let sh_path = dir.join("start.sh");
+    async_fs::write(&sh_path, start_sh).await
+        .map_err(|e| CemmError::io("Failed to write", &sh_path, e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        async_fs::set_permissions(&sh_path, std::fs::Permissions::from_mode(0o755)).await
+            .map_err(|e| CemmError::io("Failed to set permissions of", &sh_path, e))?;
+    }
+    let bat_path = dir.join("start.bat");
+    async_fs::write(&bat_path, start_bat).await
+        .map_err(|e| CemmError::io("Failed to write", &bat_path, e))?;
+    Ok(())
+}
+
+/// Packs every file below `dir` into a zip at `zip_path`, written to a temp file first.
+async fn write_zip(dir: &Path, zip_path: &Path) -> Result<(), CemmError> {
+    let files = collect_files(dir).await?;
+    let dir = dir.to_path_buf();
+    let zip_path = zip_path.to_path_buf();
+
+    tokio::task::spawn_blocking(move || {
+        let tmp_path = zip_path.with_extension("zip.part");
+        let result = write_zip_file(&dir, &files, &tmp_path).and_then(|()| {
+            std::fs::rename(&tmp_path, &zip_path)
+                .map_err(|e| CemmError::io(&format!("Failed to move {} to", tmp_path.display()), &zip_path, e))
+        });
+        if result.is_err() {
+            let _ = std::fs::remove_file(&tmp_path);
+        }
+        result
+    })
+    .await
+    .map_err(|e| CemmError::validation(format!("Zip task failed: {}", e)))?
+}
+
+/// Writes `files` from `dir` into a new zip at `path`.
+fn write_zip_file(dir: &Path, files: &[PathBuf], path: &Path) -> Result<(), CemmError> {
+    let file = File::create(path)
+        .map_err(|e| CemmError::io("Failed to create", path, e))?;
+    let mut writer = zip::ZipWriter::new(file);
+    for file_path in files {
+        let name = file_path.strip_prefix(dir)
+            .map_err(|e| CemmError::validation(e.to_string()))?
+            .to_string_lossy()
+            .replace('\\', "/");
+        let mode = if name == "start.sh" { 0o755 } else { 0o644 };
+        let options = SimpleFileOptions::default().unix_permissions(mode);
+        writer.start_file(name.as_str(), options).map_err(|e| zip_error(path, e))?;
+        let content = std::fs::read(file_path).map_err(|e| CemmError::io("Failed to read", file_path, e))?;
+        writer.write_all(&content).map_err(|e| CemmError::io("Failed to write", path, e))?;
+    }
+    writer.finish().map_err(|e| zip_error(path, e))?;
+    Ok(())
+}
+
+fn zip_error(path: &Path, error: zip::result::ZipError) -> CemmError {
+    CemmError::io_message(format!("Failed to write {}: {}", path.display(), error), Some(path))
+}
@@ -0,0 +1,347 @@
+use crate::cache::link_or_copy;
+use crate::error::CemmError;
+use crate::installer::validate_path_within_base;
+use crate::transaction::{collect_files, InstallTransaction};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle, Manager};
+use tokio::fs as async_fs;
+use uuid::Uuid;
+
+/// Name of the snapshot directory inside the app data dir
+const SNAPSHOTS_DIR_NAME: &str = "snapshots";
+/// Metadata file written last, so snapshots without it are incomplete
+const METADATA_FILE: &str = "snapshot.json";
+/// Directory inside a snapshot holding the saved files
+const FILES_DIR: &str = "files";
+/// Instance folders that are captured completely
+pub const SNAPSHOT_FOLDERS: [&str; 4] = ["mods", "resourcepacks", "shaderpacks", "datapacks"];
+/// Snapshots kept per instance when install_update prunes after taking a new one
+pub const DEFAULT_SNAPSHOT_RETENTION: usize = 5;
+
+/// Summary of a snapshot, returned by list_snapshots and restore_snapshot.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/snapshot.rs (this file)
+/// - TypeScript: app/types/index.ts (SnapshotInfo interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub id: String,
+    pub modpack_path: String,
+    /// RFC 3339 timestamp
+    pub created_at: String,
+    pub file_count: usize,
+    pub total_bytes: u64,
+}
+
+/// Contents of `snapshot.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SnapshotMetadata {
+    #[serde(flatten)]
+    info: SnapshotInfo,
+    /// Folders captured completely; restoring removes anything else in them
+    folders: Vec<String>,
+    /// Saved files, relative to the instance root
+    files: Vec<String>,
+    /// Individually captured files that did not exist yet; restoring removes them
+    absent_files: Vec<String>,
+}
+
+/// Pre-install snapshots of instances, stored below the app data dir.
+///
+/// Files in the addon folders are hardlinked where possible since the installer
+/// only ever replaces them by rename and never writes into them. Other files, such
+/// as configs, are copied because they may be modified in place.
+#[derive(Debug, Clone)]
+pub struct SnapshotStore {
+    dir: PathBuf,
+}
+
+impl SnapshotStore {
+    /// Opens the snapshot store below the application's data directory.
+    pub fn for_app(app: &AppHandle) -> Result<Self, CemmError> {
+        let app_data_dir = app.path().app_data_dir()
+            .map_err(|e| CemmError::io_message(format!("Failed to resolve the app data directory: {}", e), None))?;
+        Ok(Self::new(&app_data_dir))
+    }
+
+    pub fn new(app_data_dir: &Path) -> Self {
+        Self { dir: app_data_dir.join(SNAPSHOTS_DIR_NAME) }
+    }
+
+    /// Captures the addon folders of `modpack_path` plus the given `extra_files`
+    /// (absolute paths inside the instance, which do not have to exist).
+    pub async fn create(&self, modpack_path: &Path, extra_files: &[PathBuf]) -> Result<SnapshotInfo, CemmError> {
+        let id = format!(
+            "{}-{}",
+            Utc::now().format("%Y%m%d-%H%M%S"),
+            &Uuid::new_v4().simple().to_string()[..8]
+        );
+        let snapshot_dir = self.dir.join(&id);
+
+        let result = self.write_snapshot(&id, &snapshot_dir, modpack_path, extra_files).await;
+        if result.is_err() {
+            if let Err(e) = async_fs::remove_dir_all(&snapshot_dir).await {
+                log::warn!("Failed to remove incomplete snapshot {}: {}", snapshot_dir.display(), e);
+            }
+        }
+        result
+    }
+
+    async fn write_snapshot(
+        &self,
+        id: &str,
+        snapshot_dir: &Path,
+        modpack_path: &Path,
+        extra_files: &[PathBuf],
+    ) -> Result<SnapshotInfo, CemmError> {
+        let files_dir = snapshot_dir.join(FILES_DIR);
+        let mut files = Vec::new();
+        let mut absent_files = Vec::new();
+        let mut total_bytes = 0u64;
+
+        for folder in SNAPSHOT_FOLDERS {
+            let folder_path = modpack_path.join(folder);
+            if !folder_path.is_dir() {
+                continue;
+            }
+            for path in collect_files(&folder_path).await? {
+                // Unfinished downloads may still be appended to
+                if path.extension().is_some_and(|ext| ext == "part") {
+                    continue;
+                }
+                let relative = relative_string(modpack_path, &path)?;
+                total_bytes += save_file(&path, &files_dir.join(&relative), true).await?;
+                files.push(relative);
+            }
+        }
+
+        for path in extra_files {
+            let relative = relative_string(modpack_path, path)?;
+            if in_snapshot_folder(&relative) || files.contains(&relative) || absent_files.contains(&relative) {
+                continue;
+            }
+            if async_fs::try_exists(path).await.unwrap_or(false) {
+                total_bytes += save_file(path, &files_dir.join(&relative), false).await?;
+                files.push(relative);
+            } else {
+                absent_files.push(relative);
+            }
+        }
+
+        let metadata = SnapshotMetadata {
+            info: SnapshotInfo {
+                id: id.to_string(),
+                modpack_path: modpack_path.to_string_lossy().to_string(),
+                created_at: Utc::now().to_rfc3339(),
+                file_count: files.len(),
+                total_bytes,
+            },
+            folders: SNAPSHOT_FOLDERS.iter().map(|f| f.to_string()).collect(),
+            files,
+            absent_files,
+        };
+        let json = serde_json::to_string_pretty(&metadata)
+            .map_err(|e| CemmError::validation(format!("Failed to serialize snapshot metadata: {}", e)))?;
+        let metadata_path = snapshot_dir.join(METADATA_FILE);
+        async_fs::write(&metadata_path, json).await
+            .map_err(|e| CemmError::io("Failed to write snapshot metadata", &metadata_path, e))?;
+
+        log::info!(
+            "Created snapshot {} of {} ({} files, {} bytes)",
+            id,
+            modpack_path.display(),
+            metadata.info.file_count,
+            metadata.info.total_bytes
+        );
+        Ok(metadata.info)
+    }
+
+    /// Lists complete snapshots, newest first, optionally only those of one instance.
+    pub async fn list(&self, modpack_path: Option<&Path>) -> Result<Vec<SnapshotInfo>, CemmError> {
+        let mut snapshots = Vec::new();
+        let mut entries = match async_fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(snapshots),
+            Err(e) => return Err(CemmError::io("Failed to read snapshot directory", &self.dir, e)),
+        };
+
+        while let Some(entry) = entries.next_entry().await
+            .map_err(|e| CemmError::io("Failed to read snapshot directory", &self.dir, e))?
+        {
+            let id = entry.file_name().to_string_lossy().to_string();
+            match self.read_metadata(&id).await {
+                Ok(metadata) => {
+                    let matches = match modpack_path {
+                        Some(path) => Path::new(&metadata.info.modpack_path) == path,
+                        None => true,
+                    };
+                    if matches {
+                        snapshots.push(metadata.info);
+                    }
+                }
+                Err(e) => log::debug!("Skipping snapshot {}: {}", id, e),
+            }
+        }
+
+        // Ids start with the creation time
+        snapshots.sort_by(|a, b| b.id.cmp(&a.id));
+        Ok(snapshots)
+    }
+
+    /// Returns the instance to the exact state captured in snapshot `id`.
+    ///
+    /// Runs as an install transaction, so a failed restore leaves the instance as it was.
+    pub async fn restore(&self, id: &str) -> Result<SnapshotInfo, CemmError> {
+        let metadata = self.read_metadata(id).await?;
+        let modpack_path = PathBuf::from(&metadata.info.modpack_path);
+        if !modpack_path.is_dir() {
+            return Err(CemmError::not_found(
+                format!("Modpack directory {} no longer exists", modpack_path.display()),
+                &metadata.info.modpack_path,
+            ));
+        }
+        let files_dir = self.snapshot_dir(id)?.join(FILES_DIR);
+
+        let transaction = InstallTransaction::begin(&modpack_path).await?;
+        let staged = async {
+            let mut kept: HashSet<PathBuf> = HashSet::new();
+            for relative in &metadata.files {
+                let dest = validate_path_within_base(&modpack_path, relative)?;
+                let staged = transaction.staging_path(Path::new(relative));
+                save_file(&files_dir.join(relative), &staged, in_snapshot_folder(relative)).await?;
+                kept.insert(dest);
+            }
+
+            let mut removals = Vec::new();
+            for folder in &metadata.folders {
+                let folder_path = validate_path_within_base(&modpack_path, folder)?;
+                if folder_path.is_dir() {
+                    removals.extend(collect_files(&folder_path).await?.into_iter().filter(|p| !kept.contains(p)));
+                }
+            }
+            for relative in &metadata.absent_files {
+                let dest = validate_path_within_base(&modpack_path, relative)?;
+                if async_fs::try_exists(&dest).await.unwrap_or(false) {
+                    removals.push(dest);
+                }
+            }
+            Ok::<Vec<PathBuf>, CemmError>(removals)
+        }
+        .await;
+
+        match staged {
+            Ok(removals) => transaction.commit(&removals).await?,
+            Err(e) => {
+                transaction.abort().await;
+                return Err(e);
+            }
+        }
+
+        log::info!("Restored snapshot {} to {}", id, modpack_path.display());
+        Ok(metadata.info)
+    }
+
+    pub async fn delete(&self, id: &str) -> Result<(), CemmError> {
+        let snapshot_dir = self.snapshot_dir(id)?;
+        async_fs::remove_dir_all(&snapshot_dir).await.map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => CemmError::not_found(format!("Snapshot {} not found", id), id),
+            _ => CemmError::io("Failed to delete snapshot", &snapshot_dir, e),
+        })?;
+        log::info!("Deleted snapshot {}", id);
+        Ok(())
+    }
+
+    /// Deletes all but the newest `keep` snapshots of an instance. Returns how many were deleted.
+    pub async fn prune(&self, modpack_path: &Path, keep: usize) -> Result<usize, CemmError> {
+        let snapshots = self.list(Some(modpack_path)).await?;
+        let mut deleted = 0usize;
+        for snapshot in snapshots.iter().skip(keep) {
+            match self.delete(&snapshot.id).await {
+                Ok(()) => deleted += 1,
+                Err(e) => log::warn!("{}", e),
+            }
+        }
+        Ok(deleted)
+    }
+
+    fn snapshot_dir(&self, id: &str) -> Result<PathBuf, CemmError> {
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(CemmError::validation(format!("Invalid snapshot id '{}'", id)));
+        }
+        Ok(self.dir.join(id))
+    }
+
+    async fn read_metadata(&self, id: &str) -> Result<SnapshotMetadata, CemmError> {
+        let path = self.snapshot_dir(id)?.join(METADATA_FILE);
+        let json = async_fs::read_to_string(&path).await.map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => CemmError::not_found(format!("Snapshot {} not found", id), id),
+            _ => CemmError::io("Failed to read snapshot metadata", &path, e),
+        })?;
+        serde_json::from_str(&json).map_err(|e| {
+            CemmError::parse(format!("Invalid snapshot metadata for {}: {}", id, e), path.display().to_string())
+        })
+    }
+}
+
+/// Returns `path` relative to `base` with forward slashes.
+fn relative_string(base: &Path, path: &Path) -> Result<String, CemmError> {
+    let relative = path.strip_prefix(base).map_err(|_| {
+        CemmError::path_traversal(
+            format!("{} is not inside the modpack directory", path.display()),
+            path.display().to_string(),
+        )
+    })?;
+    Ok(relative.to_string_lossy().replace('\\', "/"))
+}
+
+fn in_snapshot_folder(relative: &str) -> bool {
+    SNAPSHOT_FOLDERS.iter().any(|folder| relative.starts_with(&format!("{}/", folder)))
+}
+
+/// Saves `src` to `dest`, hardlinking when `link` is set. Returns the file size.
+async fn save_file(src: &Path, dest: &Path, link: bool) -> Result<u64, CemmError> {
+    if let Some(parent) = dest.parent() {
+        async_fs::create_dir_all(parent).await
+            .map_err(|e| CemmError::io("Failed to create directory", parent, e))?;
+    }
+    if link {
+        link_or_copy(src, dest).await?;
+    } else {
+        async_fs::copy(src, dest).await
+            .map_err(|e| CemmError::io(&format!("Failed to copy {} to", src.display()), dest, e))?;
+    }
+    async_fs::metadata(dest).await
+        .map(|m| m.len())
+        .map_err(|e| CemmError::io("Failed to read", dest, e))
+}
+
+/// Lists snapshots, newest first. With `modpack_path` only snapshots of that instance are returned.
+#[command]
+pub async fn list_snapshots(app: AppHandle, modpack_path: Option<String>) -> Result<Vec<SnapshotInfo>, CemmError> {
+    let snapshots = SnapshotStore::for_app(&app)?
+        .list(modpack_path.as_deref().map(Path::new))
+        .await?;
+    Ok(snapshots)
+}
+
+/// Restores an instance to the state captured in a snapshot.
+#[command]
+pub async fn restore_snapshot(app: AppHandle, snapshot_id: String) -> Result<SnapshotInfo, CemmError> {
+    SnapshotStore::for_app(&app)?.restore(&snapshot_id).await
+}
+
+#[command]
+pub async fn delete_snapshot(app: AppHandle, snapshot_id: String) -> Result<(), CemmError> {
+    SnapshotStore::for_app(&app)?.delete(&snapshot_id).await
+}
+
+/// Keeps only the newest `keep` snapshots of an instance. Returns how many were deleted.
+#[command]
+pub async fn prune_snapshots(app: AppHandle, modpack_path: String, keep: usize) -> Result<usize, CemmError> {
+    SnapshotStore::for_app(&app)?.prune(Path::new(&modpack_path), keep).await
+}
@@ -0,0 +1,249 @@
+use crate::error::CemmError;
+use std::path::{Path, PathBuf};
+use tokio::fs as async_fs;
+use uuid::Uuid;
+
+/// Prefix of the temporary directory new files are staged into.
+const STAGING_DIR_PREFIX: &str = ".cemm-staging-";
+/// Prefix of the directory replaced and removed files are moved into during commit.
+const BACKUP_DIR_PREFIX: &str = ".cemm-backup-";
+
+/// A single filesystem change made while committing, used to undo the commit.
+#[derive(Debug)]
+enum JournalEntry {
+    /// An existing file was moved out of the way into the backup directory
+    BackedUp { original: PathBuf, backup: PathBuf },
+    /// A staged file was moved into its final location
+    Installed { dest: PathBuf },
+}
+
+/// Stages an install inside the modpack directory and swaps it in all at once.
+///
+/// Files are written to a staging directory next to the real instance folders so
+/// the final moves are plain renames on the same volume. Nothing in the instance
+/// changes until [`InstallTransaction::commit`] runs, and a failed commit restores
+/// every file it touched.
+#[derive(Debug)]
+pub struct InstallTransaction {
+    root: PathBuf,
+    staging_dir: PathBuf,
+    backup_dir: PathBuf,
+}
+
+impl InstallTransaction {
+    /// Creates a fresh staging directory inside `root`.
+    pub async fn begin(root: &Path) -> Result<Self, CemmError> {
+        remove_stale_staging_dirs(root).await;
+
+        let id = Uuid::new_v4().simple().to_string();
+        let staging_dir = root.join(format!("{STAGING_DIR_PREFIX}{id}"));
+        let backup_dir = root.join(format!("{BACKUP_DIR_PREFIX}{id}"));
+
+        async_fs::create_dir_all(&staging_dir).await
+            .map_err(|e| CemmError::io("Failed to create staging directory", &staging_dir, e))?;
+
+        log::info!("Started install transaction in {}", staging_dir.display());
+        Ok(Self {
+            root: root.to_path_buf(),
+            staging_dir,
+            backup_dir,
+        })
+    }
+
+    /// Returns where a file that belongs at `relative` inside the instance should be staged.
+    pub fn staging_path(&self, relative: &Path) -> PathBuf {
+        self.staging_dir.join(relative)
+    }
+
+    /// Returns the staging location for an absolute destination inside the instance.
+    pub fn staging_path_for(&self, dest: &Path) -> Result<PathBuf, CemmError> {
+        let relative = dest.strip_prefix(&self.root).map_err(|_| {
+            CemmError::path_traversal(
+                format!("{} is not inside the modpack directory", dest.display()),
+                dest.display().to_string(),
+            )
+        })?;
+        Ok(self.staging_path(relative))
+    }
+
+    /// Discards everything that was staged. The instance is left untouched.
+    pub async fn abort(self) {
+        log::info!("Aborting install transaction, removing {}", self.staging_dir.display());
+        if let Err(e) = async_fs::remove_dir_all(&self.staging_dir).await {
+            log::warn!("Failed to remove staging directory {}: {}", self.staging_dir.display(), e);
+        }
+    }
+
+    /// Removes `removals` and moves every staged file into place.
+    ///
+    /// Existing files are moved into a backup directory first. If any step fails,
+    /// all changes are undone in reverse order before the error is returned.
+    pub async fn commit(self, removals: &[PathBuf]) -> Result<(), CemmError> {
+        let staged_files = collect_files(&self.staging_dir).await?;
+        log::info!(
+            "Committing install transaction: {} staged files, {} removals",
+            staged_files.len(),
+            removals.len()
+        );
+
+        let mut journal: Vec<JournalEntry> = Vec::new();
+        let result = self.apply(removals, &staged_files, &mut journal).await;
+
+        match result {
+            Ok(()) => {
+                self.cleanup().await;
+                log::info!("Install transaction committed");
+                Ok(())
+            }
+            Err(e) => {
+                log::error!("Install transaction failed, rolling back: {}", e);
+                match rollback(&mut journal).await {
+                    Ok(()) => {
+                        self.cleanup().await;
+                        Err(e.context("Install failed and all changes were rolled back"))
+                    }
+                    Err(rollback_err) => {
+                        // Keep the backup directory so nothing is lost.
+                        if let Err(remove_err) = async_fs::remove_dir_all(&self.staging_dir).await {
+                            log::warn!("Failed to remove staging directory {}: {}", self.staging_dir.display(), remove_err);
+                        }
+                        Err(e.context(&format!(
+                            "Install failed and rolling back failed too ({}), original files are kept in {}",
+                            rollback_err,
+                            self.backup_dir.display()
+                        )))
+                    }
+                }
+            }
+        }
+    }
+
+    async fn apply(
+        &self,
+        removals: &[PathBuf],
+        staged_files: &[PathBuf],
+        journal: &mut Vec<JournalEntry>,
+    ) -> Result<(), CemmError> {
+        for removal in removals {
+            if async_fs::try_exists(removal).await.unwrap_or(false) {
+                let backup = self.backup_path(removal);
+                move_file(removal, &backup).await?;
+                log::info!("Removed {}", removal.display());
+                journal.push(JournalEntry::BackedUp { original: removal.clone(), backup });
+            }
+        }
+
+        for staged in staged_files {
+            let relative = staged.strip_prefix(&self.staging_dir).map_err(|_| {
+                CemmError::path_traversal(
+                    format!("Staged file {} is outside the staging directory", staged.display()),
+                    staged.display().to_string(),
+                )
+            })?;
+            let dest = self.root.join(relative);
+
+            if async_fs::try_exists(&dest).await.unwrap_or(false) {
+                let backup = self.backup_path(&dest);
+                move_file(&dest, &backup).await?;
+                journal.push(JournalEntry::BackedUp { original: dest.clone(), backup });
+            }
+
+            move_file(staged, &dest).await?;
+            journal.push(JournalEntry::Installed { dest });
+        }
+
+        Ok(())
+    }
+
+    fn backup_path(&self, original: &Path) -> PathBuf {
+        match original.strip_prefix(&self.root) {
+            Ok(relative) => self.backup_dir.join(relative),
+            Err(_) => self.backup_dir.join(original.file_name().unwrap_or_default()),
+        }
+    }
+
+    async fn cleanup(&self) {
+        for dir in [&self.staging_dir, &self.backup_dir] {
+            if async_fs::try_exists(dir).await.unwrap_or(false) {
+                if let Err(e) = async_fs::remove_dir_all(dir).await {
+                    log::warn!("Failed to remove {}: {}", dir.display(), e);
+                }
+            }
+        }
+    }
+}
+
+/// Undoes journal entries in reverse order. Continues past failures and reports the first one.
+async fn rollback(journal: &mut Vec<JournalEntry>) -> Result<(), CemmError> {
+    let mut first_error: Option<CemmError> = None;
+
+    while let Some(entry) = journal.pop() {
+        let result = match &entry {
+            JournalEntry::Installed { dest } => async_fs::remove_file(dest).await
+                .map_err(|e| CemmError::io("Failed to remove", dest, e)),
+            JournalEntry::BackedUp { original, backup } => move_file(backup, original).await,
+        };
+        if let Err(e) = result {
+            log::error!("Rollback step failed: {}", e);
+            first_error.get_or_insert(e);
+        }
+    }
+
+    match first_error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+async fn move_file(from: &Path, to: &Path) -> Result<(), CemmError> {
+    if let Some(parent) = to.parent() {
+        async_fs::create_dir_all(parent).await
+            .map_err(|e| CemmError::io("Failed to create directory", parent, e))?;
+    }
+    async_fs::rename(from, to).await
+        .map_err(|e| CemmError::io(&format!("Failed to move {} to", from.display()), to, e))
+}
+
+/// Lists every file below `dir`, recursing into subdirectories.
+pub async fn collect_files(dir: &Path) -> Result<Vec<PathBuf>, CemmError> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        let mut entries = async_fs::read_dir(&current).await
+            .map_err(|e| CemmError::io("Failed to read directory", &current, e))?;
+        while let Some(entry) = entries.next_entry().await
+            .map_err(|e| CemmError::io("Failed to read directory", &current, e))?
+        {
+            let path = entry.path();
+            let file_type = entry.file_type().await
+                .map_err(|e| CemmError::io("Failed to read", &path, e))?;
+            if file_type.is_dir() {
+                pending.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Removes staging directories left behind by an install that was interrupted
+/// before it could commit. Backup directories are kept since they may hold the
+/// only copy of a replaced file.
+async fn remove_stale_staging_dirs(root: &Path) {
+    let Ok(mut entries) = async_fs::read_dir(root).await else {
+        return;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let name = entry.file_name();
+        if name.to_string_lossy().starts_with(STAGING_DIR_PREFIX) {
+            log::info!("Removing stale staging directory {}", entry.path().display());
+            if let Err(e) = async_fs::remove_dir_all(entry.path()).await {
+                log::warn!("Failed to remove stale staging directory {}: {}", entry.path().display(), e);
+            }
+        }
+    }
+}
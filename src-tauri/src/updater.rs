@@ -2,6 +2,12 @@ use serde::{Deserialize, Serialize};
 use reqwest;
 use std::path::PathBuf;
 use std::process::Command;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+use crate::download::{download_to_file, ProgressUpdate};
+use crate::error::CemmError;
+use crate::retry::RetryPolicy;
 
 #[derive(Debug, Deserialize)]
 struct GitHubRelease {
@@ -32,7 +38,7 @@ pub struct UpdateInfo {
 }
 
 #[tauri::command]
-pub async fn check_for_updates(repo: String) -> Result<UpdateInfo, String> {
+pub async fn check_for_updates(repo: String) -> Result<UpdateInfo, CemmError> {
     let current_version = env!("CARGO_PKG_VERSION"); // Gets version from Cargo.toml
     
     log::debug!("Checking for updates...");
@@ -50,14 +56,14 @@ pub async fn check_for_updates(repo: String) -> Result<UpdateInfo, String> {
         .header("User-Agent", "CEMM-App")
         .send()
         .await
-        .map_err(|e| format!("Failed to fetch releases: {}", e))?;
+        .map_err(|e| CemmError::network("Failed to fetch releases", e))?;
 
     if !response.status().is_success() {
-        return Err(format!("GitHub API returned status: {}", response.status()));
+        return Err(CemmError::from_response("Failed to fetch releases", response).await);
     }    let release: GitHubRelease = response
         .json()
         .await
-        .map_err(|e| format!("Failed to parse release data: {}", e))?;
+        .map_err(|e| CemmError::parse(format!("Failed to parse release data: {}", e), &url))?;
 
     log::debug!("Latest release tag: {}", release.tag_name);
     log::debug!("Is prerelease: {}", release.prerelease);
@@ -107,69 +113,69 @@ pub async fn check_for_updates(repo: String) -> Result<UpdateInfo, String> {
     })
 }
 
+/// Progress event payload for updater downloads
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdaterDownloadProgress {
+    pub bytes_done: u64,
+    pub bytes_total: Option<u64>,
+    pub bytes_per_second: u64,
+}
+
 #[tauri::command]
-pub async fn download_updater_file(download_url: String, asset_name: String) -> Result<String, String> {
+pub async fn download_updater_file(app: AppHandle, download_url: String, asset_name: String) -> Result<String, CemmError> {
     log::debug!("Starting download - URL: {}, Asset: {}", download_url, asset_name);
     let temp_dir = std::env::temp_dir();
     let file_path = temp_dir.join(&asset_name);
     
     log::debug!("Download destination: {}", file_path.display());
 
-    // Download file
+    // Stream the file to disk, resuming an earlier partial download if possible
     let client = reqwest::Client::new();
     log::debug!("Making HTTP request to: {}", download_url);
-    
-    let response = client
-        .get(&download_url)
-        .send()
-        .await
-        .map_err(|e| {
-            let error_msg = format!("Download failed: {}", e);
-            log::error!("{}", error_msg);
-            error_msg
-        })?;
 
-    if !response.status().is_success() {
-        let error_msg = format!("Download failed with status: {}", response.status());
-        log::error!("{}", error_msg);
-        return Err(error_msg);
-    }
-    
-    log::debug!("HTTP request successful, reading bytes...");
+    let started = Instant::now();
+    let mut last_emit: Option<Instant> = None;
+    let mut bytes_done = 0u64;
+    let mut bytes_total: Option<u64> = None;
 
-    let bytes = response
-        .bytes()
-        .await
-        .map_err(|e| {
-            let error_msg = format!("Failed to read download: {}", e);
-            log::error!("{}", error_msg);
-            error_msg
-        })?;
-
-    log::debug!("Downloaded {} bytes, writing to file...", bytes.len());
-
-    // Write to temp file
-    std::fs::write(&file_path, bytes)
-        .map_err(|e| {
-            let error_msg = format!("Failed to write file: {}", e);
-            log::error!("{}", error_msg);
-            error_msg
-        })?;
-
-    log::debug!("File written successfully: {}", file_path.display());
+    let policy = RetryPolicy::default();
+    let downloaded = download_to_file(&client, &download_url, &file_path, &policy, |update| {
+        match update {
+            ProgressUpdate::Length(len) => bytes_total = Some(len),
+            ProgressUpdate::Bytes(bytes) => bytes_done += bytes,
+        }
+        if last_emit.is_some_and(|t| t.elapsed() < Duration::from_millis(200)) {
+            return;
+        }
+        last_emit = Some(Instant::now());
+        let elapsed = started.elapsed().as_secs_f64();
+        let _ = app.emit("updater-download-progress", UpdaterDownloadProgress {
+            bytes_done,
+            bytes_total,
+            bytes_per_second: if elapsed > 0.0 { (bytes_done as f64 / elapsed) as u64 } else { 0 },
+        });
+    })
+    .await
+    .map_err(|e| {
+        let e = e.context("Download failed");
+        log::error!("{}", e);
+        e
+    })?;
+
+    log::debug!("File written successfully: {} ({} bytes)", file_path.display(), downloaded);
     Ok(file_path.to_string_lossy().to_string())
 }
 
 #[tauri::command]
-pub async fn install_updater_file(file_path: String) -> Result<(), String> {
+pub async fn install_updater_file(file_path: String) -> Result<(), CemmError> {
     let path = PathBuf::from(&file_path);
     
     log::debug!("Starting installation - File: {}", file_path);
     
     if !path.exists() {
-        let error_msg = "Update file not found".to_string();
-        log::error!("{}", error_msg);
-        return Err(error_msg);
+        let error = CemmError::not_found("Update file not found", file_path);
+        log::error!("{}", error);
+        return Err(error);
     }
 
     log::debug!("File exists, OS: {}", std::env::consts::OS);
@@ -184,9 +190,9 @@ pub async fn install_updater_file(file_path: String) -> Result<(), String> {
         "macos" => install_macos_update(&path),
         "linux" => install_linux_update(&path),
         _ => {
-            let error_msg = "Unsupported platform for auto-update".to_string();
-            log::error!("{}", error_msg);
-            return Err(error_msg);
+            let error = CemmError::validation("Unsupported platform for auto-update");
+            log::error!("{}", error);
+            return Err(error);
         }
     };
 
@@ -206,7 +212,7 @@ pub async fn install_updater_file(file_path: String) -> Result<(), String> {
     }
 }
 
-fn find_platform_asset(assets: &[GitHubAsset]) -> Result<&GitHubAsset, String> {
+fn find_platform_asset(assets: &[GitHubAsset]) -> Result<&GitHubAsset, CemmError> {
     let target_os = std::env::consts::OS;
     let _target_arch = std::env::consts::ARCH;
 
@@ -268,10 +274,10 @@ fn find_platform_asset(assets: &[GitHubAsset]) -> Result<&GitHubAsset, String> {
         return Ok(asset);
     }
 
-    Err("No suitable asset found for this platform".to_string())
+    Err(CemmError::not_found("No suitable asset found for this platform", std::env::consts::OS))
 }
 
-fn is_newer_version(current: &str, latest: &str) -> Result<bool, String> {
+fn is_newer_version(current: &str, latest: &str) -> Result<bool, CemmError> {
     log::debug!("Parsing versions - Current: '{}', Latest: '{}'", current, latest);
     
     // Parse semantic versions
@@ -308,7 +314,7 @@ fn is_newer_version(current: &str, latest: &str) -> Result<bool, String> {
     Ok(false) // Versions are equal
 }
 
-fn install_windows_update(path: &PathBuf) -> Result<(), String> {
+fn install_windows_update(path: &PathBuf) -> Result<(), CemmError> {
     log::debug!("Installing Windows update: {}", path.display());
     
     // For .msi files
@@ -339,7 +345,7 @@ fn install_windows_update(path: &PathBuf) -> Result<(), String> {
                         log::debug!("MSI installer started without elevation");
                         return Ok(());
                     },
-                    Err(e) => return Err(format!("Failed to start MSI installer: {}", e))
+                    Err(e) => return Err(CemmError::io("Failed to start MSI installer", path, e))
                 }
             }
         }
@@ -355,38 +361,38 @@ fn install_windows_update(path: &PathBuf) -> Result<(), String> {
                 log::debug!("EXE installer started");
                 return Ok(());
             },
-            Err(e) => return Err(format!("Failed to start EXE installer: {}", e))
+            Err(e) => return Err(CemmError::io("Failed to start EXE installer", path, e))
         }
     } else {
-        let error_msg = format!("Unsupported installer format: {:?}", path.extension());
-        log::error!("{}", error_msg);
-        return Err(error_msg);
+        let error = CemmError::validation(format!("Unsupported installer format: {:?}", path.extension()));
+        log::error!("{}", error);
+        return Err(error);
     }
 }
 
-fn install_macos_update(path: &PathBuf) -> Result<(), String> {
+fn install_macos_update(path: &PathBuf) -> Result<(), CemmError> {
     // For .dmg files, open for user to install
     Command::new("open")
         .arg(&path)
         .spawn()
-        .map_err(|e| format!("Failed to open DMG: {}", e))?;
+        .map_err(|e| CemmError::io("Failed to open", path, e))?;
     
     Ok(())
 }
 
-fn install_linux_update(path: &PathBuf) -> Result<(), String> {
+fn install_linux_update(path: &PathBuf) -> Result<(), CemmError> {
     // For AppImage, make executable and launch
     if path.extension().and_then(|s| s.to_str()) == Some("AppImage") {
         // Make executable
         Command::new("chmod")
             .args(&["+x", &path.to_string_lossy()])
             .output()
-            .map_err(|e| format!("Failed to make executable: {}", e))?;
+            .map_err(|e| CemmError::io("Failed to set permissions of", path, e))?;
         
         // Launch new version
         Command::new(&path)
             .spawn()
-            .map_err(|e| format!("Failed to launch new version: {}", e))?;
+            .map_err(|e| CemmError::io("Failed to launch", path, e))?;
     }
     
     Ok(())
@@ -0,0 +1,485 @@
+use crate::cache::DownloadCache;
+use crate::cancel::{OperationKind, Operations};
+use crate::composables::manifest::{Addon, Manifest};
+use crate::environment::{addon_runs_on, config_environment, override_runs_on, InstallSide};
+use crate::error::CemmError;
+use crate::installer::{
+    addon_categories, emit_progress, install_config_file, is_config_only, load_receipt, run_downloads,
+    validate_path_within_base, write_file, ConfigFile, ConfigTarget, DownloadContext, DownloadJob, InstallOptions,
+};
+use crate::integrity::{sha256_file, verify_addon_file};
+use crate::merge::config_base_path;
+use crate::overrides::{OverrideFileWithContent, OVERRIDES_CATEGORY};
+use crate::receipt::{ReceiptFile, ReceiptFileKind};
+use crate::retry::RetryPolicy;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tauri::{command, Manager, Window};
+use tokio::fs as async_fs;
+
+/// State of a single file of an instance compared to a manifest.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FileStatus {
+    /// The file is present with the expected content
+    Ok,
+    /// The file is not there at all
+    Missing,
+    /// The file exists but does not match the hashes in the manifest, or a config
+    /// file no longer has the content CEMM installed
+    WrongHash,
+    /// The addon is enabled in the manifest but installed as `.disabled`
+    UnexpectedlyDisabled,
+    /// The addon is disabled in the manifest but installed enabled
+    UnexpectedlyEnabled,
+    /// A file in an addon folder that the manifest does not know about
+    Extra,
+}
+
+/// A file checked by verify_instance.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/verify.rs (this file)
+/// - TypeScript: app/types/index.ts (VerifiedFile interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifiedFile {
+    pub status: FileStatus,
+    /// "mods", "resourcepacks", "shaderpacks", "datapacks", "config" or "overrides"
+    pub category: String,
+    /// Addon name, or file name for config, override and extra files
+    pub name: String,
+    /// Path the file has on disk (or should have if it is missing), relative to the modpack directory
+    pub relative_path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub addon_project_id: Option<u64>,
+}
+
+/// Result of verify_instance.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/verify.rs (this file)
+/// - TypeScript: app/types/index.ts (VerifyReport interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyReport {
+    pub files: Vec<VerifiedFile>,
+    /// Number of files whose status is not `ok`
+    pub problem_count: usize,
+}
+
+/// Options for repair_instance.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/verify.rs (this file)
+/// - TypeScript: app/types/index.ts (RepairOptions interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepairOptions {
+    /// Delete files in addon folders that are not part of the manifest (default: false)
+    #[serde(default)]
+    pub remove_extra_files: bool,
+    /// Maximum number of addon downloads running in parallel (default: 6, max: 32)
+    #[serde(default)]
+    pub max_concurrent_downloads: Option<usize>,
+    /// Retry policy for downloads (default: 3 retries with exponential backoff)
+    #[serde(default)]
+    pub retry: Option<RetryPolicy>,
+    /// Serve addons from the shared download cache (default: true)
+    #[serde(default)]
+    pub use_cache: Option<bool>,
+    /// Kind of instance being repaired, see `InstallOptions::side` (default: client)
+    #[serde(default)]
+    pub side: Option<InstallSide>,
+}
+
+/// Result of repair_instance.
+///
+/// This struct is mirrored in:
+/// - Rust: src-tauri/src/verify.rs (this file)
+/// - TypeScript: app/types/index.ts (RepairResult interface)
+///
+/// When modifying this struct, ensure all definitions remain consistent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepairResult {
+    /// The problems that were fixed, as reported before the repair
+    pub repaired: Vec<VerifiedFile>,
+    /// The state of the instance after the repair
+    pub report: VerifyReport,
+}
+
+/// A verified file together with what is needed to fix it.
+struct Finding<'a> {
+    file: VerifiedFile,
+    addon: Option<(&'a Addon, &'static str)>,
+    /// Absolute path the file should have
+    expected: PathBuf,
+}
+
+fn with_disabled_suffix(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".disabled");
+    PathBuf::from(name)
+}
+
+/// Compares the instance at `modpack_path` with `manifest`.
+///
+/// Addons, config files and override files that belong on the other side are not expected.
+/// Config files are checked against the install receipt and their merge base, see `config_status`.
+async fn find_problems<'a>(
+    modpack_path: &Path,
+    manifest: &'a Manifest,
+    side: InstallSide,
+) -> Result<Vec<Finding<'a>>, CemmError> {
+    let mut findings = Vec::new();
+
+    // Config-only manifests carry no addon lists, so every addon would look extra
+    if !is_config_only(manifest) {
+        for category in addon_categories(manifest) {
+            let mut known: HashSet<String> = HashSet::new();
+
+            for addon in category.addons.iter().filter(|a| addon_runs_on(a, side)) {
+                let folder = category.folder_for(modpack_path, addon)?;
+                // Only the category's own folder is checked for extra files
+                if folder == category.folder {
+                    known.insert(addon.file_name_on_disk.clone());
+                    known.insert(format!("{}.disabled", addon.file_name_on_disk));
+                }
+
+                let enabled_path = modpack_path.join(&folder).join(&addon.file_name_on_disk);
+                let disabled_path = with_disabled_suffix(&enabled_path);
+                let wants_disabled = addon.disabled == Some(true);
+                let (expected, other) = if wants_disabled {
+                    (disabled_path, enabled_path)
+                } else {
+                    (enabled_path, disabled_path)
+                };
+
+                let (status, actual) = if expected.exists() {
+                    match verify_addon_file(addon, &expected).await {
+                        Ok(()) => (FileStatus::Ok, &expected),
+                        Err(e) => {
+                            log::warn!("verify_instance: {}", e);
+                            (FileStatus::WrongHash, &expected)
+                        }
+                    }
+                } else if other.exists() && wants_disabled {
+                    (FileStatus::UnexpectedlyEnabled, &other)
+                } else if other.exists() {
+                    (FileStatus::UnexpectedlyDisabled, &other)
+                } else if wants_disabled {
+                    // Disabled addons are not downloaded, so not having them is fine
+                    continue;
+                } else {
+                    (FileStatus::Missing, &expected)
+                };
+
+                findings.push(Finding {
+                    file: VerifiedFile {
+                        status,
+                        category: category.folder.to_string(),
+                        name: addon.addon_name.clone(),
+                        relative_path: relative_to(modpack_path, actual),
+                        addon_project_id: Some(addon.addon_project_id),
+                    },
+                    addon: Some((addon, category.label)),
+                    expected: expected.clone(),
+                });
+            }
+
+            findings.extend(find_extra_files(modpack_path, category.folder, &known).await?);
+        }
+    }
+
+    let receipt = load_receipt(modpack_path).await;
+    for config in &manifest.config_files {
+        if !config_environment(&config.relative_path, manifest).runs_on(side) {
+            continue;
+        }
+        let relative_path = config.relative_path.replace('\\', "/");
+        let dest = validate_path_within_base(modpack_path, &relative_path)?;
+        let recorded = receipt.as_ref()
+            .and_then(|r| r.file(&relative_path))
+            .filter(|f| f.kind == ReceiptFileKind::Config);
+        let status = config_status(&dest, &config_base_path(modpack_path, &relative_path), recorded).await;
+        findings.push(Finding {
+            file: VerifiedFile {
+                status,
+                category: "config".to_string(),
+                name: config.filename.clone(),
+                relative_path,
+                addon_project_id: None,
+            },
+            addon: None,
+            expected: dest,
+        });
+    }
+
+    for file in manifest.overrides.iter().filter(|o| override_runs_on(o, side)) {
+        let relative_path = file.relative_path.replace('\\', "/");
+        let dest = validate_path_within_base(modpack_path, &relative_path)?;
+        let status = if !dest.is_file() {
+            FileStatus::Missing
+        } else {
+            match sha256_file(&dest).await {
+                Ok(hash) if hash.eq_ignore_ascii_case(&file.sha256) => FileStatus::Ok,
+                Ok(_) => FileStatus::WrongHash,
+                Err(e) => {
+                    log::warn!("verify_instance: {}", e);
+                    FileStatus::WrongHash
+                }
+            }
+        };
+        findings.push(Finding {
+            file: VerifiedFile {
+                status,
+                category: OVERRIDES_CATEGORY.to_string(),
+                name: relative_path.rsplit('/').next().unwrap_or_default().to_string(),
+                relative_path,
+                addon_project_id: None,
+            },
+            addon: None,
+            expected: dest,
+        });
+    }
+
+    Ok(findings)
+}
+
+/// Checks an installed config file against what CEMM wrote there.
+///
+/// The file is fine if it has the content recorded in the install receipt, which includes
+/// merged local changes, or the modpack's version kept at `base_path` for merging. Files
+/// with neither on record are only checked for presence.
+async fn config_status(dest: &Path, base_path: &Path, recorded: Option<&ReceiptFile>) -> FileStatus {
+    if !dest.is_file() {
+        return FileStatus::Missing;
+    }
+    let base_hash = match sha256_file(base_path).await {
+        Ok(hash) => Some(hash),
+        Err(CemmError::NotFound { .. }) => None,
+        Err(e) => {
+            log::warn!("verify_instance: {}", e);
+            None
+        }
+    };
+    if recorded.is_none() && base_hash.is_none() {
+        return FileStatus::Ok;
+    }
+
+    match sha256_file(dest).await {
+        Ok(hash) => {
+            let installed = recorded.is_some_and(|f| f.sha256.eq_ignore_ascii_case(&hash))
+                || base_hash.is_some_and(|base| base.eq_ignore_ascii_case(&hash));
+            if installed { FileStatus::Ok } else { FileStatus::WrongHash }
+        }
+        Err(e) => {
+            log::warn!("verify_instance: {}", e);
+            FileStatus::WrongHash
+        }
+    }
+}
+
+/// Lists files in an addon folder that none of the manifest's addons installed.
+async fn find_extra_files(
+    modpack_path: &Path,
+    folder: &'static str,
+    known: &HashSet<String>,
+) -> Result<Vec<Finding<'static>>, CemmError> {
+    let folder_path = modpack_path.join(folder);
+    let mut findings = Vec::new();
+    let mut entries = match async_fs::read_dir(&folder_path).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(findings),
+        Err(e) => return Err(CemmError::io("Failed to read directory", &folder_path, e)),
+    };
+
+    while let Some(entry) = entries.next_entry().await
+        .map_err(|e| CemmError::io("Failed to read directory", &folder_path, e))?
+    {
+        let is_file = entry.file_type().await.map(|t| t.is_file()).unwrap_or(false);
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        // Hidden files and leftovers of interrupted downloads are not addons
+        if !is_file || known.contains(&file_name) || file_name.starts_with('.') || file_name.ends_with(".part") {
+            continue;
+        }
+        findings.push(Finding {
+            file: VerifiedFile {
+                status: FileStatus::Extra,
+                category: folder.to_string(),
+                name: file_name.clone(),
+                relative_path: format!("{}/{}", folder, file_name),
+                addon_project_id: None,
+            },
+            addon: None,
+            expected: entry.path(),
+        });
+    }
+    Ok(findings)
+}
+
+fn relative_to(modpack_path: &Path, path: &Path) -> String {
+    path.strip_prefix(modpack_path)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+fn into_report(findings: Vec<Finding<'_>>) -> VerifyReport {
+    let files: Vec<VerifiedFile> = findings.into_iter().map(|f| f.file).collect();
+    let problem_count = files.iter().filter(|f| f.status != FileStatus::Ok).count();
+    VerifyReport { files, problem_count }
+}
+
+/// Checks every addon, config file and override file of `manifest` in the instance at `modpack_path`.
+///
+/// Addons and override files are verified against the hashes in the manifest, config files against
+/// the content CEMM installed. Files in the addon folders that belong to no addon are reported as
+/// `extra`. Nothing on disk is changed.
+#[command]
+pub async fn verify_instance(
+    modpack_path: String,
+    manifest: Manifest,
+    side: Option<InstallSide>,
+) -> Result<VerifyReport, CemmError> {
+    let findings = find_problems(Path::new(&modpack_path), &manifest, side.unwrap_or_default()).await?;
+    let report = into_report(findings);
+    log::info!(
+        "verify_instance: {} files checked, {} problems",
+        report.files.len(),
+        report.problem_count
+    );
+    Ok(report)
+}
+
+/// Fixes only the broken files of an instance.
+///
+/// Missing addons and addons with the wrong hash are downloaded again, addons with
+/// the wrong enabled state are renamed, and missing config files and config files with
+/// the wrong hash are written from `config_files` if they are given. Missing override files and override files with
+/// the wrong hash are written from `overrides` if they are given. Extra files are only deleted when
+/// `remove_extra_files` is set. Progress is reported with `install-progress` events,
+/// and the repair can be stopped with cancel_install.
+#[command]
+pub async fn repair_instance(
+    window: Window,
+    modpack_path: String,
+    manifest: Manifest,
+    config_files: Option<Vec<ConfigFile>>,
+    overrides: Option<Vec<OverrideFileWithContent>>,
+    options: Option<RepairOptions>,
+) -> Result<RepairResult, CemmError> {
+    let options = options.unwrap_or_default();
+    let config_files = config_files.unwrap_or_default();
+    let overrides = overrides.unwrap_or_default();
+    let operations = window.state::<Operations>();
+    let operation = operations.start(OperationKind::Install)?;
+    let cancel = operation.token();
+    let modpack_path = PathBuf::from(&modpack_path);
+
+    let side = options.side.unwrap_or_default();
+    let findings = find_problems(&modpack_path, &manifest, side).await?;
+    let broken: Vec<&Finding<'_>> = findings.iter().filter(|f| f.file.status != FileStatus::Ok).collect();
+    log::info!("repair_instance: {} problems found", broken.len());
+
+    let mut repaired: Vec<VerifiedFile> = Vec::new();
+    let mut jobs: Vec<DownloadJob> = Vec::new();
+    let total = broken.len();
+    let mut current = 0usize;
+
+    for finding in &broken {
+        cancel.check()?;
+        let actual = modpack_path.join(&finding.file.relative_path);
+        match (finding.file.status, finding.addon) {
+            (FileStatus::Missing | FileStatus::WrongHash, Some((addon, label))) => {
+                jobs.push(DownloadJob {
+                    addon: addon.clone(),
+                    dest: finding.expected.clone(),
+                    label,
+                });
+                repaired.push(finding.file.clone());
+                continue;
+            }
+            (FileStatus::UnexpectedlyDisabled | FileStatus::UnexpectedlyEnabled, _) => {
+                async_fs::rename(&actual, &finding.expected).await
+                    .map_err(|e| CemmError::io(&format!("Failed to rename {} to", actual.display()), &finding.expected, e))?;
+                log::info!("Renamed {} to {}", actual.display(), finding.expected.display());
+            }
+            (FileStatus::Extra, _) if options.remove_extra_files => {
+                async_fs::remove_file(&actual).await
+                    .map_err(|e| CemmError::io("Failed to remove file", &actual, e))?;
+                log::info!("Removed extra file {}", actual.display());
+            }
+            (FileStatus::Missing | FileStatus::WrongHash, None) if finding.file.category == OVERRIDES_CATEGORY => {
+                let Some(content) = overrides.iter()
+                    .find(|o| o.file.relative_path.replace('\\', "/") == finding.file.relative_path)
+                else {
+                    log::warn!("Cannot restore {}: its content was not provided", finding.file.relative_path);
+                    continue;
+                };
+                write_file(&finding.expected, &content.decode()?).await?;
+            }
+            (FileStatus::Missing | FileStatus::WrongHash, None) => {
+                let Some(config) = config_files.iter()
+                    .find(|c| c.relative_path.replace('\\', "/") == finding.file.relative_path)
+                else {
+                    log::warn!("Cannot restore {}: its content was not provided", finding.file.relative_path);
+                    continue;
+                };
+                let target = ConfigTarget {
+                    modpack_path: &modpack_path,
+                    dest: &finding.expected,
+                    write_path: &finding.expected,
+                    transaction: None,
+                };
+                // Merging would carry the broken content over, so write the modpack's version
+                install_config_file(config, target, false).await?;
+            }
+            _ => continue,
+        }
+        repaired.push(finding.file.clone());
+        current += 1;
+        emit_progress(&window, current, total, &format!("Repaired {}", finding.file.relative_path));
+    }
+
+    if !jobs.is_empty() {
+        let install_options = InstallOptions {
+            max_concurrent_downloads: options.max_concurrent_downloads,
+            retry: options.retry.clone(),
+            use_cache: options.use_cache,
+            ..Default::default()
+        };
+        let cache = if install_options.uses_cache() {
+            match DownloadCache::for_app(window.app_handle()) {
+                Ok(cache) => Some(cache),
+                Err(e) => {
+                    log::warn!("Download cache unavailable: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let ctx = DownloadContext {
+            client: Client::new(),
+            policy: install_options.retry_policy(),
+            cache,
+            cancel: cancel.clone(),
+        };
+        run_downloads(&window, &ctx, jobs, install_options.download_concurrency(), &mut current, total).await?;
+    }
+
+    let report = into_report(find_problems(&modpack_path, &manifest, side).await?);
+    emit_progress(&window, total, total, "Repair complete!");
+    log::info!(
+        "repair_instance: repaired {} files, {} problems left",
+        repaired.len(),
+        report.problem_count
+    );
+    Ok(RepairResult { repaired, report })
+}